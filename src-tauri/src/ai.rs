@@ -1,19 +1,21 @@
+use crate::control::ControlService;
 use crate::events::{Event, EventBus};
 use crate::model::{Parameter, SharedState};
+use crate::supervisor::{supervise, EventHandler, Flow};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::error::Error;
-use tokio::sync::broadcast;
 use tokio::task::JoinHandle;
-use tracing::{debug, info, warn};
+use tracing::{debug, error, info};
 
 /// AI Service for generating parameter values with OpenAI
+#[derive(Clone)]
 pub struct AIService {
     state: SharedState,
     event_bus: EventBus,
+    control: ControlService,
     client: Client,
-    event_receiver: broadcast::Receiver<Event>,
 }
 
 #[derive(Serialize)]
@@ -59,107 +61,109 @@ struct AIResponse {
 
 impl AIService {
     /// Create a new AI service
-    pub fn new(state: SharedState, event_bus: EventBus) -> Self {
-        let event_receiver = event_bus.subscribe();
+    pub fn new(state: SharedState, event_bus: EventBus, control: ControlService) -> Self {
         let client = Client::new();
 
         Self {
             state,
             event_bus,
+            control,
             client,
-            event_receiver,
         }
     }
 
-    /// Start the AI service
-    pub fn start(mut self) -> JoinHandle<()> {
-        tokio::spawn(async move {
-            while let Ok(event) = self.event_receiver.recv().await {
-                if let Event::GenerateAIValues { bank_id, snap_id } = event {
-                    // Try to get API key from environment variable first
-                    let api_key = match env::var("OPENAI_API_KEY") {
-                        Ok(key) => Some(key),
-                        Err(_) => {
-                            // Fall back to stored key in project if env var not found
-                            let state_guard = self.state.read().unwrap();
-                            state_guard.project.openai_api_key.clone()
+    /// Start the AI service as a supervised event handler
+    pub fn start(self) -> JoinHandle<()> {
+        let event_bus = self.event_bus.clone();
+        supervise(&event_bus, "ai-service", move || {
+            AIService::new(self.state.clone(), self.event_bus.clone(), self.control.clone())
+        })
+    }
+
+    /// Generate values for a snap and publish the outcome
+    async fn generate_for_snap(&self, bank_id: usize, snap_id: usize) {
+        // Try to get API key from environment variable first
+        let api_key = match env::var("OPENAI_API_KEY") {
+            Ok(key) => Some(key),
+            Err(_) => {
+                // Fall back to stored key in project if env var not found
+                let state_guard = self.state.read().unwrap();
+                state_guard.project.openai_api_key.clone()
+            }
+        };
+
+        // Check if we have an API key
+        if let Some(api_key) = api_key {
+            // Get the prompt information
+            let prompt_info = {
+                let state_guard = self.state.read().unwrap();
+                let project = &state_guard.project;
+                project.banks.get(bank_id).and_then(|bank| {
+                    let snap = bank.snaps.get(snap_id)?;
+                    Some((
+                        project.project_name.clone(),
+                        bank.name.clone(),
+                        snap.name.clone(),
+                        snap.description.clone(),
+                        project.parameters.clone(),
+                    ))
+                })
+            };
+            let Some((project_name, bank_name, snap_name, snap_description, parameters)) = prompt_info else {
+                error!("Cannot generate AI values for missing snap {} of bank {}", snap_id, bank_id);
+                let _ = self.event_bus.publish(Event::AIGenerationFailed {
+                    bank_id,
+                    snap_id,
+                    error: "Snap not found".to_string(),
+                });
+                return;
+            };
+
+            // Build a comprehensive prompt with all parameters and context
+            let prompt = self.build_audio_reasoning_prompt(
+                &project_name,
+                &bank_name,
+                &snap_name,
+                &snap_description,
+                &parameters,
+            );
+
+            // Call the OpenAI API
+            match self.generate_values(&api_key, &prompt, &parameters).await {
+                Ok(values) => {
+                    // Write the values as an edit, so a journal replay gets them without asking OpenAI again
+                    match self.control.update_snap(bank_id, snap_id, None, None, Some(values.clone())) {
+                        Ok(()) => {
+                            let _ = self.event_bus.publish(Event::AIGenerationCompleted {
+                                bank_id,
+                                snap_id,
+                                values,
+                            });
                         }
-                    };
-
-                    // Check if we have an API key
-                    if let Some(api_key) = api_key {
-                        // Get the prompt information
-                        let (project_name, bank_name, snap_name, snap_description, parameters) = {
-                            let state_guard = self.state.read().unwrap();
-                            let project = &state_guard.project;
-                            let bank = &project.banks[bank_id];
-                            let snap = &bank.snaps[snap_id];
-
-                            (
-                                project.project_name.clone(),
-                                bank.name.clone(),
-                                snap.name.clone(),
-                                snap.description.clone(),
-                                project.parameters.clone(),
-                            )
-                        };
-
-                        // Build a comprehensive prompt with all parameters and context
-                        let prompt = self.build_audio_reasoning_prompt(
-                            &project_name,
-                            &bank_name,
-                            &snap_name,
-                            &snap_description,
-                            &parameters,
-                        );
-
-                        // Call the OpenAI API
-                        match self.generate_values(&api_key, &prompt, &parameters).await {
-                            Ok(values) => {
-                                // Update the snap with the generated values
-                                {
-                                    let mut state_guard = self.state.write().unwrap();
-                                    let bank = &mut state_guard.project.banks[bank_id];
-                                    let snap = &mut bank.snaps[snap_id];
-
-                                    // Ensure the values array is large enough
-                                    if snap.values.len() < values.len() {
-                                        snap.values.resize(values.len(), 64);
-                                    }
-
-                                    // Overwrite the existing values with the generated ones
-                                    for (i, value) in values.iter().enumerate() {
-                                        snap.values[i] = *value;
-                                    }
-                                }
-
-                                // Send the event that generation completed
-                                let _ = self.event_bus.publish(Event::AIGenerationCompleted {
-                                    bank_id,
-                                    snap_id,
-                                    values,
-                                });
-                            }
-                            Err(e) => {
-                                // Send failure event
-                                let _ = self.event_bus.publish(Event::AIGenerationFailed {
-                                    bank_id,
-                                    snap_id,
-                                    error: e.to_string(),
-                                });
-                            }
+                        // The snap may have been removed while the request ran
+                        Err(e) => {
+                            error!("Cannot store AI values for snap {} of bank {}: {}", snap_id, bank_id, e);
+                            let _ = self.event_bus.publish(Event::AIGenerationFailed { bank_id, snap_id, error: e });
                         }
-                    } else {
-                        // No API key, send failure event
-                        let _ = self.event_bus.publish(Event::AIGenerationFailed {
-                            bank_id,
-                            snap_id,
-                            error: "No OpenAI API key provided. Set OPENAI_API_KEY environment variable or in application settings.".to_string(),
-                        });
                     }
                 }
+                Err(e) => {
+                    // Send failure event
+                    let _ = self.event_bus.publish(Event::AIGenerationFailed {
+                        bank_id,
+                        snap_id,
+                        error: e.to_string(),
+                    });
+                }
             }
-        })
+        } else {
+            // No API key, send failure event
+            let _ = self.event_bus.publish(Event::AIGenerationFailed {
+                bank_id,
+                snap_id,
+                error: "No OpenAI API key provided. Set OPENAI_API_KEY environment variable or in application settings.".to_string(),
+            });
+        }
     }
 
     /// Build a prompt focused on reasoning about audio parameters and their relationship to desired sound
@@ -380,4 +384,41 @@ fn extract_json_from_response(content: &str) -> String {
 
     // Just return the entire content if no code blocks found
     content.trim().to_string()
-}
\ No newline at end of file
+}
+
+impl EventHandler for AIService {
+    async fn handle(&mut self, event: Event) -> Flow {
+        if let Event::GenerateAIValues { bank_id, snap_id } = event {
+            // The request takes seconds; waiting for it here would leave the subscription lagging
+            let service = self.clone();
+            tokio::spawn(async move { service.generate_for_snap(bank_id, snap_id).await });
+        }
+        Flow::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::new_shared_state;
+
+    #[tokio::test]
+    async fn missing_snaps_fail_without_a_request() {
+        let state = new_shared_state();
+        state.write().unwrap().project.openai_api_key = Some("unused".to_string());
+        let bus = EventBus::new(64, "test");
+        let mut receiver = bus.subscribe();
+        let control = ControlService::new(state.clone(), bus.clone(), None, None);
+        let service = AIService::new(state, bus, control);
+
+        service.generate_for_snap(3, 0).await;
+        service.generate_for_snap(0, 9).await;
+
+        for _ in 0..2 {
+            match receiver.try_recv() {
+                Ok(Event::AIGenerationFailed { error, .. }) => assert_eq!(error, "Snap not found"),
+                other => panic!("Expected a failed generation, got {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,556 @@
+// src-tauri/src/api.rs
+use crate::control::{parse_curve, ControlService};
+use crate::events::{EventBus, EventSubscriber, LaunchQuantize, LoopMode, MorphDuration, MorphLoop};
+use crate::runtime::{RuntimeMessage, RuntimeTracker};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::net::IpAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// HTTP and WebSocket API settings, stored with the app settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ApiSettings {
+    /// Serve the API
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Address to listen on; only this machine can connect unless changed
+    #[serde(default = "default_bind")]
+    pub bind: String,
+
+    /// TCP port to listen on
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    /// Token clients must present, as a bearer token or a `token` query parameter
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// Web page origins, e.g. `http://192.168.1.20:8080`, allowed to call the API
+    /// when no token is set; pages served by other sites are refused
+    #[serde(default)]
+    pub origins: Vec<String>,
+}
+
+fn default_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    9100
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_bind(),
+            port: default_port(),
+            token: None,
+            origins: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    control: ControlService,
+    event_bus: EventBus,
+    runtime: RuntimeTracker,
+    token: Option<Arc<str>>,
+    origins: Arc<[String]>,
+    bind: Arc<str>,
+}
+
+/// Error response with a JSON body
+struct ApiError(StatusCode, String);
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError(StatusCode::BAD_REQUEST, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+/// Start serving the API.
+///
+/// Routes, all under `/api`:
+/// - `GET /project`, `GET /state`, `GET /link`
+/// - `POST /bank`, `POST /snap`, `POST /morph`, `POST /parameter`, `POST /tempo`, `POST /quantum`, `POST /transport`
+/// - `GET /events`: WebSocket stream of every bus event as JSON
+/// - `GET /runtime`: runtime state snapshot; `GET /runtime/deltas?since=<version>`: what changed since
+/// - `GET /runtime/stream?since=<version>`: WebSocket stream of runtime state deltas
+pub fn start(
+    settings: &ApiSettings,
+    control: ControlService,
+    event_bus: EventBus,
+    runtime: RuntimeTracker,
+) -> Result<JoinHandle<()>, Box<dyn Error>> {
+    let listener = std::net::TcpListener::bind((settings.bind.as_str(), settings.port))?;
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+    info!("Control API listening on http://{}", listener.local_addr()?);
+
+    let loopback = settings
+        .bind
+        .parse::<IpAddr>()
+        .map_or(settings.bind == "localhost", |ip| ip.is_loopback());
+    if !loopback && settings.token.is_none() {
+        warn!("Control API is reachable from the network without a token");
+    }
+
+    let state = ApiState {
+        control,
+        event_bus,
+        runtime,
+        token: settings.token.as_deref().map(Arc::from),
+        origins: settings.origins.clone().into(),
+        bind: Arc::from(settings.bind.as_str()),
+    };
+
+    let app = Router::new()
+        .route("/api/project", get(get_project))
+        .route("/api/state", get(get_state))
+        .route("/api/link", get(get_link))
+        .route("/api/bank", post(select_bank))
+        .route("/api/snap", post(select_snap))
+        .route("/api/morph", post(start_morph))
+        .route("/api/chain", post(start_chain))
+        .route("/api/loop", post(start_loop))
+        .route("/api/morph/stop", post(stop_morph))
+        .route("/api/morph/manual", post(start_manual_morph))
+        .route("/api/morph/position", post(set_morph_position))
+        .route("/api/parameter", post(set_parameter))
+        .route("/api/tempo", post(set_tempo))
+        .route("/api/quantum", post(set_quantum))
+        .route("/api/transport", post(set_transport))
+        .route("/api/events", get(events))
+        .route("/api/runtime", get(get_runtime))
+        .route("/api/runtime/deltas", get(get_runtime_deltas))
+        .route("/api/runtime/stream", get(runtime_stream))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state);
+
+    Ok(tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            error!("Control API stopped: {}", e);
+        }
+    }))
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Reject requests without the configured token. Without a token, reject requests
+/// from web pages of other origins: a browser lets any site open a WebSocket to
+/// localhost, so the event stream would otherwise be readable by every page. The
+/// `Host` must name this machine too, or a page could rebind its own domain to it.
+async fn require_token(
+    State(state): State<ApiState>,
+    Query(query): Query<TokenQuery>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(expected) = &state.token else {
+        return if request_allowed(&headers, &state.bind, &state.origins) {
+            next.run(request).await
+        } else {
+            ApiError(StatusCode::FORBIDDEN, "Origin not allowed".to_string()).into_response()
+        };
+    };
+
+    // Browsers cannot set headers on WebSocket connections, so the query parameter is accepted too
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string)
+        .or(query.token);
+
+    match presented {
+        Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => next.run(request).await,
+        _ => ApiError(StatusCode::UNAUTHORIZED, "Missing or invalid token".to_string()).into_response(),
+    }
+}
+
+/// Whether a request without a token may go ahead: from one of the configured
+/// origins, or addressed to this machine by a loopback name or the bind address
+/// and either without an `Origin` (tools rather than web pages) or from the API's own.
+fn request_allowed(headers: &HeaderMap, bind: &str, allowed: &[String]) -> bool {
+    let get = |name: header::HeaderName| headers.get(name).map(|value| value.to_str().unwrap_or_default());
+    let (host, origin) = (get(header::HOST), get(header::ORIGIN));
+
+    if origin.is_some_and(|origin| allowed.iter().any(|allowed| allowed.trim_end_matches('/') == origin)) {
+        return true;
+    }
+
+    let Some(host) = host.filter(|host| host_is_local(host, bind)) else {
+        return false;
+    };
+    origin.is_none_or(|origin| origin.split_once("://").map(|(_, origin_host)| origin_host) == Some(host))
+}
+
+/// Whether a `Host` header names a loopback address or the address the API is bound to
+fn host_is_local(host: &str, bind: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+        || name.eq_ignore_ascii_case(bind)
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn get_project(State(state): State<ApiState>) -> impl IntoResponse {
+    Json(state.control.project())
+}
+
+async fn get_state(State(state): State<ApiState>) -> impl IntoResponse {
+    Json(state.control.selection())
+}
+
+async fn get_link(State(state): State<ApiState>) -> ApiResult<impl IntoResponse> {
+    Ok(Json(state.control.link_status().await?))
+}
+
+#[derive(Deserialize)]
+struct BankRequest {
+    bank_id: usize,
+}
+
+async fn select_bank(State(state): State<ApiState>, Json(req): Json<BankRequest>) -> ApiResult<StatusCode> {
+    state.control.select_bank(req.bank_id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct SnapRequest {
+    /// Defaults to the current bank
+    bank_id: Option<usize>,
+    snap_id: usize,
+    /// Defaults to the bank's recall setting
+    quantize: Option<LaunchQuantize>,
+}
+
+async fn select_snap(State(state): State<ApiState>, Json(req): Json<SnapRequest>) -> ApiResult<StatusCode> {
+    let bank_id = req.bank_id.unwrap_or_else(|| state.control.current_bank());
+    state.control.launch_snap(bank_id, req.snap_id, req.quantize)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct MorphRequest {
+    /// Defaults to the current snap
+    from_snap: Option<usize>,
+    to_snap: usize,
+    /// Such as `4bars`, `3/4bar`, `2beats` or `500ms`; defaults to the duration picked on the controller
+    duration: Option<String>,
+    /// Used when `duration` is missing
+    duration_bars: Option<f64>,
+    curve: Option<String>,
+    /// Defaults to the bank's morph setting
+    quantize: Option<LaunchQuantize>,
+}
+
+impl MorphRequest {
+    fn duration(&self) -> Result<Option<MorphDuration>, String> {
+        match (&self.duration, self.duration_bars) {
+            (Some(duration), _) => duration.parse().map(Some),
+            (None, Some(bars)) => Ok(Some(MorphDuration::Bars(bars))),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+async fn start_morph(State(state): State<ApiState>, Json(req): Json<MorphRequest>) -> ApiResult<StatusCode> {
+    let curve = parse_curve(req.curve.as_deref().unwrap_or("linear"));
+    let quantize = req.quantize;
+    let duration = req.duration()?;
+
+    match req.from_snap {
+        Some(from_snap) => {
+            let duration = duration
+                .unwrap_or_else(|| MorphDuration::Bars(state.control.selection().morph_duration as f64));
+            state
+                .control
+                .start_morph(from_snap, req.to_snap, duration, curve, quantize)?
+        }
+        None => state
+            .control
+            .morph_to(req.to_snap, duration, curve, quantize)?,
+    }
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Deserialize)]
+struct LoopRequest {
+    to_snap: usize,
+    /// Length of one pass, such as `2bars` or `750ms`
+    period: String,
+    mode: LoopMode,
+    repeats: Option<u32>,
+    curve: Option<String>,
+    quantize: Option<LaunchQuantize>,
+}
+
+async fn start_loop(State(state): State<ApiState>, Json(req): Json<LoopRequest>) -> ApiResult<StatusCode> {
+    let looping = MorphLoop {
+        mode: req.mode,
+        period: req.period.parse::<MorphDuration>()?,
+        repeats: req.repeats,
+    };
+    let curve = parse_curve(req.curve.as_deref().unwrap_or("linear"));
+    state
+        .control
+        .loop_to(req.to_snap, looping, curve, req.quantize)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn stop_morph(State(state): State<ApiState>) -> ApiResult<StatusCode> {
+    state.control.stop_morph()?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Deserialize)]
+struct ManualMorphRequest {
+    to_snap: usize,
+    curve: Option<String>,
+}
+
+async fn start_manual_morph(
+    State(state): State<ApiState>,
+    Json(req): Json<ManualMorphRequest>,
+) -> ApiResult<StatusCode> {
+    let curve = parse_curve(req.curve.as_deref().unwrap_or("linear"));
+    state.control.start_manual_morph(req.to_snap, curve)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Deserialize)]
+struct PositionRequest {
+    /// From 0 (source snap) to 1 (target snap)
+    position: f64,
+}
+
+async fn set_morph_position(State(state): State<ApiState>, Json(req): Json<PositionRequest>) -> ApiResult<StatusCode> {
+    state.control.set_morph_position(req.position)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Deserialize)]
+struct ChainRequest {
+    chain_id: String,
+    quantize: Option<LaunchQuantize>,
+}
+
+async fn start_chain(State(state): State<ApiState>, Json(req): Json<ChainRequest>) -> ApiResult<StatusCode> {
+    state.control.start_chain(&req.chain_id, req.quantize)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(Deserialize)]
+struct ParameterRequest {
+    param_id: usize,
+    value: u8,
+}
+
+async fn set_parameter(State(state): State<ApiState>, Json(req): Json<ParameterRequest>) -> ApiResult<StatusCode> {
+    state.control.set_parameter(req.param_id, req.value)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct TempoRequest {
+    tempo: f64,
+}
+
+async fn set_tempo(State(state): State<ApiState>, Json(req): Json<TempoRequest>) -> ApiResult<StatusCode> {
+    state.control.set_tempo(req.tempo).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct QuantumRequest {
+    quantum: f64,
+}
+
+async fn set_quantum(State(state): State<ApiState>, Json(req): Json<QuantumRequest>) -> ApiResult<StatusCode> {
+    state.control.set_quantum(req.quantum).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct TransportRequest {
+    playing: bool,
+}
+
+async fn set_transport(State(state): State<ApiState>, Json(req): Json<TransportRequest>) -> ApiResult<StatusCode> {
+    state.control.set_transport(req.playing).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn events(State(state): State<ApiState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| stream_events(socket, state.event_bus))
+}
+
+/// Mirror the bus to a WebSocket client, the same events the UI receives
+async fn stream_events(mut socket: WebSocket, event_bus: EventBus) {
+    let mut subscriber = EventSubscriber::new(&event_bus, "api-websocket");
+
+    loop {
+        tokio::select! {
+            received = subscriber.recv() => {
+                let text = match received {
+                    Ok(event) => match serde_json::to_string(&event) {
+                        Ok(json) => json,
+                        Err(_) => continue,
+                    },
+                    // Tell the client to refetch state instead of silently missing events
+                    Err(RecvError::Lagged(missed)) => {
+                        serde_json::json!({ "Resync": { "missed": missed } }).to_string()
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Clients only listen on this socket; anything else they send is ignored
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SinceQuery {
+    since: Option<u64>,
+}
+
+async fn get_runtime(State(state): State<ApiState>) -> impl IntoResponse {
+    Json(state.runtime.snapshot())
+}
+
+/// The deltas after `since`, or a snapshot when they are no longer kept
+async fn get_runtime_deltas(State(state): State<ApiState>, Query(query): Query<SinceQuery>) -> impl IntoResponse {
+    Json(state.runtime.resume(query.since))
+}
+
+async fn runtime_stream(
+    State(state): State<ApiState>,
+    Query(query): Query<SinceQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| stream_runtime(socket, state.runtime, query.since))
+}
+
+/// Bring a client up to date, then forward every delta as it happens
+async fn stream_runtime(mut socket: WebSocket, runtime: RuntimeTracker, since: Option<u64>) {
+    // Subscribe before catching up so nothing falls between the two
+    let mut deltas = runtime.subscribe();
+    let mut version = 0;
+
+    for message in runtime.resume(since) {
+        version = message.version();
+        if !send_runtime(&mut socket, &message).await {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            received = deltas.recv() => {
+                let message = match received {
+                    // Already covered by the catch-up
+                    Ok(delta) if delta.version <= version => continue,
+                    Ok(delta) => RuntimeMessage::Delta(delta),
+                    // Start the client over rather than leave a gap in its versions
+                    Err(RecvError::Lagged(_)) => RuntimeMessage::Snapshot(Box::new(runtime.snapshot())),
+                    Err(RecvError::Closed) => break,
+                };
+                version = message.version();
+
+                if !send_runtime(&mut socket, &message).await {
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+async fn send_runtime(socket: &mut WebSocket, message: &RuntimeMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(json) => socket.send(Message::Text(json)).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn headers(host: &str, origin: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, HeaderValue::from_str(host).unwrap());
+        if let Some(origin) = origin {
+            headers.insert(header::ORIGIN, HeaderValue::from_str(origin).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn local_requests_are_allowed_without_a_token() {
+        let none: &[String] = &[];
+        assert!(request_allowed(&headers("127.0.0.1:9100", None), "127.0.0.1", none));
+        assert!(request_allowed(&headers("localhost:9100", Some("http://localhost:9100")), "127.0.0.1", none));
+        assert!(request_allowed(&headers("[::1]:9100", None), "127.0.0.1", none));
+        assert!(request_allowed(&headers("192.168.1.5:9100", None), "192.168.1.5", none));
+    }
+
+    #[test]
+    fn rebound_and_foreign_pages_are_refused() {
+        let none: &[String] = &[];
+        // A page whose domain now resolves to this machine sends matching Host and Origin
+        let rebound = headers("evil.example:9100", Some("http://evil.example:9100"));
+        assert!(!request_allowed(&rebound, "127.0.0.1", none));
+        assert!(!request_allowed(&headers("evil.example:9100", None), "127.0.0.1", none));
+        assert!(!request_allowed(&headers("127.0.0.1:9100", Some("https://evil.example")), "127.0.0.1", none));
+
+        let allowed = ["http://192.168.1.20:8080/".to_string()];
+        let listed = headers("192.168.1.5:9100", Some("http://192.168.1.20:8080"));
+        assert!(request_allowed(&listed, "0.0.0.0", &allowed));
+    }
+}
@@ -14,14 +14,17 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // Subscribe to events to see what comes from the Launchpad X
     let event_bus_clone = event_bus.clone();
-    let event_receiver = event_bus.subscribe();
+    let event_receiver = event_bus.subscribe_realtime();
 
     // Spawn a thread to handle events
     thread::spawn(move || {
         let mut rx = event_receiver;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("Failed to create event runtime");
 
         loop {
-            match rx.blocking_recv() {
+            match runtime.block_on(rx.recv()) {
                 Ok(event) => {
                     println!("Received event: {:?}", event);
 
@@ -0,0 +1,57 @@
+use snapblaster::mcp::McpSettings;
+use std::error::Error;
+use tokio::io::{self, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const USAGE: &str = "Usage: mcp_bridge [port]
+
+Connects an MCP client that speaks stdio to the MCP server of a running
+Snap-Blaster, which listens on 127.0.0.1. Enable the server in the settings first.
+If the server has a token, pass it in the SNAPBLASTER_MCP_TOKEN environment variable.";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let port = match std::env::args().nth(1) {
+        Some(arg) if arg == "--help" || arg == "-h" => {
+            eprintln!("{}", USAGE);
+            return Ok(());
+        }
+        Some(arg) => arg.parse::<u16>().map_err(|_| format!("Invalid port: {}\n\n{}", arg, USAGE))?,
+        None => McpSettings::default().port,
+    };
+
+    // stdout belongs to the protocol, so diagnostics go to stderr
+    let stream = TcpStream::connect(("127.0.0.1", port))
+        .await
+        .map_err(|e| format!("Could not reach Snap-Blaster on port {}: {}", port, e))?;
+    let (mut from_server, mut to_server) = stream.into_split();
+
+    // The token goes first, before anything the client sends
+    if let Ok(token) = std::env::var("SNAPBLASTER_MCP_TOKEN") {
+        let mut handshake = serde_json::to_vec(&serde_json::json!({ "token": token }))?;
+        handshake.push(b'\n');
+        to_server.write_all(&handshake).await?;
+    }
+
+    let upstream = async {
+        io::copy(&mut io::stdin(), &mut to_server).await?;
+        // The client closed stdin; let the server finish and close its side
+        to_server.shutdown().await
+    };
+    let downstream = async {
+        let mut stdout = io::stdout();
+        io::copy(&mut from_server, &mut stdout).await?;
+        stdout.flush().await
+    };
+
+    // The server closing ends the session; stdin closing waits for the last replies
+    tokio::pin!(downstream);
+    tokio::select! {
+        result = upstream => {
+            result?;
+            downstream.await?;
+        }
+        result = &mut downstream => result?,
+    }
+    Ok(())
+}
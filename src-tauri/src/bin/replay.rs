@@ -0,0 +1,187 @@
+use snapblaster::control::ControlService;
+use snapblaster::events::{Event, EventBus, EventSubscriber};
+use snapblaster::journal::{self, JournalRecorder, ReplaySpeed};
+use snapblaster::launch::Launcher;
+use snapblaster::midi::manager::MidiManager;
+use snapblaster::model::new_shared_state;
+use snapblaster::morph::MorphEngine;
+use snapblaster::storage::ProjectStorage;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{self, Instant};
+use tracing::warn;
+
+const USAGE: &str = "Usage: replay <journal.jsonl> <project> [--fast] [--raw] [--out <journal.jsonl>]
+
+Feeds a recorded event journal back into an event bus with the given project loaded.
+
+  --fast    run on a clock that skips ahead whenever the app is idle instead of
+            waiting in real time; morphs take the same steps as in real time
+  --raw     publish every recorded event and run no engines, instead of replaying
+            only controller, Link and command input through the pad handler,
+            launcher and morph engine
+  --out     record the replayed session to a new journal for comparison";
+
+/// How long to wait for a morph started by the replay to finish
+const MORPH_SETTLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+struct Options {
+    journal: PathBuf,
+    project: PathBuf,
+    speed: ReplaySpeed,
+    raw: bool,
+    out: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut positional = Vec::new();
+    let mut speed = ReplaySpeed::RealTime;
+    let mut raw = false;
+    let mut out = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fast" => speed = ReplaySpeed::Fast,
+            "--raw" => raw = true,
+            "--out" => out = Some(PathBuf::from(args.next().ok_or("--out needs a path")?)),
+            "-h" | "--help" => return Err(String::new()),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    match <[PathBuf; 2]>::try_from(positional) {
+        Ok([journal, project]) => Ok(Options {
+            journal,
+            project,
+            speed,
+            raw,
+            out,
+        }),
+        Err(_) => Err("Expected a journal and a project file".to_string()),
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    tracing_subscriber::fmt::init();
+
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(message) => {
+            if !message.is_empty() {
+                eprintln!("{}\n", message);
+            }
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    options.speed.runtime()?.block_on(run(options))
+}
+
+async fn run(options: Options) -> Result<(), Box<dyn Error>> {
+    let entries = journal::read_journal(&options.journal)?;
+    println!("Loaded {} events from {:?}", entries.len(), options.journal);
+
+    // Opened, imported and merged files are not in the journal, so what follows them can drift
+    let unreplayable = entries
+        .iter()
+        .skip_while(|entry| !journal::is_input(&entry.event))
+        .filter(|entry| journal::replaces_project(&entry.event))
+        .count();
+    if unreplayable > 0 {
+        warn!(
+            "The journal opens, creates, imports or merges a project {} times; those files are not replayed",
+            unreplayable
+        );
+    }
+
+    // Large enough that a fast replay never laps the slowest handler
+    let state = new_shared_state();
+    let event_bus = EventBus::new(10_000, "replay");
+
+    let mut handles = Vec::new();
+    if let Some(out) = &options.out {
+        handles.push(JournalRecorder::start(&event_bus, out)?);
+    }
+
+    // Print everything that goes through the bus, including what the engines derive
+    let mut printer = EventSubscriber::new(&event_bus, "replay-printer");
+    let started = Instant::now();
+    handles.push(tokio::spawn(async move {
+        loop {
+            match printer.recv().await {
+                Ok(event) => {
+                    println!("{:>10.3}s  {}", started.elapsed().as_secs_f64(), event);
+                    if matches!(event, Event::Shutdown) {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(missed)) => warn!("Printer fell behind, {} events not shown", missed),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    }));
+
+    ProjectStorage::new(state.clone(), event_bus.clone()).load_project(&options.project)?;
+
+    if !options.raw {
+        // The same processing path as the app, minus hardware and Link
+        let midi_manager = Arc::new(MidiManager::new(event_bus.clone(), Some(state.clone())));
+        handles.push(midi_manager.start_pad_handler());
+        handles.push(MorphEngine::new(state.clone(), event_bus.clone(), None).start());
+
+        // Recorded commands are performed again; the replayed event itself is already on the bus
+        let control = ControlService::new(state.clone(), event_bus.clone(), Some(midi_manager), None).internal();
+        handles.push(Launcher::new(control.clone(), state.clone(), event_bus.clone(), None).start());
+
+        let mut commands = EventSubscriber::new(&event_bus, "replay-commands");
+        handles.push(tokio::spawn(async move {
+            loop {
+                match commands.recv().await {
+                    Ok(Event::CommandIssued { command }) => {
+                        if let Err(e) = control.perform(*command) {
+                            println!("Replayed command failed: {}", e);
+                        }
+                    }
+                    Ok(Event::Shutdown) | Err(RecvError::Closed) => break,
+                    Ok(_) => {}
+                    // The rest of the journal still applies, but the final state may differ
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("Command replay fell behind, {} events dropped", missed);
+                    }
+                }
+            }
+        }));
+    }
+
+    let published = if options.raw {
+        journal::replay(&entries, &event_bus, |event| !matches!(event, Event::Shutdown)).await
+    } else {
+        journal::replay(&entries, &event_bus, journal::is_input).await
+    };
+    println!("Replayed {} events", published);
+
+    // Let a morph started by the last events run to completion
+    let deadline = Instant::now() + MORPH_SETTLE_TIMEOUT;
+    while state.read().unwrap().active_morph.is_some() && Instant::now() < deadline {
+        time::sleep(Duration::from_millis(50)).await;
+    }
+
+    let _ = event_bus.publish(Event::Shutdown);
+    for handle in handles {
+        let _ = time::timeout(Duration::from_secs(1), handle).await;
+    }
+
+    let state_guard = state.read().unwrap();
+    println!(
+        "Final state: bank {}, snap {}",
+        state_guard.current_bank, state_guard.current_snap
+    );
+
+    Ok(())
+}
@@ -0,0 +1,1309 @@
+// src-tauri/src/control.rs
+use crate::curves::{builtin_curves, curve_by_id, CurvePoint, ResolvedCurve, UserCurve};
+use crate::events::{Event, EventBus, LaunchQuantize, MorphCurve, MorphDuration, MorphLoop};
+use crate::link::{LinkStatus, LinkSynchronizer};
+use crate::midi::manager::MidiManager;
+use crate::model::{
+    InterruptPolicy, LaunchQuantization, ManualControl, MorphChain, MorphTiming, Parameter, ParameterMotion,
+    ParameterTiming, Project, SharedState, Snap, QUANTUM_RANGE, SNAP_PADS, TEMPO_RANGE,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::error;
+
+/// Longest crossfade used when recalling a snap, in milliseconds
+const MAX_RECALL_CROSSFADE_MS: u32 = 5000;
+
+/// The current position in the project
+#[derive(Clone, Debug, Serialize)]
+pub struct Selection {
+    pub bank: usize,
+    pub snap: usize,
+
+    /// Morph duration picked on the controller, in bars
+    pub morph_duration: u8,
+
+    /// Progress of the running morph, if any
+    pub morph_progress: Option<f64>,
+}
+
+/// A playback or editing operation asked of the control service. The UI, remote
+/// surfaces and scripts publish each one that succeeds as `Event::CommandIssued`, so a
+/// journal replay can perform it again against the same project as it was edited;
+/// what the controller's pads do is replayed from the pads.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ControlCommand {
+    SelectBank {
+        bank_id: usize,
+    },
+    LaunchSnap {
+        bank_id: usize,
+        snap_id: usize,
+        quantize: Option<LaunchQuantize>,
+    },
+    RecallSnap {
+        bank_id: usize,
+        snap_id: usize,
+    },
+    StartMorph {
+        from_snap: usize,
+        to_snap: usize,
+        duration: MorphDuration,
+        curve_type: MorphCurve,
+        quantize: Option<LaunchQuantize>,
+    },
+    MorphTo {
+        to_snap: usize,
+        duration: Option<MorphDuration>,
+        curve_type: MorphCurve,
+        quantize: Option<LaunchQuantize>,
+    },
+    LoopTo {
+        to_snap: usize,
+        looping: MorphLoop,
+        curve_type: MorphCurve,
+        quantize: Option<LaunchQuantize>,
+    },
+    StartManualMorph {
+        to_snap: usize,
+        curve_type: MorphCurve,
+    },
+    SetMorphPosition {
+        position: f64,
+    },
+    StopMorph,
+    SetParameter {
+        param_id: usize,
+        value: u8,
+    },
+    StartChain {
+        id: String,
+        quantize: Option<LaunchQuantize>,
+    },
+    SetManualControl {
+        manual_control: ManualControl,
+    },
+    AddSnap {
+        bank_id: usize,
+        pad_index: usize,
+        name: String,
+        description: String,
+        values: Option<Vec<u8>>,
+    },
+    UpdateSnap {
+        bank_id: usize,
+        snap_id: usize,
+        name: Option<String>,
+        description: Option<String>,
+        values: Option<Vec<u8>>,
+    },
+    SetParameterTiming {
+        param_id: usize,
+        timing: Option<MorphTiming>,
+    },
+    SetParameterMotion {
+        param_id: usize,
+        motion: ParameterMotion,
+    },
+    SetSnapTiming {
+        bank_id: usize,
+        snap_id: usize,
+        param_id: usize,
+        timing: Option<MorphTiming>,
+    },
+    SaveCurve {
+        curve: UserCurve,
+    },
+    DeleteCurve {
+        id: String,
+    },
+    SetRecallCrossfade {
+        crossfade_ms: u32,
+    },
+    SetInterruptPolicy {
+        policy: InterruptPolicy,
+    },
+    SetLaunchQuantization {
+        bank_id: Option<usize>,
+        launch_quantization: Option<LaunchQuantization>,
+    },
+    SaveChain {
+        chain: MorphChain,
+    },
+    DeleteChain {
+        id: String,
+    },
+    AddParameter {
+        name: String,
+        description: String,
+        cc: u8,
+    },
+    UpdateParameter {
+        param_id: usize,
+        name: String,
+        description: String,
+        cc: u8,
+    },
+    SetController {
+        name: String,
+    },
+}
+
+/// ControlService performs the operations that remote surfaces and the UI share:
+/// selecting banks and snaps, morphing, editing parameters and driving Link
+#[derive(Clone)]
+pub struct ControlService {
+    state: SharedState,
+    event_bus: EventBus,
+    midi_manager: Option<Arc<MidiManager>>,
+    link_sync: Option<LinkSynchronizer>,
+
+    /// Whether operations are published as commands for the journal
+    commands: bool,
+}
+
+impl ControlService {
+    /// Create a control service over the running components
+    pub fn new(
+        state: SharedState,
+        event_bus: EventBus,
+        midi_manager: Option<Arc<MidiManager>>,
+        link_sync: Option<LinkSynchronizer>,
+    ) -> Self {
+        Self {
+            state,
+            event_bus,
+            midi_manager,
+            link_sync,
+            commands: true,
+        }
+    }
+
+    /// A control service for the app's own components, such as the launcher, whose
+    /// operations follow from recorded input and so are not published as commands
+    pub fn internal(&self) -> Self {
+        Self {
+            commands: false,
+            ..self.clone()
+        }
+    }
+
+    /// The open project, without credentials
+    pub fn project(&self) -> Project {
+        let mut project = self.state.read().unwrap().project.clone();
+        project.openai_api_key = None;
+        project
+    }
+
+    /// The current bank, snap and morph
+    pub fn selection(&self) -> Selection {
+        let state_guard = self.state.read().unwrap();
+        Selection {
+            bank: state_guard.current_bank,
+            snap: state_guard.current_snap,
+            morph_duration: state_guard.morph_duration,
+            morph_progress: state_guard.active_morph.as_ref().map(|morph| morph.progress),
+        }
+    }
+
+    /// Link session status
+    pub async fn link_status(&self) -> Result<LinkStatus, String> {
+        self.link()?.status().await.map_err(|e| e.to_string())
+    }
+
+    /// Index of the current bank
+    pub fn current_bank(&self) -> usize {
+        self.state.read().unwrap().current_bank
+    }
+
+    /// Make a bank current, keeping the snap selection if the bank has that snap
+    pub fn select_bank(&self, bank_id: usize) -> Result<(), String> {
+        let command = ControlCommand::SelectBank { bank_id };
+        {
+            let mut state_guard = self.state.write().unwrap();
+            let bank = state_guard
+                .project
+                .banks
+                .get(bank_id)
+                .ok_or_else(|| "Bank ID out of range".to_string())?;
+
+            let snap_count = bank.snaps.len();
+            state_guard.current_bank = bank_id;
+            if state_guard.current_snap >= snap_count {
+                state_guard.current_snap = 0;
+            }
+        }
+
+        let result = self.publish(Event::BankSelected { bank_id });
+        self.recorded(command, result)
+    }
+
+    /// Select a snap and send all its values to the DAW, on the bank's launch grid
+    pub fn select_snap(&self, bank_id: usize, snap_id: usize) -> Result<(), String> {
+        self.launch_snap(bank_id, snap_id, None)
+    }
+
+    /// Select a snap once the beat grid reaches its launch point. Without a
+    /// quantization the bank's recall setting is used.
+    pub fn launch_snap(&self, bank_id: usize, snap_id: usize, quantize: Option<LaunchQuantize>) -> Result<(), String> {
+        let command = ControlCommand::LaunchSnap {
+            bank_id,
+            snap_id,
+            quantize,
+        };
+        let quantize = {
+            let state_guard = self.state.read().unwrap();
+            let bank = state_guard
+                .project
+                .banks
+                .get(bank_id)
+                .ok_or_else(|| "Bank ID out of range".to_string())?;
+            if snap_id >= bank.snaps.len() {
+                return Err("Snap ID out of range".to_string());
+            }
+            quantize.unwrap_or(state_guard.project.launch_quantization_for(bank_id).recall)
+        };
+
+        let result = match quantize {
+            LaunchQuantize::Immediate => self.recall(bank_id, snap_id),
+            quantize => self.publish(Event::LaunchScheduled {
+                bank_id,
+                snap_id,
+                quantize,
+            }),
+        };
+        self.recorded(command, result)
+    }
+
+    /// Select a snap and send all its values to the DAW right away
+    pub fn recall_snap(&self, bank_id: usize, snap_id: usize) -> Result<(), String> {
+        let command = ControlCommand::RecallSnap { bank_id, snap_id };
+        let result = self.recall(bank_id, snap_id);
+        self.recorded(command, result)
+    }
+
+    fn recall(&self, bank_id: usize, snap_id: usize) -> Result<(), String> {
+        let (params_to_send, crossfade, cancel_morph) = {
+            let mut state_guard = self.state.write().unwrap();
+
+            // Validate indices
+            if bank_id >= state_guard.project.banks.len() {
+                return Err("Bank ID out of range".to_string());
+            }
+            if snap_id >= state_guard.project.banks[bank_id].snaps.len() {
+                return Err("Snap ID out of range".to_string());
+            }
+
+            // Mid-morph, a recall in the same bank is a morph of the crossfade length,
+            // possibly 0; the morph engine applies the interrupt policy and selects the
+            // snap when it arrives
+            let same_bank = bank_id == state_guard.current_bank;
+            if same_bank && state_guard.active_morph.is_some() {
+                let from_snap = state_guard.current_snap;
+                let duration = MorphDuration::Millis(state_guard.project.recall_crossfade_ms as u64);
+                drop(state_guard);
+                return self.morph(from_snap, snap_id, duration, MorphCurve::Linear, Some(LaunchQuantize::Immediate));
+            }
+
+            // A morph cannot carry on into another bank
+            let cancel_morph = state_guard.active_morph.take().is_some();
+
+            // Within the bank a recall crossfade is a short morph from the current snap
+            let crossfade = match state_guard.project.recall_crossfade_ms {
+                0 => None,
+                ms if same_bank => {
+                    Some((state_guard.current_snap, MorphDuration::Millis(ms as u64)))
+                }
+                _ => None,
+            };
+
+            // Update state
+            state_guard.current_bank = bank_id;
+            state_guard.current_snap = snap_id;
+
+            // Ensure the snap has values for all parameters
+            let param_count = state_guard.project.parameters.len();
+            let state_ref = &mut *state_guard;
+            let snap = &mut state_ref.project.banks[bank_id].snaps[snap_id];
+            if snap.values.len() < param_count {
+                snap.values.resize(param_count, 64);
+            }
+
+            let params_to_send = state_ref
+                .project
+                .parameters
+                .iter()
+                .zip(&snap.values)
+                .map(|(param, value)| (param.cc, *value))
+                .collect::<Vec<(u8, u8)>>();
+            (params_to_send, crossfade, cancel_morph)
+        };
+
+        if cancel_morph {
+            self.publish(Event::MorphCompleted)?;
+        }
+
+        match crossfade {
+            Some((from_snap, duration)) => {
+                self.morph(from_snap, snap_id, duration, MorphCurve::Linear, Some(LaunchQuantize::Immediate))?
+            }
+            None => {
+                if let Some(midi_manager) = &self.midi_manager {
+                    // MIDI failure shouldn't stop the snap selection
+                    if let Err(e) = midi_manager.send_snap_values(&params_to_send) {
+                        error!("Failed to send snap values via MIDI: {}", e);
+                    }
+                }
+            }
+        }
+
+        self.publish(Event::SnapSelected {
+            bank: bank_id,
+            snap_id,
+        })
+    }
+
+    /// Start a morph between two snaps of the current bank. Without a
+    /// quantization the bank's morph setting is used.
+    pub fn start_morph(
+        &self,
+        from_snap: usize,
+        to_snap: usize,
+        duration: MorphDuration,
+        curve_type: MorphCurve,
+        quantize: Option<LaunchQuantize>,
+    ) -> Result<(), String> {
+        let command = ControlCommand::StartMorph {
+            from_snap,
+            to_snap,
+            duration,
+            curve_type: curve_type.clone(),
+            quantize,
+        };
+        {
+            let state_guard = self.state.read().unwrap();
+            let snap_count = state_guard
+                .project
+                .banks
+                .get(state_guard.current_bank)
+                .map_or(0, |bank| bank.snaps.len());
+            if from_snap >= snap_count || to_snap >= snap_count {
+                return Err("Snap ID out of range".to_string());
+            }
+        }
+
+        let result = self.morph(from_snap, to_snap, duration, curve_type, quantize);
+        self.recorded(command, result)
+    }
+
+    fn morph(
+        &self,
+        from_snap: usize,
+        to_snap: usize,
+        duration: MorphDuration,
+        curve_type: MorphCurve,
+        quantize: Option<LaunchQuantize>,
+    ) -> Result<(), String> {
+        duration.validate()?;
+        ResolvedCurve::resolve(&curve_type, &self.state.read().unwrap().project.curves)?;
+        self.publish(Event::MorphInitiated {
+            from_snap,
+            to_snap,
+            duration,
+            curve_type,
+            quantize: self.morph_quantize(quantize),
+        })
+    }
+
+    /// The quantization asked for, or the current bank's morph setting
+    fn morph_quantize(&self, quantize: Option<LaunchQuantize>) -> LaunchQuantize {
+        quantize.unwrap_or_else(|| {
+            let state_guard = self.state.read().unwrap();
+            state_guard.project.launch_quantization_for(state_guard.current_bank).morph
+        })
+    }
+
+    /// Morph from the current snap to another one in the current bank.
+    /// Without a duration the last duration picked on the controller is used.
+    pub fn morph_to(
+        &self,
+        to_snap: usize,
+        duration: Option<MorphDuration>,
+        curve_type: MorphCurve,
+        quantize: Option<LaunchQuantize>,
+    ) -> Result<(), String> {
+        let command = ControlCommand::MorphTo {
+            to_snap,
+            duration,
+            curve_type: curve_type.clone(),
+            quantize,
+        };
+        let (from_snap, duration) = {
+            let state_guard = self.state.read().unwrap();
+            let snap_count = state_guard
+                .project
+                .banks
+                .get(state_guard.current_bank)
+                .map_or(0, |bank| bank.snaps.len());
+            if to_snap >= snap_count {
+                return Err("Snap ID out of range".to_string());
+            }
+
+            (
+                state_guard.current_snap,
+                duration.unwrap_or(MorphDuration::Bars(state_guard.morph_duration as f64)),
+            )
+        };
+
+        let result = self.morph(from_snap, to_snap, duration, curve_type, quantize);
+        self.recorded(command, result)
+    }
+
+    /// Loop between the current snap and another one in the current bank until
+    /// stopped or out of repeats
+    pub fn loop_to(
+        &self,
+        to_snap: usize,
+        looping: MorphLoop,
+        curve_type: MorphCurve,
+        quantize: Option<LaunchQuantize>,
+    ) -> Result<(), String> {
+        let command = ControlCommand::LoopTo {
+            to_snap,
+            looping,
+            curve_type: curve_type.clone(),
+            quantize,
+        };
+        looping.validate()?;
+        let from_snap = {
+            let state_guard = self.state.read().unwrap();
+            let snap_count = state_guard
+                .project
+                .banks
+                .get(state_guard.current_bank)
+                .map_or(0, |bank| bank.snaps.len());
+            if to_snap >= snap_count {
+                return Err("Snap ID out of range".to_string());
+            }
+            ResolvedCurve::resolve(&curve_type, &state_guard.project.curves)?;
+            state_guard.current_snap
+        };
+
+        let result = self.publish(Event::LoopInitiated {
+            from_snap,
+            to_snap,
+            looping,
+            curve_type,
+            quantize: self.morph_quantize(quantize),
+        });
+        self.recorded(command, result)
+    }
+
+    /// Start a manual morph to a snap, whose position then comes from the
+    /// bound fader, pad pressure or `set_morph_position`
+    pub fn start_manual_morph(&self, to_snap: usize, curve_type: MorphCurve) -> Result<(), String> {
+        let command = ControlCommand::StartManualMorph {
+            to_snap,
+            curve_type: curve_type.clone(),
+        };
+        let from_snap = {
+            let state_guard = self.state.read().unwrap();
+            let snap_count = state_guard
+                .project
+                .banks
+                .get(state_guard.current_bank)
+                .map_or(0, |bank| bank.snaps.len());
+            if to_snap >= snap_count {
+                return Err("Snap ID out of range".to_string());
+            }
+            ResolvedCurve::resolve(&curve_type, &state_guard.project.curves)?;
+            state_guard.current_snap
+        };
+
+        let result = self.publish(Event::ManualMorphInitiated {
+            from_snap,
+            to_snap,
+            curve_type,
+        });
+        self.recorded(command, result)
+    }
+
+    /// Set the position of the running morph by hand, from 0 (source) to 1 (target)
+    pub fn set_morph_position(&self, position: f64) -> Result<(), String> {
+        let command = ControlCommand::SetMorphPosition { position };
+        if !(0.0..=1.0).contains(&position) {
+            return Err("Morph position must be between 0 and 1".to_string());
+        }
+        if self.state.read().unwrap().active_morph.is_none() {
+            return Err("No morph is running".to_string());
+        }
+        let result = self.publish(Event::MorphPositionInput { position });
+        self.recorded(command, result)
+    }
+
+    /// Choose which CC and whether pad pressure drive manual morphs
+    pub fn set_manual_control(&self, manual_control: ManualControl) -> Result<(), String> {
+        manual_control.validate()?;
+        let command = ControlCommand::SetManualControl {
+            manual_control: manual_control.clone(),
+        };
+        self.state.write().unwrap().project.manual_control = manual_control;
+        self.record(command);
+        Ok(())
+    }
+
+    /// Stop the running morph, loop or chain where it is
+    pub fn stop_morph(&self) -> Result<(), String> {
+        let command = ControlCommand::StopMorph;
+        let result = self.publish(Event::MorphCompleted);
+        self.recorded(command, result)
+    }
+
+    /// Set a parameter of the current snap and send it to the DAW
+    pub fn set_parameter(&self, param_id: usize, value: u8) -> Result<(), String> {
+        let command = ControlCommand::SetParameter { param_id, value };
+        let value = value.min(127);
+        let cc = {
+            let mut state_guard = self.state.write().unwrap();
+            let cc = state_guard
+                .project
+                .parameters
+                .get(param_id)
+                .map(|param| param.cc)
+                .ok_or_else(|| "Parameter ID out of range".to_string())?;
+
+            let current_bank = state_guard.current_bank;
+            let current_snap = state_guard.current_snap;
+            let snap = state_guard
+                .project
+                .banks
+                .get_mut(current_bank)
+                .and_then(|bank| bank.snaps.get_mut(current_snap))
+                .ok_or_else(|| "No snap is selected".to_string())?;
+
+            // Ensure the values array is big enough
+            if snap.values.len() <= param_id {
+                snap.values.resize(param_id + 1, 64);
+            }
+            snap.values[param_id] = value;
+            cc
+        };
+
+        if let Some(midi_manager) = &self.midi_manager {
+            // MIDI failure shouldn't stop the parameter edit
+            if let Err(e) = midi_manager.send_cc(0, cc, value) {
+                error!("Failed to send parameter CC via MIDI: {}", e);
+            }
+        }
+
+        let result = self.publish(Event::ParameterEdited { param_id, value });
+        self.recorded(command, result)
+    }
+
+    /// Put a new snap on a pad of a bank, with every parameter at its middle value
+    /// unless values are given
+    pub fn add_snap(
+        &self,
+        bank_id: usize,
+        pad_index: usize,
+        name: String,
+        description: String,
+        values: Option<Vec<u8>>,
+    ) -> Result<(), String> {
+        let command = ControlCommand::AddSnap {
+            bank_id,
+            pad_index,
+            name: name.clone(),
+            description: description.clone(),
+            values: values.clone(),
+        };
+        if pad_index >= SNAP_PADS {
+            return Err(format!("Pad index {} is out of range (0-{})", pad_index, SNAP_PADS - 1));
+        }
+
+        let mut state_guard = self.state.write().unwrap();
+        let param_count = state_guard.project.parameters.len();
+        let values = match values {
+            Some(values) => validate_values(values, param_count)?,
+            None => vec![64; param_count],
+        };
+
+        let bank = state_guard
+            .project
+            .banks
+            .get_mut(bank_id)
+            .ok_or_else(|| "Bank ID out of range".to_string())?;
+
+        // Ensure we have space for this snap position
+        if pad_index >= bank.snaps.len() {
+            bank.snaps.resize(
+                pad_index + 1,
+                Snap {
+                    name: String::new(),
+                    description: String::new(),
+                    values: vec![],
+                    morph_timings: Vec::new(),
+                },
+            );
+        }
+
+        bank.snaps[pad_index] = Snap {
+            name,
+            description,
+            values,
+            morph_timings: Vec::new(),
+        };
+        drop(state_guard);
+
+        let result = self.publish(Event::SnapEdited {
+            bank_id,
+            snap_id: pad_index,
+        });
+        self.recorded(command, result)
+    }
+
+    /// Change the name, description or values of a snap; fields left out are kept
+    pub fn update_snap(
+        &self,
+        bank_id: usize,
+        snap_id: usize,
+        name: Option<String>,
+        description: Option<String>,
+        values: Option<Vec<u8>>,
+    ) -> Result<(), String> {
+        let command = ControlCommand::UpdateSnap {
+            bank_id,
+            snap_id,
+            name: name.clone(),
+            description: description.clone(),
+            values: values.clone(),
+        };
+        let mut state_guard = self.state.write().unwrap();
+        let param_count = state_guard.project.parameters.len();
+        let values = values
+            .map(|values| validate_values(values, param_count))
+            .transpose()?;
+
+        let snap = state_guard
+            .project
+            .banks
+            .get_mut(bank_id)
+            .ok_or_else(|| "Bank ID out of range".to_string())?
+            .snaps
+            .get_mut(snap_id)
+            .ok_or_else(|| "Snap ID out of range".to_string())?;
+
+        if let Some(name) = name {
+            snap.name = name;
+        }
+        if let Some(description) = description {
+            snap.description = description;
+        }
+        if let Some(values) = values {
+            snap.values = values;
+        }
+        drop(state_guard);
+
+        let result = self.publish(Event::SnapEdited { bank_id, snap_id });
+        self.recorded(command, result)
+    }
+
+    /// Set when a parameter moves during morphs; None makes it follow the whole morph
+    pub fn set_parameter_timing(&self, param_id: usize, timing: Option<MorphTiming>) -> Result<(), String> {
+        let command = ControlCommand::SetParameterTiming {
+            param_id,
+            timing: timing.clone(),
+        };
+        let mut state_guard = self.state.write().unwrap();
+        if let Some(timing) = &timing {
+            validate_timing(timing, &state_guard.project.curves)?;
+        }
+
+        let param = state_guard
+            .project
+            .parameters
+            .get_mut(param_id)
+            .ok_or_else(|| "Parameter ID out of range".to_string())?;
+        param.morph_timing = timing;
+        drop(state_guard);
+
+        self.record(command);
+        Ok(())
+    }
+
+    /// Add a parameter; every snap gets the middle value for it
+    pub fn add_parameter(&self, name: String, description: String, cc: u8) -> Result<(), String> {
+        let command = ControlCommand::AddParameter {
+            name: name.clone(),
+            description: description.clone(),
+            cc,
+        };
+        let mut state_guard = self.state.write().unwrap();
+        state_guard.project.parameters.push(Parameter {
+            name,
+            description,
+            cc,
+            morph_timing: None,
+            motion: ParameterMotion::Continuous,
+        });
+        for bank in &mut state_guard.project.banks {
+            for snap in &mut bank.snaps {
+                snap.values.push(64);
+            }
+        }
+        drop(state_guard);
+
+        self.record(command);
+        Ok(())
+    }
+
+    /// Rename a parameter or move it to another CC
+    pub fn update_parameter(&self, param_id: usize, name: String, description: String, cc: u8) -> Result<(), String> {
+        let command = ControlCommand::UpdateParameter {
+            param_id,
+            name: name.clone(),
+            description: description.clone(),
+            cc,
+        };
+        let mut state_guard = self.state.write().unwrap();
+        let param = state_guard
+            .project
+            .parameters
+            .get_mut(param_id)
+            .ok_or_else(|| "Parameter ID out of range".to_string())?;
+        param.name = name;
+        param.description = description;
+        param.cc = cc;
+        drop(state_guard);
+
+        self.record(command);
+        Ok(())
+    }
+
+    /// Set the controller the project is played from
+    pub fn set_controller(&self, name: String) -> Result<(), String> {
+        let command = ControlCommand::SetController { name: name.clone() };
+        self.state.write().unwrap().project.controller = name.clone();
+
+        let result = self.publish(Event::ControllerChanged { name });
+        self.recorded(command, result)
+    }
+
+    /// Set whether a parameter glides, jumps or steps during morphs
+    pub fn set_parameter_motion(&self, param_id: usize, motion: ParameterMotion) -> Result<(), String> {
+        motion.validate()?;
+        let command = ControlCommand::SetParameterMotion {
+            param_id,
+            motion,
+        };
+        let mut state_guard = self.state.write().unwrap();
+        let param = state_guard
+            .project
+            .parameters
+            .get_mut(param_id)
+            .ok_or_else(|| "Parameter ID out of range".to_string())?;
+        param.motion = motion;
+        drop(state_guard);
+
+        self.record(command);
+        Ok(())
+    }
+
+    /// Override a parameter's morph timing for morphs to one snap; None removes the override
+    pub fn set_snap_timing(
+        &self,
+        bank_id: usize,
+        snap_id: usize,
+        param_id: usize,
+        timing: Option<MorphTiming>,
+    ) -> Result<(), String> {
+        let command = ControlCommand::SetSnapTiming {
+            bank_id,
+            snap_id,
+            param_id,
+            timing: timing.clone(),
+        };
+        let mut state_guard = self.state.write().unwrap();
+        if param_id >= state_guard.project.parameters.len() {
+            return Err("Parameter ID out of range".to_string());
+        }
+        if let Some(timing) = &timing {
+            validate_timing(timing, &state_guard.project.curves)?;
+        }
+
+        let snap = state_guard
+            .project
+            .banks
+            .get_mut(bank_id)
+            .ok_or_else(|| "Bank ID out of range".to_string())?
+            .snaps
+            .get_mut(snap_id)
+            .ok_or_else(|| "Snap ID out of range".to_string())?;
+
+        snap.morph_timings.retain(|existing| existing.param_id != param_id);
+        if let Some(timing) = timing {
+            snap.morph_timings.push(ParameterTiming { param_id, timing });
+            snap.morph_timings.sort_by_key(|timing| timing.param_id);
+        }
+        drop(state_guard);
+
+        self.record(command);
+        Ok(())
+    }
+
+    /// Built-in curve IDs available in this build, and the project's user curves
+    pub fn curves(&self) -> (Vec<String>, Vec<UserCurve>) {
+        let builtin = builtin_curves().into_iter().map(|(id, _)| id.to_string()).collect();
+        (builtin, self.state.read().unwrap().project.curves.clone())
+    }
+
+    /// Add a user curve, or replace the one with the same ID
+    pub fn save_curve(&self, curve: UserCurve) -> Result<(), String> {
+        curve.validate()?;
+        let command = ControlCommand::SaveCurve { curve: curve.clone() };
+
+        let mut state_guard = self.state.write().unwrap();
+        let curves = &mut state_guard.project.curves;
+        match curves.iter_mut().find(|existing| existing.id == curve.id) {
+            Some(existing) => *existing = curve,
+            None => curves.push(curve),
+        }
+        drop(state_guard);
+
+        self.record(command);
+        Ok(())
+    }
+
+    /// Remove a user curve; morphs that name it fall back to linear
+    pub fn delete_curve(&self, id: &str) -> Result<(), String> {
+        let mut state_guard = self.state.write().unwrap();
+        let curves = &mut state_guard.project.curves;
+        let before = curves.len();
+        curves.retain(|curve| curve.id != id);
+        if curves.len() == before {
+            return Err(format!("Unknown curve: {}", id));
+        }
+        drop(state_guard);
+
+        self.record(ControlCommand::DeleteCurve { id: id.to_string() });
+        Ok(())
+    }
+
+    /// Set the crossfade used when recalling a snap; 0 recalls instantly
+    pub fn set_recall_crossfade(&self, crossfade_ms: u32) -> Result<(), String> {
+        if crossfade_ms > MAX_RECALL_CROSSFADE_MS {
+            return Err(format!(
+                "Recall crossfade must be at most {} ms",
+                MAX_RECALL_CROSSFADE_MS
+            ));
+        }
+        self.state.write().unwrap().project.recall_crossfade_ms = crossfade_ms;
+        self.record(ControlCommand::SetRecallCrossfade { crossfade_ms });
+        Ok(())
+    }
+
+    /// Set how a running morph reacts to a new morph or recall
+    pub fn set_interrupt_policy(&self, policy: InterruptPolicy) -> Result<(), String> {
+        self.state.write().unwrap().project.interrupt_policy = policy;
+        self.record(ControlCommand::SetInterruptPolicy { policy });
+        Ok(())
+    }
+
+    /// Set when morphs and recalls launch, for the project or for one bank.
+    /// Clearing a bank's setting makes it follow the project again.
+    pub fn set_launch_quantization(
+        &self,
+        bank_id: Option<usize>,
+        launch_quantization: Option<LaunchQuantization>,
+    ) -> Result<(), String> {
+        let command = ControlCommand::SetLaunchQuantization {
+            bank_id,
+            launch_quantization,
+        };
+        let mut state_guard = self.state.write().unwrap();
+        let project = &mut state_guard.project;
+        match bank_id {
+            Some(bank_id) => {
+                project
+                    .banks
+                    .get_mut(bank_id)
+                    .ok_or_else(|| "Bank ID out of range".to_string())?
+                    .launch_quantization = launch_quantization;
+            }
+            None => project.launch_quantization = launch_quantization.unwrap_or_default(),
+        }
+        drop(state_guard);
+
+        self.record(command);
+        Ok(())
+    }
+
+    /// The project's morph chains
+    pub fn chains(&self) -> Vec<MorphChain> {
+        self.state.read().unwrap().project.chains.clone()
+    }
+
+    /// Add a morph chain, or replace the one with the same ID
+    pub fn save_chain(&self, chain: MorphChain) -> Result<(), String> {
+        chain.validate()?;
+        let command = ControlCommand::SaveChain { chain: chain.clone() };
+
+        let mut state_guard = self.state.write().unwrap();
+        let project = &mut state_guard.project;
+        for step in &chain.steps {
+            if let Some(id) = &step.curve {
+                ResolvedCurve::resolve(&curve_by_id(id), &project.curves)?;
+            }
+        }
+        if let Some(other) = project
+            .chains
+            .iter()
+            .find(|other| other.id != chain.id && chain.pad.is_some() && other.pad == chain.pad)
+        {
+            return Err(format!("Pad is already used by chain '{}'", other.id));
+        }
+
+        match project.chains.iter_mut().find(|existing| existing.id == chain.id) {
+            Some(existing) => *existing = chain,
+            None => project.chains.push(chain),
+        }
+        drop(state_guard);
+
+        self.record(command);
+        Ok(())
+    }
+
+    /// Remove a morph chain
+    pub fn delete_chain(&self, id: &str) -> Result<(), String> {
+        let mut state_guard = self.state.write().unwrap();
+        let chains = &mut state_guard.project.chains;
+        let before = chains.len();
+        chains.retain(|chain| chain.id != id);
+        if chains.len() == before {
+            return Err(format!("Unknown chain: {}", id));
+        }
+        drop(state_guard);
+
+        self.record(ControlCommand::DeleteChain { id: id.to_string() });
+        Ok(())
+    }
+
+    /// Run a morph chain from the current snap
+    pub fn start_chain(&self, id: &str, quantize: Option<LaunchQuantize>) -> Result<(), String> {
+        let command = ControlCommand::StartChain {
+            id: id.to_string(),
+            quantize,
+        };
+        {
+            let state_guard = self.state.read().unwrap();
+            let chain = state_guard
+                .project
+                .chains
+                .iter()
+                .find(|chain| chain.id == id)
+                .ok_or_else(|| format!("Unknown chain: {}", id))?;
+            let snap_count = state_guard
+                .project
+                .banks
+                .get(state_guard.current_bank)
+                .map_or(0, |bank| bank.snaps.len());
+            if let Some(step) = chain.steps.iter().find(|step| step.to_snap >= snap_count) {
+                return Err(format!("Snap {} is not in the current bank", step.to_snap));
+            }
+        }
+
+        let result = self.publish(Event::ChainInitiated {
+            chain_id: id.to_string(),
+            quantize: self.morph_quantize(quantize),
+        });
+        self.recorded(command, result)
+    }
+
+    /// Points along a built-in or user curve, for drawing it
+    pub fn sample_curve(&self, id: &str, count: usize) -> Result<Vec<CurvePoint>, String> {
+        let curve = parse_curve(id);
+        let resolved = ResolvedCurve::resolve(&curve, &self.state.read().unwrap().project.curves)?;
+        Ok(resolved.sample(count))
+    }
+
+    /// Set the Link tempo, between 20 and 999 BPM
+    pub async fn set_tempo(&self, tempo: f64) -> Result<(), String> {
+        if !TEMPO_RANGE.contains(&tempo) {
+            return Err(format!("Tempo {} is out of range", tempo));
+        }
+
+        self.link()?.set_tempo(tempo).await;
+        self.publish(Event::LinkTempoChanged { tempo })
+    }
+
+    /// Set the Link quantum, the bar length in beats, between 1 and 16
+    pub async fn set_quantum(&self, quantum: f64) -> Result<(), String> {
+        if !QUANTUM_RANGE.contains(&quantum) {
+            return Err(format!("Quantum {} is out of range", quantum));
+        }
+
+        self.link()?.set_quantum(quantum).await;
+        self.publish(Event::LinkQuantumChanged { quantum })
+    }
+
+    /// Start or stop the Link transport
+    pub async fn set_transport(&self, playing: bool) -> Result<(), String> {
+        let link_sync = self.link()?;
+        if playing {
+            link_sync.start_transport().await;
+        } else {
+            link_sync.stop_transport().await;
+        }
+        self.publish(Event::LinkTransportChanged { playing })
+    }
+
+    fn link(&self) -> Result<&LinkSynchronizer, String> {
+        self.link_sync
+            .as_ref()
+            .ok_or_else(|| "Link synchronizer not initialized".to_string())
+    }
+
+    /// Perform a recorded command again, for a journal replay
+    pub fn perform(&self, command: ControlCommand) -> Result<(), String> {
+        match command {
+            ControlCommand::SelectBank { bank_id } => self.select_bank(bank_id),
+            ControlCommand::LaunchSnap {
+                bank_id,
+                snap_id,
+                quantize,
+            } => self.launch_snap(bank_id, snap_id, quantize),
+            ControlCommand::RecallSnap { bank_id, snap_id } => self.recall_snap(bank_id, snap_id),
+            ControlCommand::StartMorph {
+                from_snap,
+                to_snap,
+                duration,
+                curve_type,
+                quantize,
+            } => self.start_morph(from_snap, to_snap, duration, curve_type, quantize),
+            ControlCommand::MorphTo {
+                to_snap,
+                duration,
+                curve_type,
+                quantize,
+            } => self.morph_to(to_snap, duration, curve_type, quantize),
+            ControlCommand::LoopTo {
+                to_snap,
+                looping,
+                curve_type,
+                quantize,
+            } => self.loop_to(to_snap, looping, curve_type, quantize),
+            ControlCommand::StartManualMorph { to_snap, curve_type } => self.start_manual_morph(to_snap, curve_type),
+            ControlCommand::SetMorphPosition { position } => self.set_morph_position(position),
+            ControlCommand::StopMorph => self.stop_morph(),
+            ControlCommand::SetParameter { param_id, value } => self.set_parameter(param_id, value),
+            ControlCommand::StartChain { id, quantize } => self.start_chain(&id, quantize),
+            ControlCommand::SetManualControl { manual_control } => self.set_manual_control(manual_control),
+            ControlCommand::AddSnap {
+                bank_id,
+                pad_index,
+                name,
+                description,
+                values,
+            } => self.add_snap(bank_id, pad_index, name, description, values),
+            ControlCommand::UpdateSnap {
+                bank_id,
+                snap_id,
+                name,
+                description,
+                values,
+            } => self.update_snap(bank_id, snap_id, name, description, values),
+            ControlCommand::SetParameterTiming { param_id, timing } => self.set_parameter_timing(param_id, timing),
+            ControlCommand::SetParameterMotion { param_id, motion } => self.set_parameter_motion(param_id, motion),
+            ControlCommand::SetSnapTiming {
+                bank_id,
+                snap_id,
+                param_id,
+                timing,
+            } => self.set_snap_timing(bank_id, snap_id, param_id, timing),
+            ControlCommand::SaveCurve { curve } => self.save_curve(curve),
+            ControlCommand::DeleteCurve { id } => self.delete_curve(&id),
+            ControlCommand::SetRecallCrossfade { crossfade_ms } => self.set_recall_crossfade(crossfade_ms),
+            ControlCommand::SetInterruptPolicy { policy } => self.set_interrupt_policy(policy),
+            ControlCommand::SetLaunchQuantization {
+                bank_id,
+                launch_quantization,
+            } => self.set_launch_quantization(bank_id, launch_quantization),
+            ControlCommand::SaveChain { chain } => self.save_chain(chain),
+            ControlCommand::DeleteChain { id } => self.delete_chain(&id),
+            ControlCommand::AddParameter { name, description, cc } => self.add_parameter(name, description, cc),
+            ControlCommand::UpdateParameter {
+                param_id,
+                name,
+                description,
+                cc,
+            } => self.update_parameter(param_id, name, description, cc),
+            ControlCommand::SetController { name } => self.set_controller(name),
+        }
+    }
+
+    /// Record a command once it has succeeded, so a replay never repeats a rejected one
+    fn recorded(&self, command: ControlCommand, result: Result<(), String>) -> Result<(), String> {
+        if result.is_ok() {
+            self.record(command);
+        }
+        result
+    }
+
+    /// Publish an operation for the journal, unless it follows from recorded input
+    fn record(&self, command: ControlCommand) {
+        if self.commands {
+            self.event_bus.try_publish(Event::CommandIssued {
+                command: Box::new(command),
+            });
+        }
+    }
+
+    fn publish(&self, event: Event) -> Result<(), String> {
+        self.event_bus
+            .publish(event)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Check a full set of snap values: one per parameter, each a valid CC value
+fn validate_values(values: Vec<u8>, param_count: usize) -> Result<Vec<u8>, String> {
+    if values.len() != param_count {
+        return Err(format!(
+            "Expected {} values, one per parameter, got {}",
+            param_count,
+            values.len()
+        ));
+    }
+    if let Some(value) = values.iter().find(|value| **value > 127) {
+        return Err(format!("Value {} is out of range 0-127", value));
+    }
+    Ok(values)
+}
+
+/// Check a morph window, and that its curve exists
+fn validate_timing(timing: &MorphTiming, curves: &[UserCurve]) -> Result<(), String> {
+    timing.validate()?;
+    if let Some(id) = &timing.curve {
+        ResolvedCurve::resolve(&curve_by_id(id), curves)?;
+    }
+    Ok(())
+}
+
+/// Map a curve ID to a curve. Names that are not built-in refer to user curves,
+/// which the morph operations check exist.
+pub fn parse_curve(name: &str) -> MorphCurve {
+    curve_by_id(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::new_shared_state;
+
+    /// Commands the service published for the journal
+    fn issued(receiver: &mut crate::events::EventReceiver) -> Vec<ControlCommand> {
+        let mut commands = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if let Event::CommandIssued { command } = event {
+                commands.push(*command);
+            }
+        }
+        commands
+    }
+
+    #[test]
+    fn replayed_edits_rebuild_the_edited_project() {
+        let bus = EventBus::new(64, "test");
+        let mut receiver = bus.subscribe();
+        let control = ControlService::new(new_shared_state(), bus.clone(), None, None);
+
+        control.add_snap(0, 2, "Drop".to_string(), String::new(), None).unwrap();
+        control.add_parameter("Cutoff".to_string(), String::new(), 74).unwrap();
+        control.update_parameter(0, "Filter".to_string(), "Low-pass".to_string(), 71).unwrap();
+        control.update_snap(0, 2, None, Some("Big".to_string()), Some(vec![100])).unwrap();
+        control.set_controller("Launchpad Pro".to_string()).unwrap();
+        control.set_recall_crossfade(250).unwrap();
+        control.set_interrupt_policy(InterruptPolicy::FinishThenGo).unwrap();
+        assert!(control.delete_chain("missing").is_err());
+        assert!(control.update_parameter(5, String::new(), String::new(), 1).is_err());
+        let commands = issued(&mut receiver);
+        assert_eq!(commands.len(), 7, "rejected edits are not recorded");
+
+        // Performing the recorded commands on the unedited project gives the same result
+        let replay_bus = EventBus::new(64, "replay");
+        let _replay_receiver = replay_bus.subscribe();
+        let replay = ControlService::new(new_shared_state(), replay_bus, None, None).internal();
+        for command in commands {
+            let json = serde_json::to_string(&command).unwrap();
+            replay.perform(serde_json::from_str(&json).unwrap()).unwrap();
+        }
+
+        assert_eq!(
+            serde_json::to_value(replay.project()).unwrap(),
+            serde_json::to_value(control.project()).unwrap()
+        );
+    }
+
+    #[test]
+    fn morphs_and_edits_outside_the_current_bank_are_rejected() {
+        let state = new_shared_state();
+        let bus = EventBus::new(64, "test");
+        let _receiver = bus.subscribe();
+        let control = ControlService::new(state.clone(), bus, None, None);
+
+        let morph = |from_snap| control.start_morph(from_snap, 0, MorphDuration::Beats(1.0), MorphCurve::Linear, None);
+        assert!(morph(0).is_ok());
+        assert_eq!(morph(1).unwrap_err(), "Snap ID out of range");
+
+        // A bank without snaps has nothing to edit, and nothing to panic on
+        {
+            let mut state_guard = state.write().unwrap();
+            state_guard.project.parameters.push(crate::model::Parameter {
+                name: "Cutoff".to_string(),
+                description: String::new(),
+                cc: 74,
+                morph_timing: None,
+                motion: Default::default(),
+            });
+            state_guard.project.banks[0].snaps.clear();
+        }
+        assert!(control.set_parameter(0, 100).is_err());
+        assert!(!state.is_poisoned());
+    }
+
+    #[test]
+    fn morphs_with_unknown_curves_are_rejected() {
+        let bus = EventBus::new(64, "test");
+        let mut receiver = bus.subscribe();
+        let control = ControlService::new(new_shared_state(), bus, None, None);
+        let missing = || MorphCurve::Custom("missing".to_string());
+        let looping = MorphLoop {
+            mode: crate::events::LoopMode::PingPong,
+            period: MorphDuration::Bars(1.0),
+            repeats: None,
+        };
+
+        let unknown = Err("Unknown curve: missing".to_string());
+        assert_eq!(control.start_morph(0, 0, MorphDuration::Beats(1.0), missing(), None), unknown);
+        assert_eq!(control.morph_to(0, None, missing(), None), unknown);
+        assert_eq!(control.loop_to(0, looping, missing(), None), unknown);
+        assert_eq!(control.start_manual_morph(0, missing()), unknown);
+        assert!(issued(&mut receiver).is_empty());
+
+        assert!(control.morph_to(0, None, MorphCurve::Linear, None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn quanta_outside_the_range_are_rejected() {
+        let control = ControlService::new(new_shared_state(), EventBus::new(64, "test"), None, None);
+
+        for quantum in [0.0, -4.0, 17.0, f64::NAN, f64::INFINITY] {
+            let error = control.set_quantum(quantum).await.unwrap_err();
+            assert!(error.contains("out of range"), "{}: {}", quantum, error);
+        }
+        // In range, it only fails for want of Link
+        assert_eq!(control.set_quantum(7.0).await.unwrap_err(), "Link synchronizer not initialized");
+    }
+}
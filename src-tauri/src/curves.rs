@@ -0,0 +1,401 @@
+// src-tauri/src/curves.rs
+use crate::events::MorphCurve;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "pro")]
+use std::f64::consts::PI;
+
+/// Samples returned when the caller asks for none
+pub const DEFAULT_SAMPLES: usize = 64;
+
+/// Most samples returned for one curve
+const MAX_SAMPLES: usize = 1024;
+
+/// Curve names that cannot be used as user curve IDs
+const BUILTIN_IDS: [&str; 4] = ["linear", "exponential", "logarithmic", "scurve"];
+
+/// A point on a curve; both coordinates run from 0 to 1
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// How a user curve maps morph progress to blend amount
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CurveShape {
+    /// Straight segments through the points, in order of x
+    Breakpoints { points: Vec<CurvePoint> },
+
+    /// Cubic Bézier easing from (0, 0) to (1, 1), as in CSS `cubic-bezier(x1, y1, x2, y2)`
+    Bezier { x1: f64, y1: f64, x2: f64, y2: f64 },
+
+    /// A staircase of equally spaced levels from 0 to 1
+    Stepped { steps: u32 },
+
+    /// Stay on the source snap until `at`, then jump to the target
+    HoldThenJump { at: f64 },
+}
+
+/// A curve defined by the user and stored in the project
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserCurve {
+    /// Referenced by `MorphCurve::Custom`
+    pub id: String,
+
+    /// Name shown in the UI
+    pub name: String,
+
+    pub shape: CurveShape,
+}
+
+impl UserCurve {
+    /// Check the ID and shape before the curve is stored
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Curve ID '{}' must be lowercase letters, digits, '-' or '_'",
+                self.id
+            ));
+        }
+        if BUILTIN_IDS.contains(&self.id.as_str()) {
+            return Err(format!("Curve ID '{}' is reserved for a built-in curve", self.id));
+        }
+        self.shape.validate()
+    }
+}
+
+impl CurveShape {
+    /// Check that the shape is well formed
+    pub fn validate(&self) -> Result<(), String> {
+        let unit = |v: f64| v.is_finite() && (0.0..=1.0).contains(&v);
+
+        match self {
+            CurveShape::Breakpoints { points } => {
+                if points.len() < 2 {
+                    return Err("A breakpoint curve needs at least two points".to_string());
+                }
+                if let Some(point) = points.iter().find(|p| !unit(p.x) || !unit(p.y)) {
+                    return Err(format!("Point ({}, {}) is outside 0-1", point.x, point.y));
+                }
+                if points.windows(2).any(|pair| pair[1].x < pair[0].x) {
+                    return Err("Breakpoints must be in order of x".to_string());
+                }
+                Ok(())
+            }
+            CurveShape::Bezier { x1, y1, x2, y2 } => {
+                // x must stay within 0-1 for the curve to be a function of progress
+                if !unit(*x1) || !unit(*x2) {
+                    return Err("Bézier x control points must be between 0 and 1".to_string());
+                }
+                if !y1.is_finite() || !y2.is_finite() {
+                    return Err("Bézier y control points must be numbers".to_string());
+                }
+                Ok(())
+            }
+            CurveShape::Stepped { steps } => {
+                if *steps < 2 {
+                    return Err("A stepped curve needs at least two steps".to_string());
+                }
+                Ok(())
+            }
+            CurveShape::HoldThenJump { at } => {
+                if !unit(*at) {
+                    return Err("The jump point must be between 0 and 1".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Blend amount at a point of progress, both from 0 to 1
+    pub fn evaluate(&self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+
+        match self {
+            CurveShape::Breakpoints { points } => {
+                let (Some(first), Some(last)) = (points.first(), points.last()) else {
+                    return x;
+                };
+                if x <= first.x {
+                    return first.y;
+                }
+                if x >= last.x {
+                    return last.y;
+                }
+
+                points
+                    .windows(2)
+                    .find(|pair| x <= pair[1].x)
+                    .map(|pair| {
+                        let (a, b) = (pair[0], pair[1]);
+                        if b.x == a.x {
+                            b.y
+                        } else {
+                            a.y + (b.y - a.y) * (x - a.x) / (b.x - a.x)
+                        }
+                    })
+                    .unwrap_or(last.y)
+            }
+            CurveShape::Bezier { x1, y1, x2, y2 } => {
+                let t = bezier_t_for_x(x, *x1, *x2);
+                bezier(t, *y1, *y2)
+            }
+            CurveShape::Stepped { steps } => {
+                let steps = (*steps).max(2) as f64;
+                (x * steps).floor().min(steps - 1.0) / (steps - 1.0)
+            }
+            CurveShape::HoldThenJump { at } => {
+                if x < *at {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+/// One coordinate of a cubic Bézier from 0 to 1 with control values `a` and `b`
+fn bezier(t: f64, a: f64, b: f64) -> f64 {
+    let u = 1.0 - t;
+    3.0 * u * u * t * a + 3.0 * u * t * t * b + t * t * t
+}
+
+/// Find the curve parameter where the x coordinate reaches `x`
+fn bezier_t_for_x(x: f64, x1: f64, x2: f64) -> f64 {
+    // Newton's method converges in a few steps for typical easings
+    let mut t = x;
+    for _ in 0..8 {
+        let error = bezier(t, x1, x2) - x;
+        if error.abs() < 1e-7 {
+            return t;
+        }
+        let u = 1.0 - t;
+        let slope = 3.0 * u * u * x1 + 6.0 * u * t * (x2 - x1) + 3.0 * t * t * (1.0 - x2);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        t = (t - error / slope).clamp(0.0, 1.0);
+    }
+
+    // Fall back to bisection where the curve is nearly flat in x
+    let (mut lo, mut hi) = (0.0, 1.0);
+    t = x;
+    for _ in 0..40 {
+        if bezier(t, x1, x2) < x {
+            lo = t;
+        } else {
+            hi = t;
+        }
+        t = (lo + hi) / 2.0;
+    }
+    t
+}
+
+/// A curve with any user curve looked up, ready to evaluate during a morph
+#[derive(Clone, Debug)]
+pub enum ResolvedCurve {
+    Builtin(MorphCurve),
+    User(CurveShape),
+}
+
+impl ResolvedCurve {
+    /// Look up the user curve a `MorphCurve::Custom` refers to
+    pub fn resolve(curve: &MorphCurve, user_curves: &[UserCurve]) -> Result<Self, String> {
+        match curve {
+            MorphCurve::Custom(id) => user_curves
+                .iter()
+                .find(|user| &user.id == id)
+                .map(|user| ResolvedCurve::User(user.shape.clone()))
+                .ok_or_else(|| format!("Unknown curve: {}", id)),
+            builtin => Ok(ResolvedCurve::Builtin(builtin.clone())),
+        }
+    }
+
+    /// Blend amount at a point of progress, both from 0 to 1
+    pub fn evaluate(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0.0, 1.0);
+        let value = match self {
+            ResolvedCurve::Builtin(curve) => match curve {
+                MorphCurve::Linear => progress,
+                #[cfg(feature = "pro")]
+                MorphCurve::Exponential => progress * progress,
+                #[cfg(feature = "pro")]
+                MorphCurve::Logarithmic => progress.sqrt(),
+                #[cfg(feature = "pro")]
+                MorphCurve::SCurve => 0.5 * (1.0 - (PI * progress).cos()),
+                // Resolved into a user curve before it gets here
+                MorphCurve::Custom(_) => progress,
+            },
+            ResolvedCurve::User(shape) => shape.evaluate(progress),
+        };
+
+        // Bézier overshoot cannot go past the snap values; CC values have nowhere to go
+        value.clamp(0.0, 1.0)
+    }
+
+    /// Evenly spaced points along the curve, including both ends
+    pub fn sample(&self, count: usize) -> Vec<CurvePoint> {
+        let count = match count {
+            0 => DEFAULT_SAMPLES,
+            n => n.clamp(2, MAX_SAMPLES),
+        };
+
+        (0..count)
+            .map(|i| {
+                let x = i as f64 / (count - 1) as f64;
+                CurvePoint {
+                    x,
+                    y: self.evaluate(x),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The curve an ID refers to: a built-in curve, or otherwise a user curve
+pub fn curve_by_id(id: &str) -> MorphCurve {
+    let id = id.to_ascii_lowercase();
+    builtin_curves()
+        .into_iter()
+        .find(|(builtin, _)| *builtin == id)
+        .map(|(_, curve)| curve)
+        .unwrap_or(MorphCurve::Custom(id))
+}
+
+/// Built-in curves available in this build, by ID
+pub fn builtin_curves() -> Vec<(&'static str, MorphCurve)> {
+    vec![
+        ("linear", MorphCurve::Linear),
+        #[cfg(feature = "pro")]
+        ("exponential", MorphCurve::Exponential),
+        #[cfg(feature = "pro")]
+        ("logarithmic", MorphCurve::Logarithmic),
+        #[cfg(feature = "pro")]
+        ("scurve", MorphCurve::SCurve),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn breakpoints_interpolate_and_hold_their_ends() {
+        let shape = CurveShape::Breakpoints {
+            points: vec![
+                CurvePoint { x: 0.2, y: 0.1 },
+                CurvePoint { x: 0.6, y: 0.9 },
+                CurvePoint { x: 0.6, y: 0.5 },
+                CurvePoint { x: 0.8, y: 0.7 },
+            ],
+        };
+
+        assert!(close(shape.evaluate(0.0), 0.1));
+        assert!(close(shape.evaluate(0.4), 0.5));
+        // At a vertical step the level before it holds up to the step
+        assert!(close(shape.evaluate(0.6), 0.9));
+        assert!(close(shape.evaluate(0.7), 0.6));
+        assert!(close(shape.evaluate(1.0), 0.7));
+        assert!(close(shape.evaluate(2.0), 0.7));
+    }
+
+    #[test]
+    fn stepped_and_hold_then_jump_land_on_levels() {
+        let stepped = CurveShape::Stepped { steps: 4 };
+        let levels: Vec<f64> = [0.0, 0.24, 0.25, 0.6, 0.99, 1.0].iter().map(|&x| stepped.evaluate(x)).collect();
+        assert_eq!(levels, vec![0.0, 0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0, 1.0]);
+
+        let jump = CurveShape::HoldThenJump { at: 0.5 };
+        assert_eq!(jump.evaluate(0.49), 0.0);
+        assert_eq!(jump.evaluate(0.5), 1.0);
+    }
+
+    #[test]
+    fn bezier_t_for_x_inverts_the_x_coordinate() {
+        for (x1, x2) in [(0.25, 0.25), (0.42, 0.58), (0.0, 1.0), (1.0, 0.0), (0.9, 0.1)] {
+            for i in 0..=20 {
+                let x = i as f64 / 20.0;
+                let t = bezier_t_for_x(x, x1, x2);
+                assert!((0.0..=1.0).contains(&t));
+                assert!((bezier(t, x1, x2) - x).abs() < 1e-4, "x1={} x2={} x={} t={}", x1, x2, x, t);
+            }
+        }
+    }
+
+    #[test]
+    fn bezier_easing_runs_from_zero_to_one() {
+        let linear = CurveShape::Bezier {
+            x1: 1.0 / 3.0,
+            y1: 1.0 / 3.0,
+            x2: 2.0 / 3.0,
+            y2: 2.0 / 3.0,
+        };
+        for i in 0..=10 {
+            let x = i as f64 / 10.0;
+            assert!(close(linear.evaluate(x), x));
+        }
+
+        let ease = CurveShape::Bezier {
+            x1: 0.25,
+            y1: 0.1,
+            x2: 0.25,
+            y2: 1.0,
+        };
+        assert!(close(ease.evaluate(0.0), 0.0));
+        assert!(close(ease.evaluate(1.0), 1.0));
+        assert!(ease.evaluate(0.5) > 0.5);
+    }
+
+    #[test]
+    fn evaluation_clamps_overshoot() {
+        let back = ResolvedCurve::User(CurveShape::Bezier {
+            x1: 0.5,
+            y1: -1.0,
+            x2: 0.5,
+            y2: 2.0,
+        });
+        let ys: Vec<f64> = back.sample(50).iter().map(|point| point.y).collect();
+        assert!(ys.iter().all(|y| (0.0..=1.0).contains(y)));
+        assert!(ys.iter().any(|&y| y == 0.0 || y == 1.0));
+    }
+
+    #[test]
+    fn sample_includes_both_ends_and_bounds_the_count() {
+        let linear = ResolvedCurve::Builtin(MorphCurve::Linear);
+
+        let points = linear.sample(5);
+        assert_eq!(points.len(), 5);
+        assert_eq!(points[0], CurvePoint { x: 0.0, y: 0.0 });
+        assert_eq!(points[2], CurvePoint { x: 0.5, y: 0.5 });
+        assert_eq!(points[4], CurvePoint { x: 1.0, y: 1.0 });
+
+        assert_eq!(linear.sample(0).len(), DEFAULT_SAMPLES);
+        assert_eq!(linear.sample(1).len(), 2);
+        assert_eq!(linear.sample(100_000).len(), MAX_SAMPLES);
+    }
+
+    #[test]
+    fn user_curves_resolve_by_id() {
+        let curves = vec![UserCurve {
+            id: "late".to_string(),
+            name: "Late".to_string(),
+            shape: CurveShape::HoldThenJump { at: 0.9 },
+        }];
+
+        let late = ResolvedCurve::resolve(&MorphCurve::Custom("late".to_string()), &curves).unwrap();
+        assert_eq!(late.evaluate(0.5), 0.0);
+        assert!(ResolvedCurve::resolve(&MorphCurve::Custom("missing".to_string()), &curves).is_err());
+        assert_eq!(curve_by_id("Linear"), MorphCurve::Linear);
+    }
+}
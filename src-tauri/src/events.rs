@@ -1,8 +1,10 @@
 // src-tauri/src/events.rs
+use crate::control::ControlCommand;
+use crate::metrics::{BusMetrics, MetricsSnapshot, SubscriberMetrics};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
@@ -17,6 +19,262 @@ pub enum MorphCurve {
     Logarithmic,
     #[cfg(feature = "pro")]
     SCurve,
+    /// A user curve stored in the project, by ID
+    Custom(String),
+}
+
+/// Longest tempo-based morph, in bars
+const MAX_MORPH_BARS: f64 = 256.0;
+
+/// Longest time-based morph, in milliseconds
+const MAX_MORPH_MILLIS: u64 = 10 * 60 * 1000;
+
+/// How long a morph takes: in bars or beats at the Link tempo, or in
+/// milliseconds regardless of tempo. Zero recalls the target instantly.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum MorphDuration {
+    /// Bars, including fractions such as 0.25 for a quarter bar
+    Bars(f64),
+    Beats(f64),
+    Millis(u64),
+}
+
+impl MorphDuration {
+    /// Whether the length depends on the tempo
+    pub fn is_tempo_based(&self) -> bool {
+        !matches!(self, MorphDuration::Millis(_))
+    }
+
+    /// Whether the target is recalled at once
+    pub fn is_instant(&self) -> bool {
+        match *self {
+            MorphDuration::Bars(length) | MorphDuration::Beats(length) => length == 0.0,
+            MorphDuration::Millis(ms) => ms == 0,
+        }
+    }
+
+    /// Length in beats, with `beats_per_bar` beats in a bar; None for time-based lengths
+    pub fn beats(&self, beats_per_bar: f64) -> Option<f64> {
+        match *self {
+            MorphDuration::Bars(bars) => Some(bars * beats_per_bar),
+            MorphDuration::Beats(beats) => Some(beats),
+            MorphDuration::Millis(_) => None,
+        }
+    }
+
+    /// Length in seconds at a tempo, with `beats_per_bar` beats in a bar
+    pub fn seconds(&self, bpm: f64, beats_per_bar: f64) -> f64 {
+        let seconds_per_beat = 60.0 / bpm.max(1.0);
+        match *self {
+            MorphDuration::Bars(bars) => bars * beats_per_bar * seconds_per_beat,
+            MorphDuration::Beats(beats) => beats * seconds_per_beat,
+            MorphDuration::Millis(ms) => ms as f64 / 1000.0,
+        }
+    }
+
+    /// Check the length is usable
+    pub fn validate(&self) -> Result<(), String> {
+        let in_range = match *self {
+            MorphDuration::Bars(bars) => {
+                bars.is_finite() && (0.0..=MAX_MORPH_BARS).contains(&bars)
+            }
+            MorphDuration::Beats(beats) => {
+                beats.is_finite() && (0.0..=MAX_MORPH_BARS * 4.0).contains(&beats)
+            }
+            MorphDuration::Millis(ms) => ms <= MAX_MORPH_MILLIS,
+        };
+        if in_range {
+            Ok(())
+        } else {
+            Err(format!("Morph duration {} is out of range", self))
+        }
+    }
+}
+
+impl fmt::Display for MorphDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MorphDuration::Bars(bars) => write!(f, "{}bars", bars),
+            MorphDuration::Beats(beats) => write!(f, "{}beats", beats),
+            MorphDuration::Millis(ms) => write!(f, "{}ms", ms),
+        }
+    }
+}
+
+impl std::str::FromStr for MorphDuration {
+    type Err = String;
+
+    /// Parse durations such as `4`, `4bars`, `3/4bar`, `0.5 bar`, `2beats`, `500ms` or `1.5s`;
+    /// a plain number is in bars
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim().to_ascii_lowercase();
+        let split = text
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let number = number.trim();
+        let invalid = || format!("Invalid duration: {}", text);
+
+        let value = match number.split_once('/') {
+            Some((numerator, denominator)) => {
+                let numerator: f64 = numerator.trim().parse().map_err(|_| invalid())?;
+                let denominator: f64 = denominator.trim().parse().map_err(|_| invalid())?;
+                if denominator == 0.0 {
+                    return Err(invalid());
+                }
+                numerator / denominator
+            }
+            None => number.parse().map_err(|_| invalid())?,
+        };
+        if !value.is_finite() || value < 0.0 {
+            return Err(invalid());
+        }
+
+        let duration = match unit.trim() {
+            "" | "bar" | "bars" => MorphDuration::Bars(value),
+            "beat" | "beats" => MorphDuration::Beats(value),
+            "ms" => MorphDuration::Millis(value.round() as u64),
+            "s" | "sec" => MorphDuration::Millis((value * 1000.0).round() as u64),
+            other => return Err(format!("Unknown duration unit: {}", other)),
+        };
+        duration.validate()?;
+        Ok(duration)
+    }
+}
+
+/// How a looping morph moves between its two snaps
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopMode {
+    /// From the first snap to the second, then start over from the first
+    Restart,
+    /// To the second snap and back again
+    PingPong,
+}
+
+/// A morph that keeps going between two snaps
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MorphLoop {
+    pub mode: LoopMode,
+
+    /// Length of one pass from one snap to the other
+    pub period: MorphDuration,
+
+    /// Passes before the loop stops on the snap it reached; None loops until stopped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeats: Option<u32>,
+}
+
+impl MorphLoop {
+    /// Check the loop can run
+    pub fn validate(&self) -> Result<(), String> {
+        self.period.validate()?;
+        if self.period.is_instant() {
+            return Err("A loop period must be longer than 0".to_string());
+        }
+        if self.repeats == Some(0) {
+            return Err("A loop needs at least one repeat".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Phrase lengths, in bars, that a launch can wait for
+const PHRASE_BARS: [u32; 4] = [2, 4, 8, 16];
+
+/// When a recall or morph launches, relative to the Link beat grid. Bars and
+/// phrases follow the Link quantum. Written as `none`, `beat`, `bar` or a
+/// phrase such as `4bars`; `true` and `false` read as `bar` and `none`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "LaunchQuantizeRepr", into = "String")]
+pub enum LaunchQuantize {
+    /// Launch at once
+    #[default]
+    Immediate,
+    /// The next beat
+    Beat,
+    /// The next bar
+    Bar,
+    /// The next phrase of 2, 4, 8 or 16 bars
+    Phrase(u32),
+}
+
+impl LaunchQuantize {
+    /// Grid to launch on in beats, with `beats_per_bar` beats in a bar; None launches at once
+    pub fn beats(&self, beats_per_bar: f64) -> Option<f64> {
+        match *self {
+            LaunchQuantize::Immediate => None,
+            LaunchQuantize::Beat => Some(1.0),
+            LaunchQuantize::Bar => Some(beats_per_bar),
+            LaunchQuantize::Phrase(bars) => Some(bars as f64 * beats_per_bar),
+        }
+    }
+
+    /// Quantization for the old on/off flag: the next bar, or none
+    pub fn from_flag(quantize: bool) -> Self {
+        if quantize {
+            LaunchQuantize::Bar
+        } else {
+            LaunchQuantize::Immediate
+        }
+    }
+}
+
+impl fmt::Display for LaunchQuantize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchQuantize::Immediate => write!(f, "none"),
+            LaunchQuantize::Beat => write!(f, "beat"),
+            LaunchQuantize::Bar => write!(f, "bar"),
+            LaunchQuantize::Phrase(bars) => write!(f, "{}bars", bars),
+        }
+    }
+}
+
+impl std::str::FromStr for LaunchQuantize {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim().to_ascii_lowercase();
+        match text.as_str() {
+            "none" | "off" | "false" => return Ok(LaunchQuantize::Immediate),
+            "beat" | "1beat" => return Ok(LaunchQuantize::Beat),
+            "bar" | "1bar" | "true" => return Ok(LaunchQuantize::Bar),
+            _ => {}
+        }
+
+        let bars = text
+            .strip_suffix("bars")
+            .and_then(|bars| bars.trim().parse::<u32>().ok())
+            .filter(|bars| PHRASE_BARS.contains(bars))
+            .ok_or_else(|| format!("Invalid launch quantization: {} (use none, beat, bar, 2bars, 4bars, 8bars or 16bars)", text))?;
+        Ok(LaunchQuantize::Phrase(bars))
+    }
+}
+
+impl From<LaunchQuantize> for String {
+    fn from(quantize: LaunchQuantize) -> Self {
+        quantize.to_string()
+    }
+}
+
+/// Launch quantization as written: a name, or the old on/off flag
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LaunchQuantizeRepr {
+    Flag(bool),
+    Name(String),
+}
+
+impl TryFrom<LaunchQuantizeRepr> for LaunchQuantize {
+    type Error = String;
+
+    fn try_from(repr: LaunchQuantizeRepr) -> Result<Self, Self::Error> {
+        match repr {
+            LaunchQuantizeRepr::Flag(quantize) => Ok(LaunchQuantize::from_flag(quantize)),
+            LaunchQuantizeRepr::Name(name) => name.parse(),
+        }
+    }
 }
 
 /// Core events that flow through the system
@@ -54,9 +312,22 @@ pub enum Event {
         param_id: usize,
         value: u8,
     },
+    /// A snap was added, or its name, description or values changed
+    SnapEdited {
+        bank_id: usize,
+        snap_id: usize,
+    },
     BankSelected {
         bank_id: usize,
     },
+    MorphDurationChanged {
+        bars: u8,
+    },
+
+    // Control commands from the UI, remote surfaces and scripts, for the journal
+    CommandIssued {
+        command: Box<ControlCommand>,
+    },
 
     // AI events
     GenerateAIValues {
@@ -78,15 +349,66 @@ pub enum Event {
     MorphInitiated {
         from_snap: usize,
         to_snap: usize,
-        duration_bars: u8,
+        duration: MorphDuration,
         curve_type: MorphCurve,
-        quantize: bool,
+        quantize: LaunchQuantize,
     },
     MorphProgressed {
         progress: f64,
         current_values: Vec<u8>,
+        /// Step of the running morph chain, if the morph is part of one
+        #[serde(default)]
+        step: Option<usize>,
     },
     MorphCompleted,
+    ChainInitiated {
+        chain_id: String,
+        quantize: LaunchQuantize,
+    },
+    LoopInitiated {
+        from_snap: usize,
+        to_snap: usize,
+        looping: MorphLoop,
+        curve_type: MorphCurve,
+        quantize: LaunchQuantize,
+    },
+    /// A snap recall waiting for its launch point on the beat grid
+    LaunchScheduled {
+        bank_id: usize,
+        snap_id: usize,
+        quantize: LaunchQuantize,
+    },
+    /// A morph whose position is set by hand rather than by time
+    ManualMorphInitiated {
+        from_snap: usize,
+        to_snap: usize,
+        curve_type: MorphCurve,
+    },
+    /// Position for the manual morph, from 0 (source) to 1 (target)
+    MorphPositionInput {
+        position: f64,
+    },
+    /// Control change received from a MIDI input
+    ControlInput {
+        channel: u8,
+        cc: u8,
+        value: u8,
+    },
+    /// Polyphonic aftertouch on a controller pad
+    PadPressure {
+        pad: u8,
+        pressure: u8,
+    },
+    ChainCompleted {
+        chain_id: String,
+    },
+
+    // MIDI setup events
+    /// The controller or manual-control MIDI ports were connected or closed
+    MidiPortsChanged,
+    ControllerChanged {
+        name: String,
+    },
 
     // New events for MIDI controller morph handling
     RequestUpdateLEDs,
@@ -95,6 +417,12 @@ pub enum Event {
     // Project events
     ProjectLoaded,
     ProjectSaved,
+    BankImported {
+        bank_id: usize,
+    },
+    ProjectMerged {
+        banks_added: usize,
+    },
 
     // System events
     Shutdown,
@@ -107,14 +435,23 @@ pub enum Event {
     LinkTempoChanged {
         tempo: f64,
     },
+    LinkQuantumChanged {
+        quantum: f64,
+    },
     LinkTransportChanged {
         playing: bool,
     },
-    RequestLinkStatus,
-    RequestLinkTempo,
-    RequestNextBarTime,
-    NextBarTime {
-        wait_time_ms: u64,
+
+    // Diagnostics
+    MetricsReported {
+        metrics: Box<MetricsSnapshot>,
+    },
+
+    // Scripting events
+    ScriptsChanged,
+    ScriptsLoaded {
+        scripts: Vec<String>,
+        errors: Vec<String>,
     },
 }
 
@@ -124,22 +461,63 @@ pub struct EventStats {
     pub messages_sent: AtomicUsize,
     pub messages_received: AtomicUsize,
     pub subscribers_peak: AtomicUsize,
+    /// Events lost because a subscriber lagged behind the channel capacity
+    pub events_dropped: AtomicUsize,
+    /// Supervised handlers restarted after a failure
+    pub handler_restarts: AtomicUsize,
 }
 
-/// EventBus is the central message bus for the application
+/// Capacity of the real-time lane. Pad and CC traffic is small but has to get through
+/// promptly, so this lane is kept separate from everything else rather than large.
+const REALTIME_CAPACITY: usize = 256;
+
+/// An event as it travels through a channel, stamped with its publish time and
+/// its place in the order of everything published on the bus
+#[derive(Clone, Debug)]
+struct Stamped {
+    event: Event,
+    published: Instant,
+    seq: u64,
+}
+
+/// The channel an event travels on.
+///
+/// Only subscribers to the real-time lane alone, such as the pad handler and the
+/// CC forwarder, get its events ahead of normal traffic. A subscriber to both lanes
+/// receives everything in publish order, so a real-time event still waits behind the
+/// normal events published before it; the morph engine, for one, handles manual-morph
+/// input in turn with its morph progress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lane {
+    /// High-priority events: pads, CC output, manual-morph input and shutdown
+    Realtime,
+    /// Everything else
+    Normal,
+}
+
+/// EventBus is the central message bus for the application.
+/// High-priority events travel on their own real-time lane so a backlog of
+/// beat, LED or UI events can never delay a pad press for the handlers that
+/// only listen to that lane, nor push it out of the channel.
 #[derive(Clone)]
 pub struct EventBus {
-    sender: broadcast::Sender<Event>,
+    sender: broadcast::Sender<Stamped>,
+    realtime_sender: broadcast::Sender<Stamped>,
+    /// Number of the next event; events are numbered and sent under this lock
+    sequence: std::sync::Arc<std::sync::Mutex<u64>>,
     name: String,        // Name for debugging purposes
     stats: std::sync::Arc<EventStats>,
+    realtime_stats: std::sync::Arc<EventStats>,
+    metrics: std::sync::Arc<BusMetrics>,
 }
 
 impl fmt::Debug for EventBus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("EventBus")
             .field("name", &self.name)
-            .field("current_receivers", &self.sender.receiver_count())
+            .field("current_receivers", &self.receiver_count())
             .field("stats", &self.stats)
+            .field("realtime_stats", &self.realtime_stats)
             .finish()
     }
 }
@@ -147,36 +525,83 @@ impl fmt::Debug for EventBus {
 impl EventBus {
     /// Create a new event bus with the specified capacity and name
     pub fn new(capacity: usize, name: &str) -> Self {
+        Self::with_realtime_capacity(capacity, REALTIME_CAPACITY, name)
+    }
+
+    /// Create a new event bus with separate capacities for the normal and real-time lanes
+    pub fn with_realtime_capacity(capacity: usize, realtime_capacity: usize, name: &str) -> Self {
         let (sender, _) = broadcast::channel(capacity);
+        let (realtime_sender, _) = broadcast::channel(realtime_capacity);
         Self {
             sender,
+            realtime_sender,
+            sequence: std::sync::Arc::new(std::sync::Mutex::new(0)),
             name: name.to_string(),
             stats: std::sync::Arc::new(EventStats::default()),
+            realtime_stats: std::sync::Arc::new(EventStats::default()),
+            metrics: std::sync::Arc::new(BusMetrics::default()),
         }
     }
 
-    /// Get a new subscription to the event bus
-    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
-        let rx = self.sender.subscribe();
+    /// Get a new subscription to both lanes of the event bus
+    pub fn subscribe(&self) -> EventReceiver {
+        self.subscribe_as("unnamed", false)
+    }
+
+    /// Get a subscription to the real-time lane only, for consumers that only
+    /// care about high-priority events
+    pub fn subscribe_realtime(&self) -> EventReceiver {
+        self.subscribe_as("unnamed", true)
+    }
 
-        // Update statistics
-        let current = self.sender.receiver_count();
+    /// Get a subscription whose counters are reported under `name`
+    pub fn subscribe_as(&self, name: &str, realtime_only: bool) -> EventReceiver {
+        let rx = EventReceiver {
+            realtime: Some(self.realtime_sender.subscribe()),
+            normal: (!realtime_only).then(|| self.sender.subscribe()),
+            realtime_head: None,
+            normal_head: None,
+            stats: self.stats.clone(),
+            realtime_stats: self.realtime_stats.clone(),
+            metrics: self.metrics.register_subscriber(name),
+            last_published: None,
+        };
+        self.update_peak();
+        rx
+    }
+
+    fn update_peak(&self) {
+        let current = self.receiver_count();
         let peak = self.stats.subscribers_peak.load(Ordering::Relaxed);
         if current > peak {
             self.stats.subscribers_peak.store(current, Ordering::Relaxed);
         }
-
-        rx
     }
 
-    /// Publish an event to all subscribers
+    /// Publish an event to all subscribers, on the lane its priority calls for
     pub fn publish(&self, event: Event) -> Result<usize, broadcast::error::SendError<Event>> {
-        debug!(bus = %self.name, event_type = %event.event_type(), "Publishing event");
+        let lane = event.lane();
+        debug!(bus = %self.name, event_type = %event.event_type(), ?lane, "Publishing event");
 
         // Update statistics
-        self.stats.messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.lane_stats(lane).messages_sent.fetch_add(1, Ordering::Relaxed);
+        self.metrics.record_published(&event);
 
-        let result = self.sender.send(event);
+        let result = {
+            // Numbering and sending together keeps both lanes in publish order
+            let mut sequence = self.sequence.lock().unwrap();
+            let stamped = Stamped {
+                event,
+                published: Instant::now(),
+                seq: *sequence,
+            };
+            *sequence += 1;
+            match lane {
+                Lane::Realtime => self.realtime_sender.send(stamped),
+                Lane::Normal => self.sender.send(stamped),
+            }
+        }
+        .map_err(|e| broadcast::error::SendError(e.0.event));
 
         // Log if no receivers
         if let Ok(receiver_count) = &result {
@@ -202,9 +627,10 @@ impl EventBus {
         }
     }
 
-    /// Get the number of active receivers
+    /// Get the number of active subscriptions. Each one receives the real-time lane,
+    /// so that lane's receivers are the subscriptions, whichever lanes they take.
     pub fn receiver_count(&self) -> usize {
-        self.sender.receiver_count()
+        self.realtime_sender.receiver_count()
     }
 
     /// Get the name of this event bus
@@ -212,19 +638,173 @@ impl EventBus {
         &self.name
     }
 
-    /// Get a clone of the statistics
+    /// Get a clone of the statistics for the normal lane
     pub fn stats(&self) -> std::sync::Arc<EventStats> {
         self.stats.clone()
     }
 
+    /// Get a clone of the statistics for the real-time lane
+    pub fn realtime_stats(&self) -> std::sync::Arc<EventStats> {
+        self.realtime_stats.clone()
+    }
+
+    fn lane_stats(&self, lane: Lane) -> &EventStats {
+        match lane {
+            Lane::Realtime => &self.realtime_stats,
+            Lane::Normal => &self.stats,
+        }
+    }
+
+    /// Per-lane totals, per-type counts and per-subscriber lag and latency
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot(&self.stats, &self.realtime_stats)
+    }
+
+    /// Record that the first CC answering the latest pad press went out
+    pub fn record_pad_to_cc(&self) {
+        self.metrics.record_pad_to_cc();
+    }
+
     /// Reset the event bus statistics
     pub fn reset_stats(&self) {
-        self.stats.messages_sent.store(0, Ordering::Relaxed);
-        self.stats.messages_received.store(0, Ordering::Relaxed);
+        for stats in [&self.stats, &self.realtime_stats] {
+            stats.messages_sent.store(0, Ordering::Relaxed);
+            stats.messages_received.store(0, Ordering::Relaxed);
+            stats.events_dropped.store(0, Ordering::Relaxed);
+            stats.handler_restarts.store(0, Ordering::Relaxed);
+        }
+        self.metrics.reset();
         // Keep peak subscribers as-is
     }
 }
 
+/// A subscription to one or both lanes of the bus.
+/// Events are received in the order they were published, whichever lane they
+/// took, so a handler never sees an event before one that led to it.
+pub struct EventReceiver {
+    realtime: Option<broadcast::Receiver<Stamped>>,
+    normal: Option<broadcast::Receiver<Stamped>>,
+
+    /// Events taken off a lane that wait for older events on the other lane
+    realtime_head: Option<Stamped>,
+    normal_head: Option<Stamped>,
+
+    stats: std::sync::Arc<EventStats>,
+    realtime_stats: std::sync::Arc<EventStats>,
+    metrics: std::sync::Arc<SubscriberMetrics>,
+
+    /// When the most recently received event was published
+    last_published: Option<Instant>,
+}
+
+impl EventReceiver {
+    /// Receive the next event in publish order
+    pub async fn recv(&mut self) -> Result<Event, broadcast::error::RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(event) => return Ok(event),
+                Err(broadcast::error::TryRecvError::Lagged(n)) => return Err(broadcast::error::RecvError::Lagged(n)),
+                Err(broadcast::error::TryRecvError::Closed) => return Err(broadcast::error::RecvError::Closed),
+                Err(broadcast::error::TryRecvError::Empty) => {}
+            }
+
+            // Nothing is pending: wait for either lane, then order what arrived
+            let (lane, result) = match (&mut self.realtime, &mut self.normal) {
+                (Some(realtime), Some(normal)) => tokio::select! {
+                    result = realtime.recv() => (Lane::Realtime, result),
+                    result = normal.recv() => (Lane::Normal, result),
+                },
+                (Some(realtime), None) => (Lane::Realtime, realtime.recv().await),
+                (None, Some(normal)) => (Lane::Normal, normal.recv().await),
+                (None, None) => return Err(broadcast::error::RecvError::Closed),
+            };
+
+            match result {
+                Ok(stamped) => *self.head_mut(lane) = Some(stamped),
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    self.record_lag(lane, n);
+                    return Err(broadcast::error::RecvError::Lagged(n));
+                }
+                // Keep draining the other lane until it closes too
+                Err(broadcast::error::RecvError::Closed) => *self.lane_mut(lane) = None,
+            }
+        }
+    }
+
+    /// Receive a pending event without waiting, in publish order
+    pub fn try_recv(&mut self) -> Result<Event, broadcast::error::TryRecvError> {
+        // The second pass looks at each lane again after the other lane's head was taken,
+        // so an older event published on it in the meantime is not overtaken
+        for lane in [Lane::Realtime, Lane::Normal, Lane::Realtime, Lane::Normal] {
+            if self.head_mut(lane).is_some() {
+                continue;
+            }
+            let Some(receiver) = self.lane_mut(lane) else {
+                continue;
+            };
+
+            match receiver.try_recv() {
+                Ok(stamped) => *self.head_mut(lane) = Some(stamped),
+                Err(broadcast::error::TryRecvError::Lagged(n)) => {
+                    self.record_lag(lane, n);
+                    return Err(broadcast::error::TryRecvError::Lagged(n));
+                }
+                Err(broadcast::error::TryRecvError::Empty) => {}
+                Err(broadcast::error::TryRecvError::Closed) => *self.lane_mut(lane) = None,
+            }
+        }
+
+        let lane = match (&self.realtime_head, &self.normal_head) {
+            (Some(realtime), Some(normal)) if normal.seq < realtime.seq => Lane::Normal,
+            (Some(_), _) => Lane::Realtime,
+            (None, Some(_)) => Lane::Normal,
+            (None, None) if self.realtime.is_none() && self.normal.is_none() => {
+                return Err(broadcast::error::TryRecvError::Closed)
+            }
+            (None, None) => return Err(broadcast::error::TryRecvError::Empty),
+        };
+
+        let stamped = self.head_mut(lane).take().expect("lane has a head");
+        self.lane_stats(lane).messages_received.fetch_add(1, Ordering::Relaxed);
+        self.metrics.record_received();
+        self.last_published = Some(stamped.published);
+        Ok(stamped.event)
+    }
+
+    /// Record when the last received event was handled, measured from its publication
+    pub fn record_handled(&self) {
+        if let Some(published) = self.last_published {
+            self.metrics.record_latency(published.elapsed());
+        }
+    }
+
+    fn record_lag(&self, lane: Lane, missed: u64) {
+        self.lane_stats(lane).events_dropped.fetch_add(missed as usize, Ordering::Relaxed);
+        self.metrics.record_lag(missed);
+    }
+
+    fn lane_mut(&mut self, lane: Lane) -> &mut Option<broadcast::Receiver<Stamped>> {
+        match lane {
+            Lane::Realtime => &mut self.realtime,
+            Lane::Normal => &mut self.normal,
+        }
+    }
+
+    fn head_mut(&mut self, lane: Lane) -> &mut Option<Stamped> {
+        match lane {
+            Lane::Realtime => &mut self.realtime_head,
+            Lane::Normal => &mut self.normal_head,
+        }
+    }
+
+    fn lane_stats(&self, lane: Lane) -> &EventStats {
+        match lane {
+            Lane::Realtime => &self.realtime_stats,
+            Lane::Normal => &self.stats,
+        }
+    }
+}
+
 /// Default implementation with reasonable defaults
 impl Default for EventBus {
     fn default() -> Self {
@@ -244,23 +824,38 @@ impl Event {
             Event::BarOccurred { .. } => "BarOccurred",
             Event::SnapSelected { .. } => "SnapSelected",
             Event::ParameterEdited { .. } => "ParameterEdited",
+            Event::SnapEdited { .. } => "SnapEdited",
             Event::BankSelected { .. } => "BankSelected",
+            Event::MorphDurationChanged { .. } => "MorphDurationChanged",
+            Event::CommandIssued { .. } => "CommandIssued",
             Event::GenerateAIValues { .. } => "GenerateAIValues",
             Event::AIGenerationCompleted { .. } => "AIGenerationCompleted",
             Event::AIGenerationFailed { .. } => "AIGenerationFailed",
             Event::MorphInitiated { .. } => "MorphInitiated",
             Event::MorphProgressed { .. } => "MorphProgressed",
             Event::MorphCompleted => "MorphCompleted",
+            Event::ChainInitiated { .. } => "ChainInitiated",
+            Event::LoopInitiated { .. } => "LoopInitiated",
+            Event::ManualMorphInitiated { .. } => "ManualMorphInitiated",
+            Event::LaunchScheduled { .. } => "LaunchScheduled",
+            Event::MorphPositionInput { .. } => "MorphPositionInput",
+            Event::ControlInput { .. } => "ControlInput",
+            Event::PadPressure { .. } => "PadPressure",
+            Event::ChainCompleted { .. } => "ChainCompleted",
             Event::ProjectLoaded => "ProjectLoaded",
             Event::ProjectSaved => "ProjectSaved",
+            Event::BankImported { .. } => "BankImported",
+            Event::ProjectMerged { .. } => "ProjectMerged",
             Event::Shutdown => "Shutdown",
             Event::LinkStatusChanged { .. } => "LinkStatusChanged",
             Event::LinkTempoChanged { .. } => "LinkTempoChanged",
+            Event::LinkQuantumChanged { .. } => "LinkQuantumChanged",
             Event::LinkTransportChanged { .. } => "LinkTransportChanged",
-            Event::RequestLinkStatus => "RequestLinkStatus",
-            Event::RequestLinkTempo => "RequestLinkTempo",
-            Event::RequestNextBarTime => "RequestNextBarTime",
-            Event::NextBarTime { .. } => "NextBarTime",
+            Event::MetricsReported { .. } => "MetricsReported",
+            Event::ScriptsChanged => "ScriptsChanged",
+            Event::ScriptsLoaded { .. } => "ScriptsLoaded",
+            Event::MidiPortsChanged => "MidiPortsChanged",
+            Event::ControllerChanged { .. } => "ControllerChanged",
             Event::RequestUpdateLEDs => "RequestUpdateLEDs",
             Event::RequestMIDIUpdate => "RequestMIDIUpdate",
         }
@@ -270,13 +865,24 @@ impl Event {
     pub fn is_high_priority(&self) -> bool {
         matches!(
             self,
-            Event::PadPressed { .. } | 
+            Event::PadPressed { .. } |
             Event::PadReleased { .. } |
-            Event::ParameterEdited { .. } | 
+            Event::PadPressure { .. } |
             Event::CCValueChanged { .. } |
+            Event::ControlInput { .. } |
+            Event::MorphPositionInput { .. } |
             Event::Shutdown
         )
     }
+
+    /// The lane this event is published on
+    pub fn lane(&self) -> Lane {
+        if self.is_high_priority() {
+            Lane::Realtime
+        } else {
+            Lane::Normal
+        }
+    }
 }
 
 // Add Display implementation for better logging
@@ -303,7 +909,12 @@ impl fmt::Display for Event {
                 info!("Backend received parameter edit: param={}, value={}", param_id, value);
                 write!(f, "ParameterEdited: param_id={}, value={}", param_id, value)
             }
+            Event::SnapEdited { bank_id, snap_id } => {
+                write!(f, "SnapEdited: bank_id={}, snap_id={}", bank_id, snap_id)
+            }
             Event::BankSelected { bank_id } => write!(f, "BankSelected: bank_id={}", bank_id),
+            Event::MorphDurationChanged { bars } => write!(f, "MorphDurationChanged: bars={}", bars),
+            Event::CommandIssued { command } => write!(f, "CommandIssued: {:?}", command),
             Event::GenerateAIValues { bank_id, snap_id } => write!(
                 f,
                 "GenerateAIValues: bank_id={}, snap_id={}",
@@ -328,20 +939,67 @@ impl fmt::Display for Event {
             Event::MorphInitiated {
                 from_snap,
                 to_snap,
-                duration_bars,
+                duration,
                 curve_type,
-                quantize: bool,
+                quantize,
             } => write!(
                 f,
-                "MorphInitiated: from={}, to={}, duration={}bars, curve={:?}",
-                from_snap, to_snap, duration_bars, curve_type
+                "MorphInitiated: from={}, to={}, duration={}, curve={:?}, quantize={}",
+                from_snap, to_snap, duration, curve_type, quantize
             ),
-            Event::MorphProgressed { progress, .. } => {
-                write!(f, "MorphProgressed: progress={:.2}", progress)
-            }
+            Event::MorphProgressed { progress, step, .. } => match step {
+                Some(step) => write!(f, "MorphProgressed: progress={:.2}, step={}", progress, step),
+                None => write!(f, "MorphProgressed: progress={:.2}", progress),
+            },
             Event::MorphCompleted => write!(f, "MorphCompleted"),
+            Event::ChainInitiated { chain_id, quantize } => {
+                write!(f, "ChainInitiated: chain={}, quantize={}", chain_id, quantize)
+            }
+            Event::ChainCompleted { chain_id } => write!(f, "ChainCompleted: chain={}", chain_id),
+            Event::LaunchScheduled {
+                bank_id,
+                snap_id,
+                quantize,
+            } => write!(
+                f,
+                "LaunchScheduled: bank={}, snap={}, quantize={}",
+                bank_id, snap_id, quantize
+            ),
+            Event::ManualMorphInitiated {
+                from_snap,
+                to_snap,
+                curve_type,
+            } => write!(
+                f,
+                "ManualMorphInitiated: from={}, to={}, curve={:?}",
+                from_snap, to_snap, curve_type
+            ),
+            Event::MorphPositionInput { position } => {
+                write!(f, "MorphPositionInput: position={:.3}", position)
+            }
+            Event::ControlInput { channel, cc, value } => {
+                write!(f, "ControlInput: channel={}, cc={}, value={}", channel, cc, value)
+            }
+            Event::PadPressure { pad, pressure } => {
+                write!(f, "PadPressure: pad={}, pressure={}", pad, pressure)
+            }
+            Event::LoopInitiated {
+                from_snap,
+                to_snap,
+                looping,
+                curve_type,
+                ..
+            } => write!(
+                f,
+                "LoopInitiated: from={}, to={}, mode={:?}, period={}, repeats={:?}, curve={:?}",
+                from_snap, to_snap, looping.mode, looping.period, looping.repeats, curve_type
+            ),
             Event::ProjectLoaded => write!(f, "ProjectLoaded"),
             Event::ProjectSaved => write!(f, "ProjectSaved"),
+            Event::BankImported { bank_id } => write!(f, "BankImported: bank_id={}", bank_id),
+            Event::ProjectMerged { banks_added } => {
+                write!(f, "ProjectMerged: banks_added={}", banks_added)
+            }
             Event::Shutdown => write!(f, "Shutdown"),
             Event::LinkStatusChanged { connected, peers } => {
                 write!(f, "LinkStatusChanged: connected={}, peers={}", connected, peers)
@@ -349,15 +1007,27 @@ impl fmt::Display for Event {
             Event::LinkTempoChanged { tempo } => {
                 write!(f, "LinkTempoChanged: tempo={:.1}", tempo)
             },
+            Event::LinkQuantumChanged { quantum } => {
+                write!(f, "LinkQuantumChanged: quantum={:.1}", quantum)
+            },
             Event::LinkTransportChanged { playing } => {
                 write!(f, "LinkTransportChanged: playing={}", playing)
             },
-            Event::RequestLinkStatus => write!(f, "RequestLinkStatus"),
-            Event::RequestLinkTempo => write!(f, "RequestLinkTempo"),
-            Event::RequestNextBarTime => write!(f, "RequestNextBarTime"),
-            Event::NextBarTime { wait_time_ms } => {
-                write!(f, "NextBarTime: wait_time_ms={}", wait_time_ms)
+            Event::MetricsReported { metrics } => {
+                write!(
+                    f,
+                    "MetricsReported: sent={} realtime={} dropped={}",
+                    metrics.normal.sent,
+                    metrics.realtime.sent,
+                    metrics.normal.dropped + metrics.realtime.dropped
+                )
             },
+            Event::ScriptsChanged => write!(f, "ScriptsChanged"),
+            Event::ScriptsLoaded { scripts, errors } => {
+                write!(f, "ScriptsLoaded: scripts={}, errors={}", scripts.len(), errors.len())
+            },
+            Event::MidiPortsChanged => write!(f, "MidiPortsChanged"),
+            Event::ControllerChanged { name } => write!(f, "ControllerChanged: name={}", name),
             Event::RequestUpdateLEDs => write!(f, "RequestUpdateLEDs"),
             Event::RequestMIDIUpdate => write!(f, "RequestMIDIUpdate"),
         }
@@ -366,18 +1036,24 @@ impl fmt::Display for Event {
 
 /// EventSubscriber makes it easy to handle specific events
 pub struct EventSubscriber {
-    receiver: broadcast::Receiver<Event>,
+    receiver: EventReceiver,
     source_name: String,
-    stats: std::sync::Arc<EventStats>,
 }
 
 impl EventSubscriber {
     /// Create a new event subscriber
     pub fn new(event_bus: &EventBus, source_name: &str) -> Self {
         Self {
-            receiver: event_bus.subscribe(),
+            receiver: event_bus.subscribe_as(source_name, false),
+            source_name: source_name.to_string(),
+        }
+    }
+
+    /// Create a subscriber that only receives high-priority events
+    pub fn realtime(event_bus: &EventBus, source_name: &str) -> Self {
+        Self {
+            receiver: event_bus.subscribe_as(source_name, true),
             source_name: source_name.to_string(),
-            stats: event_bus.stats(),
         }
     }
 
@@ -385,7 +1061,6 @@ impl EventSubscriber {
     pub async fn recv_timeout(&mut self, duration: Duration) -> Result<Event, RecvTimeoutError> {
         match timeout(duration, self.receiver.recv()).await {
             Ok(Ok(event)) => {
-                self.stats.messages_received.fetch_add(1, Ordering::Relaxed);
                 debug!(handler = %self.source_name, event = %event, "Received event");
                 Ok(event)
             },
@@ -405,7 +1080,6 @@ impl EventSubscriber {
     pub async fn recv(&mut self) -> Result<Event, broadcast::error::RecvError> {
         match self.receiver.recv().await {
             Ok(event) => {
-                self.stats.messages_received.fetch_add(1, Ordering::Relaxed);
                 debug!(handler = %self.source_name, event = %event, "Received event");
                 Ok(event)
             },
@@ -426,7 +1100,6 @@ impl EventSubscriber {
         loop {
             match self.receiver.recv().await {
                 Ok(event) => {
-                    self.stats.messages_received.fetch_add(1, Ordering::Relaxed);
                     debug!(handler = %self.source_name, event = %event, "Handling event");
                     if !callback(event) {
                         debug!(handler = %self.source_name, "Stopping event handling loop (callback returned false)");
@@ -453,8 +1126,6 @@ impl EventSubscriber {
         loop {
             match self.receiver.recv().await {
                 Ok(event) => {
-                    self.stats.messages_received.fetch_add(1, Ordering::Relaxed);
-
                     // Only process high-priority events
                     if event.is_high_priority() {
                         debug!(handler = %self.source_name, event = %event, "Handling priority event");
@@ -485,7 +1156,6 @@ impl EventSubscriber {
         loop {
             match self.receiver.recv().await {
                 Ok(event) => {
-                    self.stats.messages_received.fetch_add(1, Ordering::Relaxed);
                     if let Some(extracted) = filter(&event) {
                         return Some(extracted);
                     }
@@ -495,6 +1165,11 @@ impl EventSubscriber {
         }
     }
 
+    /// Record that the last received event has been fully handled
+    pub fn record_handled(&self) {
+        self.receiver.record_handled();
+    }
+
     /// Get the name of this event subscriber
     pub fn name(&self) -> &str {
         &self.source_name
@@ -503,9 +1178,8 @@ impl EventSubscriber {
     /// Create a clone with a fresh subscription
     pub fn clone_with_new_subscription(&self, event_bus: &EventBus) -> Self {
         Self {
-            receiver: event_bus.subscribe(),
+            receiver: event_bus.subscribe_as(&self.source_name, false),
             source_name: self.source_name.clone(),
-            stats: event_bus.stats(),
         }
     }
 }
@@ -528,4 +1202,183 @@ impl fmt::Display for RecvTimeoutError {
     }
 }
 
-impl std::error::Error for RecvTimeoutError {}
\ No newline at end of file
+impl std::error::Error for RecvTimeoutError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn morph_durations_parse_in_every_unit() {
+        let parse = |text: &str| text.parse::<MorphDuration>();
+
+        assert_eq!(parse("4"), Ok(MorphDuration::Bars(4.0)));
+        assert_eq!(parse("4bars"), Ok(MorphDuration::Bars(4.0)));
+        assert_eq!(parse(" 3/4 Bar "), Ok(MorphDuration::Bars(0.75)));
+        assert_eq!(parse("0.5 bar"), Ok(MorphDuration::Bars(0.5)));
+        assert_eq!(parse("2beats"), Ok(MorphDuration::Beats(2.0)));
+        assert_eq!(parse("1/2beat"), Ok(MorphDuration::Beats(0.5)));
+        assert_eq!(parse("500ms"), Ok(MorphDuration::Millis(500)));
+        assert_eq!(parse("1.5s"), Ok(MorphDuration::Millis(1500)));
+        assert_eq!(parse("2sec"), Ok(MorphDuration::Millis(2000)));
+        assert_eq!(parse("0"), Ok(MorphDuration::Bars(0.0)));
+    }
+
+    #[test]
+    fn morph_durations_reject_bad_input() {
+        for text in ["", "bars", "-1", "1/0", "3/4/5bar", "nan", "inf", "4 parsecs", "2 bars extra"] {
+            assert!(text.parse::<MorphDuration>().is_err(), "{:?} should not parse", text);
+        }
+
+        // Parsed values are range checked too
+        assert!("257bars".parse::<MorphDuration>().is_err());
+        assert!("1025beats".parse::<MorphDuration>().is_err());
+        assert!("601s".parse::<MorphDuration>().is_err());
+    }
+
+    #[test]
+    fn morph_duration_validation_bounds_each_unit() {
+        assert!(MorphDuration::Bars(0.0).validate().is_ok());
+        assert!(MorphDuration::Bars(MAX_MORPH_BARS).validate().is_ok());
+        assert!(MorphDuration::Bars(MAX_MORPH_BARS + 0.5).validate().is_err());
+        assert!(MorphDuration::Bars(-0.25).validate().is_err());
+        assert!(MorphDuration::Bars(f64::NAN).validate().is_err());
+        assert!(MorphDuration::Beats(MAX_MORPH_BARS * 4.0).validate().is_ok());
+        assert!(MorphDuration::Beats(f64::INFINITY).validate().is_err());
+        assert!(MorphDuration::Millis(MAX_MORPH_MILLIS).validate().is_ok());
+        assert!(MorphDuration::Millis(MAX_MORPH_MILLIS + 1).validate().is_err());
+    }
+
+    #[test]
+    fn morph_durations_convert_at_a_tempo() {
+        assert_eq!(MorphDuration::Bars(2.0).seconds(120.0, 4.0), 4.0);
+        assert_eq!(MorphDuration::Beats(3.0).seconds(60.0, 4.0), 3.0);
+        assert_eq!(MorphDuration::Millis(250).seconds(120.0, 4.0), 0.25);
+        assert_eq!(MorphDuration::Bars(1.5).beats(3.0), Some(4.5));
+        assert_eq!(MorphDuration::Millis(250).beats(4.0), None);
+        assert!(MorphDuration::Beats(0.0).is_instant());
+        assert!(!MorphDuration::Millis(1).is_instant());
+    }
+
+    #[test]
+    fn launch_quantization_parses_names_and_flags() {
+        let parse = |text: &str| text.parse::<LaunchQuantize>();
+
+        assert_eq!(parse("none"), Ok(LaunchQuantize::Immediate));
+        assert_eq!(parse("off"), Ok(LaunchQuantize::Immediate));
+        assert_eq!(parse(" Beat "), Ok(LaunchQuantize::Beat));
+        assert_eq!(parse("1bar"), Ok(LaunchQuantize::Bar));
+        assert_eq!(parse("4bars"), Ok(LaunchQuantize::Phrase(4)));
+        assert_eq!(parse("16 BARS"), Ok(LaunchQuantize::Phrase(16)));
+        assert_eq!(parse("true"), Ok(LaunchQuantize::Bar));
+        assert_eq!(parse("false"), Ok(LaunchQuantize::Immediate));
+
+        for text in ["", "bars", "3bars", "32bars", "0bars", "-2bars", "2 beats", "yes"] {
+            assert!(parse(text).is_err(), "{:?} should not parse", text);
+        }
+
+        // Every quantization reads back as written
+        for quantize in [
+            LaunchQuantize::Immediate,
+            LaunchQuantize::Beat,
+            LaunchQuantize::Bar,
+            LaunchQuantize::Phrase(2),
+            LaunchQuantize::Phrase(8),
+        ] {
+            assert_eq!(parse(&quantize.to_string()), Ok(quantize));
+        }
+    }
+
+    #[test]
+    fn launch_quantization_reads_the_old_flag() {
+        let read = |json: &str| serde_json::from_str::<LaunchQuantize>(json);
+
+        assert_eq!(read("true").unwrap(), LaunchQuantize::Bar);
+        assert_eq!(read("false").unwrap(), LaunchQuantize::Immediate);
+        assert_eq!(read("\"2bars\"").unwrap(), LaunchQuantize::Phrase(2));
+        assert!(read("\"5bars\"").is_err());
+        assert!(read("1").is_err());
+
+        // Written as a name, never as a flag
+        assert_eq!(serde_json::to_string(&LaunchQuantize::Bar).unwrap(), "\"bar\"");
+        assert_eq!(serde_json::to_string(&LaunchQuantize::Immediate).unwrap(), "\"none\"");
+        assert_eq!(LaunchQuantize::Phrase(4).beats(3.0), Some(12.0));
+        assert_eq!(LaunchQuantize::Immediate.beats(4.0), None);
+    }
+
+    #[tokio::test]
+    async fn lanes_are_received_in_publish_order() {
+        let bus = EventBus::new(16, "test");
+        let mut receiver = bus.subscribe();
+
+        bus.publish(Event::MorphCompleted).unwrap();
+        bus.publish(Event::PadPressed { pad: 8, velocity: 100 }).unwrap();
+        bus.publish(Event::ProjectSaved).unwrap();
+        bus.publish(Event::Shutdown).unwrap();
+
+        let received: Vec<&str> = [
+            receiver.recv().await.unwrap(),
+            receiver.recv().await.unwrap(),
+            receiver.recv().await.unwrap(),
+            receiver.recv().await.unwrap(),
+        ]
+        .iter()
+        .map(Event::event_type)
+        .collect();
+        assert_eq!(received, vec!["MorphCompleted", "PadPressed", "ProjectSaved", "Shutdown"]);
+    }
+
+    #[tokio::test]
+    async fn realtime_subscribers_only_see_their_lane() {
+        let bus = EventBus::new(16, "test");
+        let mut receiver = bus.subscribe_realtime();
+
+        // Nobody listens to the normal lane, so this one is not delivered at all
+        assert!(bus.publish(Event::MorphCompleted).is_err());
+        bus.publish(Event::PadPressed { pad: 8, velocity: 100 }).unwrap();
+
+        assert_eq!(receiver.recv().await.unwrap().event_type(), "PadPressed");
+        assert!(matches!(receiver.try_recv(), Err(broadcast::error::TryRecvError::Empty)));
+    }
+
+    #[test]
+    fn subscribers_are_counted_once_whichever_lanes_they_take() {
+        let bus = EventBus::new(16, "test");
+        let _normal = bus.subscribe();
+        let _realtime = bus.subscribe_realtime();
+        let realtime = bus.subscribe_realtime();
+
+        assert_eq!(bus.receiver_count(), 3);
+        drop(realtime);
+        assert_eq!(bus.receiver_count(), 2);
+        assert_eq!(bus.stats().subscribers_peak.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn a_closed_bus_drains_before_closing() {
+        let bus = EventBus::new(16, "test");
+        let mut receiver = bus.subscribe();
+
+        bus.publish(Event::ProjectSaved).unwrap();
+        drop(bus);
+
+        assert_eq!(receiver.recv().await.unwrap().event_type(), "ProjectSaved");
+        assert!(matches!(receiver.recv().await, Err(broadcast::error::RecvError::Closed)));
+    }
+
+    #[test]
+    fn pad_to_cc_times_the_first_cc_after_a_press() {
+        let bus = EventBus::new(16, "test");
+        let _receiver = bus.subscribe();
+
+        // A CC without a press before it is not timed
+        bus.record_pad_to_cc();
+        bus.publish(Event::PadPressed { pad: 8, velocity: 100 }).unwrap();
+        bus.record_pad_to_cc();
+        bus.record_pad_to_cc();
+
+        assert_eq!(bus.metrics().pad_to_cc.count, 1);
+        bus.reset_stats();
+        assert_eq!(bus.metrics().pad_to_cc.count, 0);
+    }
+}
@@ -0,0 +1,651 @@
+// src-tauri/src/exchange.rs
+use crate::curves::UserCurve;
+use crate::model::{Bank, MorphChain, Parameter, ParameterTiming, Project, SNAP_PADS};
+use serde::{Deserialize, Serialize};
+
+/// Default value given to a parameter a snap has no value for
+const DEFAULT_VALUE: u8 = 64;
+
+/// A single bank together with the parameter definitions its snap values refer to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BankExport {
+    /// Parameters in the order of each snap's values
+    pub parameters: Vec<Parameter>,
+
+    /// The exported bank
+    pub bank: Bank,
+
+    /// User curves the bank's snaps and parameters refer to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub curves: Vec<UserCurve>,
+}
+
+/// A parameter that could not be matched cleanly against the target project
+#[derive(Clone, Debug, Serialize)]
+pub struct ParameterConflict {
+    /// The incoming parameter definition
+    pub incoming: Parameter,
+
+    /// Index of the target parameter involved in the conflict
+    pub target_id: usize,
+
+    /// Human-readable explanation
+    pub reason: String,
+}
+
+/// How incoming parameters map onto a target parameter list
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ParameterMapping {
+    /// Target index for each incoming parameter, counting the parameters still to be created
+    pub targets: Vec<usize>,
+
+    /// Incoming parameters to create, in the order they are appended to the target
+    pub created: Vec<usize>,
+
+    /// Problems found while matching
+    pub conflicts: Vec<ParameterConflict>,
+}
+
+impl ParameterMapping {
+    /// Match incoming parameters against a target list.
+    ///
+    /// The CC number identifies a parameter, since it is what goes out on the wire.
+    /// A matching CC with a different name is still mapped but reported, as is a CC
+    /// used by several target parameters. Failing that, a parameter with the same ID
+    /// and name is matched by ID and its CC change reported. A name that exists in
+    /// the target on a different CC is reported and the incoming parameter is created
+    /// separately.
+    ///
+    /// Two incoming parameters never get separate targets for the same CC: the later
+    /// one shares the earlier one's target and is reported, and only the earlier
+    /// one's values are kept.
+    pub fn plan(target: &[Parameter], incoming: &[Parameter]) -> Self {
+        let mut mapping = Self::default();
+
+        for (incoming_id, param) in incoming.iter().enumerate() {
+            if let Some(first) = incoming[..incoming_id].iter().position(|earlier| earlier.cc == param.cc) {
+                let target_id = mapping.targets[first];
+                mapping.conflicts.push(ParameterConflict {
+                    incoming: param.clone(),
+                    target_id,
+                    reason: format!(
+                        "CC {} appears more than once in the import, '{}' shares '{}' and its values are dropped",
+                        param.cc, param.name, incoming[first].name
+                    ),
+                });
+                mapping.targets.push(target_id);
+                continue;
+            }
+
+            let by_cc: Vec<usize> = target
+                .iter()
+                .enumerate()
+                .filter(|(_, t)| t.cc == param.cc)
+                .map(|(idx, _)| idx)
+                .collect();
+
+            if let Some(&target_id) = by_cc.first() {
+                if by_cc.len() > 1 {
+                    mapping.conflicts.push(ParameterConflict {
+                        incoming: param.clone(),
+                        target_id,
+                        reason: format!(
+                            "CC {} is used by {} parameters in the target, mapped to '{}'",
+                            param.cc,
+                            by_cc.len(),
+                            target[target_id].name
+                        ),
+                    });
+                } else if !same_name(&target[target_id].name, &param.name) {
+                    mapping.conflicts.push(ParameterConflict {
+                        incoming: param.clone(),
+                        target_id,
+                        reason: format!(
+                            "CC {} is named '{}' in the target but '{}' in the import",
+                            param.cc, target[target_id].name, param.name
+                        ),
+                    });
+                }
+                if mapping.targets.contains(&target_id) {
+                    mapping.conflicts.push(ParameterConflict {
+                        incoming: param.clone(),
+                        target_id,
+                        reason: format!(
+                            "'{}' was already matched by ID, the values of '{}' are dropped",
+                            target[target_id].name, param.name
+                        ),
+                    });
+                }
+                mapping.targets.push(target_id);
+                continue;
+            }
+
+            // Same ID and name, so only the CC has moved; the target keeps its CC
+            let by_id = target
+                .get(incoming_id)
+                .filter(|t| same_name(&t.name, &param.name) && !mapping.targets.contains(&incoming_id));
+            if let Some(existing) = by_id {
+                mapping.conflicts.push(ParameterConflict {
+                    incoming: param.clone(),
+                    target_id: incoming_id,
+                    reason: format!(
+                        "'{}' is on CC {} in the target but CC {} in the import, mapped by ID",
+                        param.name, existing.cc, param.cc
+                    ),
+                });
+                mapping.targets.push(incoming_id);
+                continue;
+            }
+
+            if let Some(target_id) = target.iter().position(|t| same_name(&t.name, &param.name)) {
+                mapping.conflicts.push(ParameterConflict {
+                    incoming: param.clone(),
+                    target_id,
+                    reason: format!(
+                        "'{}' is on CC {} in the target but CC {} in the import, created as a new parameter",
+                        param.name, target[target_id].cc, param.cc
+                    ),
+                });
+            }
+            mapping.targets.push(target.len() + mapping.created.len());
+            mapping.created.push(incoming_id);
+        }
+
+        mapping
+    }
+
+    /// Number of incoming parameters that will be created in the target
+    pub fn created_count(&self) -> usize {
+        self.created.len()
+    }
+}
+
+/// Outcome of importing a bank into a project
+#[derive(Clone, Debug, Serialize)]
+pub struct ImportReport {
+    /// Index of the newly added bank
+    pub bank_id: usize,
+
+    /// Names of parameters created in the target project
+    pub created: Vec<String>,
+
+    /// Problems found while matching parameters
+    pub conflicts: Vec<ParameterConflict>,
+
+    /// IDs of curves added to the target project
+    pub added_curves: Vec<String>,
+
+    /// Curves left out because the target has a different one with the same ID
+    pub skipped: Vec<String>,
+}
+
+/// Export a bank with the parameters its snap values refer to
+pub fn export_bank(project: &Project, bank_id: usize) -> Result<BankExport, String> {
+    let bank = project
+        .banks
+        .get(bank_id)
+        .ok_or_else(|| "Bank ID out of range".to_string())?;
+
+    // Snaps only refer to as many parameters as they carry values for
+    let referenced = bank
+        .snaps
+        .iter()
+        .map(|snap| snap.values.len())
+        .max()
+        .unwrap_or(0)
+        .min(project.parameters.len());
+
+    let mut bank = bank.clone();
+    for snap in &mut bank.snaps {
+        snap.values.truncate(referenced);
+        snap.morph_timings.retain(|timing| timing.param_id < referenced);
+    }
+
+    let parameters = project.parameters[..referenced].to_vec();
+
+    // Curve overrides on the snaps or the parameters travel with the bank
+    let curve_ids: Vec<&String> = bank
+        .snaps
+        .iter()
+        .flat_map(|snap| snap.morph_timings.iter().map(|timing| &timing.timing))
+        .chain(parameters.iter().filter_map(|param| param.morph_timing.as_ref()))
+        .filter_map(|timing| timing.curve.as_ref())
+        .collect();
+    let curves = project
+        .curves
+        .iter()
+        .filter(|curve| curve_ids.contains(&&curve.id))
+        .cloned()
+        .collect();
+
+    Ok(BankExport {
+        parameters,
+        bank,
+        curves,
+    })
+}
+
+/// Import an exported bank, appending it to the project's banks
+pub fn import_bank(project: &mut Project, export: BankExport) -> Result<ImportReport, String> {
+    if export.bank.snaps.len() > SNAP_PADS {
+        return Err(format!(
+            "Bank '{}' has {} snaps, but a bank holds at most {}",
+            export.bank.name,
+            export.bank.snaps.len(),
+            SNAP_PADS
+        ));
+    }
+
+    let mapping = ParameterMapping::plan(&project.parameters, &export.parameters);
+    let targets = add_missing_parameters(project, &export.parameters, &mapping);
+
+    let mut bank = export.bank;
+    let param_count = project.parameters.len();
+    for snap in &mut bank.snaps {
+        snap.values = remap_values(&snap.values, &targets, param_count);
+        snap.morph_timings = remap_timings(&snap.morph_timings, &targets);
+    }
+
+    project.banks.push(bank);
+
+    let mut skipped = Vec::new();
+    let added_curves = add_curves(project, &export.curves, &mut skipped);
+
+    Ok(ImportReport {
+        bank_id: project.banks.len() - 1,
+        created: created_names(&mapping, &export.parameters),
+        conflicts: mapping.conflicts,
+        added_curves,
+        skipped,
+    })
+}
+
+/// Append the parameters the mapping could not match, padding every existing snap
+/// so values stay aligned. Returns the resolved target index for each incoming parameter.
+pub fn add_missing_parameters(
+    project: &mut Project,
+    incoming: &[Parameter],
+    mapping: &ParameterMapping,
+) -> Vec<usize> {
+    for &incoming_id in &mapping.created {
+        // Existing snaps need a value for the new parameter before it is added
+        let new_id = project.parameters.len();
+        for bank in &mut project.banks {
+            for snap in &mut bank.snaps {
+                // Values past the defined parameters stay where they are
+                if snap.values.len() <= new_id {
+                    snap.values.resize(new_id + 1, DEFAULT_VALUE);
+                }
+                snap.values[new_id] = DEFAULT_VALUE;
+            }
+        }
+        project.parameters.push(incoming[incoming_id].clone());
+    }
+
+    mapping.targets.clone()
+}
+
+/// Reorder values into the target parameter order.
+/// When several values land on the same target, the first one is kept.
+pub fn remap_values(values: &[u8], targets: &[usize], param_count: usize) -> Vec<u8> {
+    let mut remapped = vec![DEFAULT_VALUE; param_count];
+    let mut filled = vec![false; param_count];
+
+    for (value, &target_id) in values.iter().zip(targets) {
+        if target_id < param_count && !filled[target_id] {
+            remapped[target_id] = *value;
+            filled[target_id] = true;
+        }
+    }
+
+    remapped
+}
+
+/// Point per-parameter morph timings at the target parameter order,
+/// keeping the first timing for each target like `remap_values`
+pub fn remap_timings(timings: &[ParameterTiming], targets: &[usize]) -> Vec<ParameterTiming> {
+    let mut remapped: Vec<ParameterTiming> = Vec::with_capacity(timings.len());
+
+    for timing in timings {
+        let Some(&target_id) = targets.get(timing.param_id) else {
+            continue;
+        };
+        // Timings follow the value that was kept, not whichever timing comes first
+        let kept_from = targets.iter().position(|&t| t == target_id);
+        if kept_from == Some(timing.param_id) && !remapped.iter().any(|t| t.param_id == target_id) {
+            remapped.push(ParameterTiming {
+                param_id: target_id,
+                timing: timing.timing.clone(),
+            });
+        }
+    }
+
+    remapped
+}
+
+/// Add the curves the project lacks. A different curve under an ID the project
+/// already uses is left out and noted in `skipped`; references to it then use
+/// the project's curve.
+pub fn add_curves(project: &mut Project, incoming: &[UserCurve], skipped: &mut Vec<String>) -> Vec<String> {
+    let mut added = Vec::new();
+
+    for curve in incoming {
+        match project.curves.iter().find(|existing| existing.id == curve.id) {
+            Some(existing) if existing == curve => {}
+            Some(_) => skipped.push(format!(
+                "Curve '{}' differs from the project's curve with the same ID, kept the project's",
+                curve.id
+            )),
+            None => {
+                project.curves.push(curve.clone());
+                added.push(curve.id.clone());
+            }
+        }
+    }
+
+    added
+}
+
+/// Add the chains the project lacks, like `add_curves`. A chain whose pad
+/// already starts another chain is added without its pad.
+pub fn add_chains(project: &mut Project, incoming: &[MorphChain], skipped: &mut Vec<String>) -> Vec<String> {
+    let mut added = Vec::new();
+
+    for chain in incoming {
+        match project.chains.iter().find(|existing| existing.id == chain.id) {
+            Some(existing) if existing == chain => {}
+            Some(_) => skipped.push(format!(
+                "Chain '{}' differs from the project's chain with the same ID, kept the project's",
+                chain.id
+            )),
+            None => {
+                let mut chain = chain.clone();
+                if let Some(other) = project.chains.iter().find(|other| chain.pad.is_some() && other.pad == chain.pad) {
+                    skipped.push(format!(
+                        "Chain '{}' was added without its pad, which already starts chain '{}'",
+                        chain.id, other.id
+                    ));
+                    chain.pad = None;
+                }
+                added.push(chain.id.clone());
+                project.chains.push(chain);
+            }
+        }
+    }
+
+    added
+}
+
+fn created_names(mapping: &ParameterMapping, incoming: &[Parameter]) -> Vec<String> {
+    mapping
+        .created
+        .iter()
+        .map(|&incoming_id| incoming[incoming_id].name.clone())
+        .collect()
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// Outcome of merging one project into another
+#[derive(Clone, Debug, Serialize)]
+pub struct MergeReport {
+    /// True if the merge was only previewed
+    pub dry_run: bool,
+
+    /// Names of parameters added to the base project
+    pub added_parameters: Vec<String>,
+
+    /// Names of banks appended to the base project
+    pub added_banks: Vec<String>,
+
+    /// Problems found while matching parameters
+    pub conflicts: Vec<ParameterConflict>,
+
+    /// IDs of curves added to the base project
+    pub added_curves: Vec<String>,
+
+    /// IDs of morph chains added to the base project
+    pub added_chains: Vec<String>,
+
+    /// Curves and chains left out or changed because the base project already uses their ID or pad
+    pub skipped: Vec<String>,
+
+    /// Parameter count after the merge
+    pub parameter_count: usize,
+
+    /// Bank count after the merge
+    pub bank_count: usize,
+}
+
+/// Merge `other` into `base`: parameters are unioned (deduplicated by CC, within
+/// `other` as well as against `base`), banks are appended and every incoming snap
+/// is remapped to the merged parameter order. Curves and chains the base lacks are added.
+/// With `dry_run` the base project is left untouched and only the report is produced.
+pub fn merge_projects(base: &mut Project, other: &Project, dry_run: bool) -> Result<MergeReport, String> {
+    if let Some(bank) = other.banks.iter().find(|bank| bank.snaps.len() > SNAP_PADS) {
+        return Err(format!(
+            "Bank '{}' has {} snaps, but a bank holds at most {}",
+            bank.name,
+            bank.snaps.len(),
+            SNAP_PADS
+        ));
+    }
+
+    let mapping = ParameterMapping::plan(&base.parameters, &other.parameters);
+
+    // Work on a copy for previews so the merge logic is identical in both modes
+    let mut preview;
+    let target = if dry_run {
+        preview = base.clone();
+        &mut preview
+    } else {
+        base
+    };
+
+    let targets = add_missing_parameters(target, &other.parameters, &mapping);
+    let param_count = target.parameters.len();
+
+    let mut added_banks = Vec::with_capacity(other.banks.len());
+    for bank in &other.banks {
+        let mut bank = bank.clone();
+        for snap in &mut bank.snaps {
+            snap.values = remap_values(&snap.values, &targets, param_count);
+            snap.morph_timings = remap_timings(&snap.morph_timings, &targets);
+        }
+        added_banks.push(bank.name.clone());
+        target.banks.push(bank);
+    }
+
+    let mut skipped = Vec::new();
+    let added_curves = add_curves(target, &other.curves, &mut skipped);
+    let added_chains = add_chains(target, &other.chains, &mut skipped);
+
+    Ok(MergeReport {
+        dry_run,
+        added_parameters: created_names(&mapping, &other.parameters),
+        added_banks,
+        conflicts: mapping.conflicts,
+        added_curves,
+        added_chains,
+        skipped,
+        parameter_count: param_count,
+        bank_count: target.banks.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, cc: u8) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            description: String::new(),
+            cc,
+            morph_timing: None,
+            motion: Default::default(),
+        }
+    }
+
+    #[test]
+    fn plan_matches_by_cc_and_creates_the_rest() {
+        let target = [param("Cutoff", 74), param("Resonance", 71)];
+        let incoming = [param("Resonance", 71), param("Drive", 20)];
+
+        let mapping = ParameterMapping::plan(&target, &incoming);
+
+        assert_eq!(mapping.targets, vec![1, 2]);
+        assert_eq!(mapping.created, vec![1]);
+        assert!(mapping.conflicts.is_empty());
+    }
+
+    #[test]
+    fn plan_reports_a_renamed_cc() {
+        let target = [param("Cutoff", 74)];
+        let incoming = [param("Filter", 74)];
+
+        let mapping = ParameterMapping::plan(&target, &incoming);
+
+        assert_eq!(mapping.targets, vec![0]);
+        assert_eq!(mapping.conflicts.len(), 1);
+    }
+
+    #[test]
+    fn plan_matches_by_id_when_the_cc_moved() {
+        let target = [param("Cutoff", 74), param("Resonance", 71)];
+        let incoming = [param("Cutoff", 75)];
+
+        let mapping = ParameterMapping::plan(&target, &incoming);
+
+        assert_eq!(mapping.targets, vec![0]);
+        assert!(mapping.created.is_empty());
+        assert_eq!(mapping.conflicts.len(), 1);
+    }
+
+    #[test]
+    fn plan_creates_a_name_found_at_another_id() {
+        let target = [param("Drive", 20), param("Cutoff", 74)];
+        let incoming = [param("Cutoff", 75)];
+
+        let mapping = ParameterMapping::plan(&target, &incoming);
+
+        assert_eq!(mapping.targets, vec![2]);
+        assert_eq!(mapping.created, vec![0]);
+        assert_eq!(mapping.conflicts[0].target_id, 1);
+    }
+
+    #[test]
+    fn plan_shares_a_target_between_incoming_duplicates() {
+        let target = [param("Cutoff", 74)];
+        let incoming = [param("Drive", 20), param("Drive 2", 20), param("Cutoff", 74), param("Cut", 74)];
+
+        let mapping = ParameterMapping::plan(&target, &incoming);
+
+        assert_eq!(mapping.targets, vec![1, 1, 0, 0]);
+        assert_eq!(mapping.created, vec![0]);
+        assert_eq!(mapping.conflicts.len(), 2);
+    }
+
+    fn project(parameters: Vec<Parameter>, values: Vec<u8>) -> Project {
+        let mut project = Project {
+            parameters,
+            ..Project::default()
+        };
+        project.banks[0].snaps[0].values = values;
+        project
+    }
+
+    #[test]
+    fn merge_dedupes_incoming_parameters() {
+        let mut base = project(vec![param("Cutoff", 74)], vec![1]);
+        let other = project(vec![param("Drive", 20), param("Drive", 20), param("Cutoff", 74)], vec![2, 3, 4]);
+
+        let report = merge_projects(&mut base, &other, false).unwrap();
+
+        assert_eq!(report.added_parameters, vec!["Drive".to_string()]);
+        assert_eq!(base.parameters.len(), 2);
+        assert_eq!(base.banks[0].snaps[0].values, vec![1, DEFAULT_VALUE]);
+        assert_eq!(base.banks[1].snaps[0].values, vec![4, 2]);
+    }
+
+    #[test]
+    fn merge_does_not_truncate_longer_snaps() {
+        // A snap may carry values for more parameters than the project defines
+        let mut base = project(vec![param("Cutoff", 74)], vec![1, 9, 9]);
+        let other = project(vec![param("Drive", 20)], vec![2]);
+
+        merge_projects(&mut base, &other, false).unwrap();
+
+        // The new parameter's slot starts at the default, not at a leftover value
+        assert_eq!(base.banks[0].snaps[0].values, vec![1, DEFAULT_VALUE, 9]);
+    }
+
+    #[test]
+    fn merge_dry_run_leaves_the_base_alone() {
+        let mut base = project(vec![param("Cutoff", 74)], vec![1]);
+        let other = project(vec![param("Drive", 20)], vec![2]);
+
+        let report = merge_projects(&mut base, &other, true).unwrap();
+
+        assert_eq!(report.parameter_count, 2);
+        assert_eq!(report.bank_count, 2);
+        assert_eq!(base.parameters.len(), 1);
+        assert_eq!(base.banks.len(), 1);
+    }
+
+    fn stepped(id: &str, steps: u32) -> UserCurve {
+        UserCurve {
+            id: id.to_string(),
+            name: id.to_string(),
+            shape: crate::curves::CurveShape::Stepped { steps },
+        }
+    }
+
+    #[test]
+    fn export_carries_the_curves_the_bank_uses() {
+        let mut project = project(vec![param("Cutoff", 74)], vec![1]);
+        project.curves = vec![stepped("used", 4), stepped("unused", 4)];
+        project.banks[0].snaps[0].morph_timings = vec![ParameterTiming {
+            param_id: 0,
+            timing: crate::model::MorphTiming {
+                curve: Some("used".to_string()),
+                ..Default::default()
+            },
+        }];
+
+        let export = export_bank(&project, 0).unwrap();
+
+        assert_eq!(export.curves, vec![stepped("used", 4)]);
+    }
+
+    #[test]
+    fn merge_keeps_the_base_curve_on_an_id_clash() {
+        let mut base = project(vec![param("Cutoff", 74)], vec![1]);
+        base.curves = vec![stepped("shared", 4)];
+        let mut other = project(vec![param("Cutoff", 74)], vec![2]);
+        other.curves = vec![stepped("shared", 8), stepped("new", 3)];
+
+        let report = merge_projects(&mut base, &other, false).unwrap();
+
+        assert_eq!(report.added_curves, vec!["new".to_string()]);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(base.curves, vec![stepped("shared", 4), stepped("new", 3)]);
+    }
+
+    #[test]
+    fn remap_values_reorders_and_pads() {
+        assert_eq!(remap_values(&[10, 20], &[2, 0], 3), vec![20, DEFAULT_VALUE, 10]);
+    }
+
+    #[test]
+    fn remap_values_keeps_the_first_value_for_a_shared_target() {
+        assert_eq!(remap_values(&[10, 20, 30], &[0, 0, 1], 2), vec![10, 30]);
+    }
+
+    #[test]
+    fn remap_values_ignores_missing_and_out_of_range_targets() {
+        assert_eq!(remap_values(&[10, 20, 30], &[5, 0], 1), vec![20]);
+    }
+}
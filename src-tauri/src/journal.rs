@@ -0,0 +1,188 @@
+// src-tauri/src/journal.rs
+use crate::events::{Event, EventBus};
+use crate::supervisor::{supervise, EventHandler, Flow};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
+use tracing::{info, warn};
+
+/// Environment variable naming the file to record a journal to
+pub const JOURNAL_ENV: &str = "SNAPBLASTER_JOURNAL";
+
+/// One recorded event
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Microseconds since recording started, from a monotonic clock
+    pub t_us: u64,
+
+    /// The event as it went through the bus
+    pub event: Event,
+}
+
+/// How fast a journal is fed back into the bus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    /// Keep the recorded spacing between events
+    RealTime,
+    /// Keep the recorded spacing on a paused clock that skips ahead whenever every
+    /// task is waiting, so morphs run through the same steps without the wait
+    Fast,
+}
+
+impl ReplaySpeed {
+    /// The runtime to replay on. Everything in the app that keeps time, morphs
+    /// included, uses the Tokio clock, so it all follows the replay clock.
+    pub fn runtime(self) -> std::io::Result<tokio::runtime::Runtime> {
+        match self {
+            ReplaySpeed::RealTime => tokio::runtime::Builder::new_multi_thread().enable_all().build(),
+            // A paused clock needs a single-threaded runtime
+            #[cfg(feature = "fast-replay")]
+            ReplaySpeed::Fast => tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .start_paused(true)
+                .build(),
+            #[cfg(not(feature = "fast-replay"))]
+            ReplaySpeed::Fast => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Fast replay needs the fast-replay feature",
+            )),
+        }
+    }
+}
+
+/// JournalRecorder writes every event on the bus to a JSON Lines file
+pub struct JournalRecorder {
+    writer: Arc<Mutex<BufWriter<File>>>,
+    started: Instant,
+}
+
+impl JournalRecorder {
+    /// Start recording to `path`, replacing any existing file
+    pub fn start(event_bus: &EventBus, path: &Path) -> Result<JoinHandle<()>, Box<dyn Error>> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+
+        let writer = Arc::new(Mutex::new(BufWriter::new(File::create(path)?)));
+        let started = Instant::now();
+        info!("Recording event journal to {:?}", path);
+
+        // A restarted recorder keeps appending to the same file on the same clock
+        Ok(supervise(event_bus, "journal-recorder", move || JournalRecorder {
+            writer: writer.clone(),
+            started,
+        }))
+    }
+
+    fn write(&self, entry: &JournalEntry) -> Result<(), Box<dyn Error>> {
+        let line = serde_json::to_string(entry)?;
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "{}", line)?;
+
+        // Flush every line so a crash on stage still leaves the events leading up to it
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl EventHandler for JournalRecorder {
+    async fn handle(&mut self, event: Event) -> Flow {
+        let shutting_down = matches!(event, Event::Shutdown);
+        let entry = JournalEntry {
+            t_us: self.started.elapsed().as_micros() as u64,
+            event,
+        };
+
+        if let Err(e) = self.write(&entry) {
+            warn!("Failed to write journal entry: {}", e);
+        }
+
+        if shutting_down {
+            info!("Event journal closed");
+            return Flow::Stop;
+        }
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, missed: u64) {
+        if missed > 0 {
+            warn!(
+                "Event journal is missing {} events at {}us",
+                missed,
+                self.started.elapsed().as_micros()
+            );
+        }
+    }
+}
+
+/// Read a journal file
+pub fn read_journal(path: &Path) -> Result<Vec<JournalEntry>, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let mut entries = Vec::new();
+
+    for (line_no, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = serde_json::from_str(line)
+            .map_err(|e| format!("{}:{}: {}", path.display(), line_no + 1, e))?;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Events that come from outside the app (the controller, Link, and commands from
+/// the UI, remote surfaces and scripts) rather than being derived by it. Replaying
+/// only these lets the app regenerate the rest.
+pub fn is_input(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::PadPressed { .. }
+            | Event::PadReleased { .. }
+            | Event::PadPressure { .. }
+            | Event::ControlInput { .. }
+            | Event::LinkStatusChanged { .. }
+            | Event::LinkTempoChanged { .. }
+            | Event::LinkTransportChanged { .. }
+            | Event::CommandIssued { .. }
+    )
+}
+
+/// Events recording that a project file was opened, created, imported or merged. The
+/// file's contents are not journaled, so a replay cannot repeat them.
+pub fn replaces_project(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::ProjectLoaded | Event::BankImported { .. } | Event::ProjectMerged { .. }
+    )
+}
+
+/// Publish the entries accepted by `filter`, in order and with their recorded
+/// spacing on the runtime's clock (see `ReplaySpeed::runtime`). Returns how many
+/// were published.
+pub async fn replay<F>(entries: &[JournalEntry], event_bus: &EventBus, filter: F) -> usize
+where
+    F: Fn(&Event) -> bool,
+{
+    let started = Instant::now();
+    let first_t = entries.first().map_or(0, |entry| entry.t_us);
+    let mut published = 0;
+
+    for entry in entries.iter().filter(|entry| filter(&entry.event)) {
+        let offset = Duration::from_micros(entry.t_us.saturating_sub(first_t));
+        time::sleep_until(started + offset).await;
+
+        event_bus.try_publish(entry.event.clone());
+        published += 1;
+    }
+
+    published
+}
@@ -0,0 +1,189 @@
+// src-tauri/src/launch.rs
+use crate::control::ControlService;
+use crate::events::{Event, EventBus, LaunchQuantize};
+use crate::link::LinkSynchronizer;
+use crate::model::{PendingLaunch, SharedState};
+use crate::supervisor::{supervise, EventHandler, Flow};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+/// Launcher holds quantized snap recalls until their point on the Link beat grid.
+/// Quantized morphs wait in the morph engine instead.
+pub struct Launcher {
+    control: ControlService,
+    state: SharedState,
+    event_bus: EventBus,
+    link: Option<LinkSynchronizer>,
+
+    /// The recall waiting for its launch point, if any
+    waiting: Option<JoinHandle<()>>,
+
+    /// Whether a morph is under way, as of the last event; it selects the
+    /// snaps it arrives on, which do not replace a waiting recall
+    morphing: bool,
+}
+
+impl Launcher {
+    /// Create a launcher that recalls snaps through `control`
+    pub fn new(
+        control: ControlService,
+        state: SharedState,
+        event_bus: EventBus,
+        link: Option<LinkSynchronizer>,
+    ) -> Self {
+        Self {
+            control,
+            state,
+            event_bus,
+            link,
+            waiting: None,
+            morphing: false,
+        }
+    }
+
+    /// Start the launcher as a supervised event handler
+    pub fn start(self) -> JoinHandle<()> {
+        info!("Starting launcher");
+
+        let event_bus = self.event_bus.clone();
+        supervise(&event_bus, "launcher", move || {
+            Launcher::new(
+                self.control.clone(),
+                self.state.clone(),
+                self.event_bus.clone(),
+                self.link.clone(),
+            )
+        })
+    }
+
+    /// Drop the waiting recall, if any
+    fn cancel(&mut self) {
+        if let Some(task) = self.waiting.take() {
+            task.abort();
+        }
+
+        let mut state_guard = self.state.write().unwrap();
+        if state_guard.pending_launch.is_some_and(|pending| !pending.morph) {
+            state_guard.pending_launch = None;
+            drop(state_guard);
+            self.event_bus.try_publish(Event::RequestUpdateLEDs);
+        }
+    }
+
+    /// Time until the launch point; zero when Link is off or cannot say
+    async fn time_to_launch(&self, quantize: LaunchQuantize) -> Duration {
+        let Some(link) = &self.link else {
+            return Duration::ZERO;
+        };
+
+        match link.status().await {
+            Ok(status) if status.enabled => link.time_to_next_launch(quantize).await.unwrap_or_else(|e| {
+                warn!("Recalling without quantization: {}", e);
+                Duration::ZERO
+            }),
+            Ok(_) => {
+                debug!("Link is disabled, recalling without quantization");
+                Duration::ZERO
+            }
+            Err(e) => {
+                warn!("Recalling without quantization: {}", e);
+                Duration::ZERO
+            }
+        }
+    }
+}
+
+impl Drop for Launcher {
+    /// A launcher torn down by its supervisor takes its waiting recall with it
+    fn drop(&mut self) {
+        if let Some(task) = self.waiting.take() {
+            task.abort();
+        }
+    }
+}
+
+impl EventHandler for Launcher {
+    async fn handle(&mut self, event: Event) -> Flow {
+        match event {
+            Event::LaunchScheduled {
+                bank_id,
+                snap_id,
+                quantize,
+            } => {
+                // The latest recall replaces one still waiting
+                self.cancel();
+
+                let wait = self.time_to_launch(quantize).await;
+                if wait.is_zero() {
+                    if let Err(e) = self.control.recall_snap(bank_id, snap_id) {
+                        error!("Failed to recall snap {} of bank {}: {}", snap_id, bank_id, e);
+                    }
+                    return Flow::Continue;
+                }
+
+                info!("Recalling snap {} on the next {} in {} ms", snap_id, quantize, wait.as_millis());
+                let pending = PendingLaunch {
+                    bank_id,
+                    snap_id,
+                    morph: false,
+                };
+                self.state.write().unwrap().pending_launch = Some(pending);
+                self.event_bus.try_publish(Event::RequestUpdateLEDs);
+
+                let (control, state) = (self.control.clone(), self.state.clone());
+                self.waiting = Some(tokio::spawn(async move {
+                    tokio::time::sleep(wait).await;
+
+                    {
+                        let mut state_guard = state.write().unwrap();
+                        if state_guard.pending_launch == Some(pending) {
+                            state_guard.pending_launch = None;
+                        }
+                    }
+                    if let Err(e) = control.recall_snap(bank_id, snap_id) {
+                        error!("Failed to recall snap {} of bank {}: {}", snap_id, bank_id, e);
+                    }
+                }));
+            }
+
+            Event::MorphProgressed { .. } => self.morphing = true,
+            Event::MorphCompleted => self.morphing = false,
+
+            // A snap selected some other way overrides the waiting recall
+            Event::SnapSelected { .. } if self.morphing => {}
+            Event::SnapSelected { .. } | Event::BankSelected { .. } => {
+                // The waiting recall clears its pending launch before it selects its snap
+                let waiting = self.state.read().unwrap().pending_launch.is_some_and(|pending| !pending.morph);
+                if waiting {
+                    info!("Dropping the waiting recall");
+                    self.cancel();
+                }
+            }
+
+            // The waiting recall belongs to the project that was open
+            Event::ProjectLoaded => self.cancel(),
+
+            Event::Shutdown => {
+                self.cancel();
+                return Flow::Stop;
+            }
+
+            _ => {}
+        }
+
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, _missed: u64) {
+        self.morphing = self.state.read().unwrap().active_morph.is_some();
+
+        // A restarted launcher has lost its waiting recall
+        if self.waiting.is_none() {
+            let mut state_guard = self.state.write().unwrap();
+            if state_guard.pending_launch.is_some_and(|pending| !pending.morph) {
+                state_guard.pending_launch = None;
+            }
+        }
+    }
+}
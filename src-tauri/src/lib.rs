@@ -1,9 +1,23 @@
 // Re-export modules for easier imports
 pub mod ai;
+pub mod api;
+pub mod control;
+pub mod curves;
 pub mod events;
+pub mod exchange;
+pub mod journal;
+pub mod launch;
+pub mod mcp;
+pub mod metrics;
 pub mod model;
 pub mod morph;
+pub mod osc;
+pub mod runtime;
+pub mod scripting;
+pub mod session;
 pub mod storage;
+pub mod supervisor;
+pub mod templates;
 pub mod link;
 
 // MIDI subsystem
@@ -19,18 +33,34 @@ pub mod midi {
 // App state and initialization
 pub mod app {
     use crate::ai::AIService;
+    use crate::api::{self, ApiSettings};
+    use crate::control::ControlService;
     use crate::events::EventBus;
+    use crate::exchange::{ImportReport, MergeReport};
+    use crate::journal::{JournalRecorder, JOURNAL_ENV};
+    use crate::launch::Launcher;
+    use crate::metrics::{self, DEFAULT_REPORT_INTERVAL};
     use crate::link::LinkSynchronizer;
+    use crate::mcp::{self, McpSettings};
     use crate::midi::manager::MidiManager;
-    use crate::model::{new_shared_state, SharedState};
+    use crate::model::{SharedState, MORPH_DURATION_BARS, QUANTUM_RANGE, TEMPO_RANGE};
     use crate::morph::MorphEngine;
+    use crate::osc::{OscServer, OscSettings};
+    use crate::runtime::RuntimeTracker;
+    use crate::scripting::ScriptHost;
+    use crate::session::{Session, SessionStore};
     use crate::storage::ProjectStorage;
+    use crate::templates::{TemplateInfo, TemplateStore};
     use std::error::Error;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
     use std::sync::Arc;
+    use std::time::Duration;
     use tokio::task::JoinHandle;
     use tracing::{error, info, warn};
 
+    /// How long shutdown waits for the session to be written
+    const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
     /// Main application state
     pub struct App {
         state: SharedState,
@@ -38,14 +68,24 @@ pub mod app {
         midi_manager: Option<Arc<MidiManager>>,
         link_sync: Option<LinkSynchronizer>,
         project_storage: ProjectStorage,
+        session_store: Option<SessionStore>,
+        runtime: RuntimeTracker,
         join_handles: Vec<JoinHandle<()>>,
+        /// Writes the session one last time on shutdown
+        session_recorder: Option<JoinHandle<()>>,
     }
 
     /// Initialize the application
     impl App {
         /// Create a new application instance  
         pub fn new(state: SharedState, event_bus: EventBus) -> Result<Self, Box<dyn Error>> {
-            let project_storage = ProjectStorage::new(state.clone(), event_bus.clone());
+            let session_store = SessionStore::default_location();
+
+            // User templates live next to the settings file
+            let mut project_storage = ProjectStorage::new(state.clone(), event_bus.clone());
+            if let Some(store) = &session_store {
+                project_storage.set_template_store(TemplateStore::new(store.dir().join("templates")));
+            }
 
             Ok(Self {
                 state,
@@ -53,15 +93,41 @@ pub mod app {
                 midi_manager: None, // Initialize as None
                 link_sync: None,
                 project_storage,
+                session_store,
+                runtime: RuntimeTracker::new(),
                 join_handles: Vec::new(),
+                session_recorder: None,
             })
         }
 
         /// Initialize the application
         pub fn init(&mut self) -> Result<(), Box<dyn Error>> {
+            // Record every event from the start when a journal file is requested
+            if let Some(path) = std::env::var_os(JOURNAL_ENV) {
+                match JournalRecorder::start(&self.event_bus, Path::new(&path)) {
+                    Ok(handle) => self.join_handles.push(handle),
+                    Err(e) => warn!("Failed to start event journal: {}", e),
+                }
+            }
+
+            // Restore the last session before anything reads the project
+            let session = self.restore_session();
+
             // Initialize MIDI manager with the shared state
             let midi_manager = Arc::new(MidiManager::new(self.event_bus.clone(), Some(self.state.clone())));
 
+            // Reconnect to the same hardware ports as last time
+            if let Some(session) = &session {
+                midi_manager.set_port_selection(session.midi_ports.clone());
+            }
+
+            // The manual-control input does not depend on the controller
+            if let Some(port) = session.as_ref().and_then(|session| session.midi_ports.control_input.clone()) {
+                if let Err(e) = midi_manager.connect_control_input(Some(&port)) {
+                    warn!("Failed to reconnect manual-control input {}: {}", port, e);
+                }
+            }
+
             // Initialize controller
             let controller_name = {
                 let state_guard = self.state.read().unwrap();
@@ -84,32 +150,274 @@ pub mod app {
                 info!("Initialized controller: {}", controller_name);
             }
 
+            // Bring the DAW back to the restored snap
+            if session.is_some() {
+                let cc_values = self.current_snap_cc_values();
+                if !cc_values.is_empty() {
+                    if let Err(e) = midi_manager.send_snap_values(&cc_values) {
+                        warn!("Failed to resend restored snap values: {}", e);
+                    }
+                }
+            }
+
             // Store the MIDI manager
-            self.midi_manager = Some(midi_manager);
+            self.midi_manager = Some(midi_manager.clone());
 
             // Initialize Link synchronizer
             let link_sync = LinkSynchronizer::new(self.event_bus.clone());
             let link_handle = link_sync.start();
 
+            // Restore tempo and quantum once the synchronizer is running
+            if let Some(session) = &session {
+                let link = link_sync.clone();
+                let (tempo, quantum) = (session.link_tempo, session.link_quantum);
+                tokio::spawn(async move {
+                    if let Some(tempo) = tempo {
+                        link.set_tempo(tempo).await;
+                    }
+                    if let Some(quantum) = quantum {
+                        link.set_quantum(quantum).await;
+                    }
+                });
+            }
+
             // Store the synchronizer
             self.link_sync = Some(link_sync);
             self.join_handles.push(link_handle);
 
             // Initialize morph engine with the shared state
-            let mut morph_engine = MorphEngine::new(self.state.clone(), self.event_bus.clone());
+            let morph_engine = MorphEngine::new(
+                self.state.clone(),
+                self.event_bus.clone(),
+                self.link_sync.clone(),
+            );
 
             // Start the morph engine
             let morph_handle = morph_engine.start();
             self.join_handles.push(morph_handle);
 
+            // Hold quantized snap recalls until their launch point
+            let launcher = Launcher::new(
+                self.control().internal(),
+                self.state.clone(),
+                self.event_bus.clone(),
+                self.link_sync.clone(),
+            );
+            self.join_handles.push(launcher.start());
+
             // Initialize AI service with the shared state
-            let ai_service = AIService::new(self.state.clone(), self.event_bus.clone());
+            let ai_service = AIService::new(self.state.clone(), self.event_bus.clone(), self.control());
             let ai_handle = ai_service.start();
             self.join_handles.push(ai_handle);
 
+            // Track the runtime state and its versioned deltas for every client
+            let runtime_handles = self.runtime.start(
+                &self.event_bus,
+                self.state.clone(),
+                self.midi_manager.clone(),
+                self.link_sync.clone(),
+            );
+            self.join_handles.extend(runtime_handles);
+
+            // Report bus health periodically so the UI can show whether it keeps up
+            let metrics_handle = metrics::start_reporter(self.event_bus.clone(), DEFAULT_REPORT_INTERVAL);
+            self.join_handles.push(metrics_handle);
+
+            // Let lighting and video desks trigger snaps over OSC
+            let osc_settings = self.osc_settings();
+            if osc_settings.enabled {
+                match OscServer::bind(&osc_settings, self.control()) {
+                    Ok(server) => self.join_handles.extend(server.start(&self.event_bus)),
+                    Err(e) => warn!("Failed to start OSC server on port {}: {}", osc_settings.port, e),
+                }
+            }
+
+            // Serve the control API for tablets and custom tools
+            let api_settings = self.api_settings();
+            if api_settings.enabled {
+                match api::start(&api_settings, self.control(), self.event_bus.clone(), self.runtime()) {
+                    Ok(handle) => self.join_handles.push(handle),
+                    Err(e) => warn!("Failed to start control API on port {}: {}", api_settings.port, e),
+                }
+            }
+
+            // Let AI agents read and perform the project over MCP
+            let mcp_settings = self.mcp_settings();
+            if mcp_settings.enabled {
+                match mcp::start(&mcp_settings, self.control()) {
+                    Ok(handle) => self.join_handles.push(handle),
+                    Err(e) => warn!("Failed to start MCP server on port {}: {}", mcp_settings.port, e),
+                }
+            }
+
+            // Run the project's scripts and reload them when they change
+            let script_handles = ScriptHost::start(self.state.clone(), self.event_bus.clone(), self.control());
+            self.join_handles.extend(script_handles);
+
+            // Keep the stored session up to date from here on
+            if let Some(store) = &self.session_store {
+                self.session_recorder = Some(store.start_recorder(
+                    self.state.clone(),
+                    self.event_bus.clone(),
+                    self.link_sync.clone(),
+                    Some(midi_manager),
+                ));
+            }
+
             Ok(())
         }
 
+        /// Reopen the last project and reselect its bank and snap.
+        /// Returns the restored session so the rest of init can apply it.
+        fn restore_session(&self) -> Option<Session> {
+            let settings = self.session_store.as_ref()?.load();
+            if !settings.restore_session {
+                return None;
+            }
+            let mut session = settings.session?;
+
+            // The ports and Link settings still apply when the project cannot be reopened,
+            // e.g. from a drive that is not mounted; the recorder keeps its path until another opens
+            if let Some(path) = session.project_path.clone() {
+                match self.project_storage.load_project(&path) {
+                    Ok(()) => info!("Reopened last project: {:?}", path),
+                    Err(e) => {
+                        warn!("Failed to reopen last project {:?}: {}", path, e);
+                        session.project_path = None;
+                    }
+                }
+            }
+
+            let mut state_guard = self.state.write().unwrap();
+            if session.project_path.is_some() && session.current_bank < state_guard.project.banks.len() {
+                state_guard.current_bank = session.current_bank;
+                if session.current_snap < state_guard.project.banks[session.current_bank].snaps.len() {
+                    state_guard.current_snap = session.current_snap;
+                }
+            }
+            state_guard.morph_duration = if MORPH_DURATION_BARS.contains(&session.morph_duration) {
+                session.morph_duration
+            } else {
+                warn!("Ignoring saved morph duration of {} bars", session.morph_duration);
+                4
+            };
+            drop(state_guard);
+
+            // Link keeps its defaults rather than take a tempo or bar length it cannot use
+            if session.link_tempo.is_some_and(|tempo| !TEMPO_RANGE.contains(&tempo)) {
+                warn!("Ignoring saved Link tempo of {:?} BPM", session.link_tempo);
+                session.link_tempo = None;
+            }
+            if session.link_quantum.is_some_and(|quantum| !QUANTUM_RANGE.contains(&quantum)) {
+                warn!("Ignoring saved Link quantum of {:?} beats", session.link_quantum);
+                session.link_quantum = None;
+            }
+
+            Some(session)
+        }
+
+        /// CC number and value for every parameter of the current snap
+        fn current_snap_cc_values(&self) -> Vec<(u8, u8)> {
+            let state_guard = self.state.read().unwrap();
+            let snap = match state_guard
+                .project
+                .banks
+                .get(state_guard.current_bank)
+                .and_then(|bank| bank.snaps.get(state_guard.current_snap))
+            {
+                Some(snap) => snap,
+                None => return Vec::new(),
+            };
+
+            state_guard
+                .project
+                .parameters
+                .iter()
+                .zip(&snap.values)
+                .map(|(param, value)| (param.cc, *value))
+                .collect()
+        }
+
+        /// Operations shared by the UI and remote control surfaces
+        pub fn control(&self) -> ControlService {
+            ControlService::new(
+                self.state.clone(),
+                self.event_bus.clone(),
+                self.midi_manager.clone(),
+                self.link_sync.clone(),
+            )
+        }
+
+        /// Runtime state snapshot and delta stream
+        pub fn runtime(&self) -> RuntimeTracker {
+            self.runtime.clone()
+        }
+
+        /// OSC server settings
+        pub fn osc_settings(&self) -> OscSettings {
+            self.session_store
+                .as_ref()
+                .map(|store| store.load().osc)
+                .unwrap_or_default()
+        }
+
+        /// Change the OSC server settings; they take effect on the next start
+        pub fn set_osc_settings(&self, osc: OscSettings) -> Result<(), Box<dyn Error>> {
+            let store = self
+                .session_store
+                .as_ref()
+                .ok_or("No settings directory available")?;
+            let mut settings = store.load();
+            settings.osc = osc;
+            store.save(&settings)
+        }
+
+        /// Control API settings
+        pub fn api_settings(&self) -> ApiSettings {
+            self.session_store
+                .as_ref()
+                .map(|store| store.load().api)
+                .unwrap_or_default()
+        }
+
+        /// Change the control API settings; they take effect on the next start
+        pub fn set_api_settings(&self, api: ApiSettings) -> Result<(), Box<dyn Error>> {
+            let store = self
+                .session_store
+                .as_ref()
+                .ok_or("No settings directory available")?;
+            let mut settings = store.load();
+            settings.api = api;
+            store.save(&settings)
+        }
+
+        /// MCP server settings
+        pub fn mcp_settings(&self) -> McpSettings {
+            self.session_store
+                .as_ref()
+                .map(|store| store.load().mcp)
+                .unwrap_or_default()
+        }
+
+        /// Change the MCP server settings; they take effect on the next start
+        pub fn set_mcp_settings(&self, mcp: McpSettings) -> Result<(), Box<dyn Error>> {
+            let store = self
+                .session_store
+                .as_ref()
+                .ok_or("No settings directory available")?;
+            let mut settings = store.load();
+            settings.mcp = mcp;
+            store.save(&settings)
+        }
+
+        /// Recently opened or saved projects, newest first
+        pub fn recent_projects(&self) -> Vec<PathBuf> {
+            self.session_store
+                .as_ref()
+                .map(|store| store.load().recent_projects)
+                .unwrap_or_default()
+        }
+
         pub fn link_sync(&self) -> Option<LinkSynchronizer> {
             self.link_sync.clone()
         }
@@ -128,14 +436,54 @@ pub mod app {
             self.project_storage.load_project(path)
         }
 
-        /// Create a new project
-        pub fn new_project(&self) -> Result<(), Box<dyn Error>> {
-            self.project_storage.new_project()
+        /// Export a bank to a file
+        pub fn export_bank(&self, bank_id: usize, path: &Path) -> Result<(), Box<dyn Error>> {
+            self.project_storage.export_bank(bank_id, path)
+        }
+
+        /// Import a bank from a file
+        pub fn import_bank(&self, path: &Path) -> Result<ImportReport, Box<dyn Error>> {
+            self.project_storage.import_bank(path)
+        }
+
+        /// Merge another project into the current one, or preview the merge
+        pub fn merge_project(&self, path: &Path, dry_run: bool) -> Result<MergeReport, Box<dyn Error>> {
+            self.project_storage.merge_project(path, dry_run)
+        }
+
+        /// Create a new project from a template
+        pub fn new_project(&self, template: Option<&str>) -> Result<(), Box<dyn Error>> {
+            self.project_storage.new_project(template)
         }
 
-        /// Shutdown the application
-        pub fn shutdown(&self) {
+        /// List the templates available for new projects
+        pub fn list_templates(&self) -> Vec<TemplateInfo> {
+            self.project_storage.list_templates()
+        }
+
+        /// Save the current project as a template
+        pub fn save_template(&self, name: &str, description: &str, reset_values: bool) -> Result<(), Box<dyn Error>> {
+            self.project_storage.save_template(name, description, reset_values)
+        }
+
+        /// Shutdown the application, waiting briefly for the session to be written
+        pub fn shutdown(&mut self) {
             let _ = self.event_bus.publish(crate::events::Event::Shutdown);
+
+            let Some(recorder) = self.session_recorder.take() else {
+                return;
+            };
+
+            // Callers such as the UI's exit hook are synchronous, so wait from a
+            // thread of our own rather than block a runtime thread
+            let runtime = tokio::runtime::Handle::current();
+            let waited = std::thread::spawn(move || {
+                runtime.block_on(async { tokio::time::timeout(SHUTDOWN_TIMEOUT, recorder).await.is_ok() })
+            })
+            .join();
+            if !matches!(waited, Ok(true)) {
+                warn!("Session was not written before shutdown");
+            }
         }
     }
 }
\ No newline at end of file
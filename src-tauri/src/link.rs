@@ -1,13 +1,41 @@
 // src-tauri/src/link.rs
-use crate::events::{Event, EventBus};
+use crate::events::{Event, EventBus, LaunchQuantize};
 use rusty_link::{AblLink, SessionState};
+use serde::Serialize;
 use std::convert::TryInto;
+use std::fmt;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 use tokio::time;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
+
+/// How long a query waits for the Link instance before giving up
+const QUERY_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Snapshot of the Link session, returned by `LinkSynchronizer::status`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LinkStatus {
+    pub enabled: bool,
+    pub connected: bool,
+    pub peers: usize,
+    pub playing: bool,
+    pub tempo: f64,
+    pub quantum: f64,
+}
+
+/// Error returned when a Link query cannot be answered in time
+#[derive(Debug)]
+pub struct LinkQueryTimeout;
+
+impl fmt::Display for LinkQueryTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Timed out waiting for Link after {} ms", QUERY_TIMEOUT.as_millis())
+    }
+}
+
+impl std::error::Error for LinkQueryTimeout {}
 
 /// Link integration for tempo synchronization with Ableton Link
 #[derive(Clone)]
@@ -53,82 +81,6 @@ impl LinkSynchronizer {
         let last_beat = self.last_beat.clone();
         let last_bar = self.last_bar.clone();
 
-        // Clone for event handler
-        let event_link = link.clone();
-        let event_quantum = quantum.clone();
-        let event_bus_clone = event_bus.clone();
-
-        // Listen for specific events related to Link
-        let mut event_receiver = event_bus.subscribe();
-        tokio::spawn(async move {
-            while let Ok(event) = event_receiver.recv().await {
-                match event {
-                    Event::RequestLinkStatus => {
-                        // Get current Link status
-                        let peers = {
-                            let link_guard = event_link.lock().await;
-                            let num_peers = link_guard.num_peers();
-                            (link_guard.is_enabled(), num_peers.try_into().unwrap_or(0))
-                        };
-
-                        // Send status back
-                        let _ = event_bus_clone.publish(Event::LinkStatusChanged {
-                            connected: peers.0 && peers.1 > 0,
-                            peers: peers.1
-                        });
-                    },
-                    Event::RequestLinkTempo => {
-                        // Get current tempo
-                        let tempo = {
-                            let link_guard = event_link.lock().await;
-                            let mut session_state = SessionState::new();
-                            link_guard.capture_app_session_state(&mut session_state);
-                            session_state.tempo()
-                        };
-
-                        // Send tempo back
-                        let _ = event_bus_clone.publish(Event::LinkTempoChanged {
-                            tempo
-                        });
-                    },
-                    Event::RequestNextBarTime => {
-                        // Calculate time to next bar boundary
-                        let wait_time = {
-                            let link_guard = event_link.lock().await;
-                            let mut session_state = SessionState::new();
-                            link_guard.capture_audio_session_state(&mut session_state);
-
-                            let quantum = *event_quantum.lock().await;
-                            let micros = link_guard.clock_micros();
-                            let beat_position = session_state.beat_at_time(micros, quantum);
-
-                            // Calculate beats until next bar
-                            let beat_in_bar = beat_position % quantum;
-                            let beats_to_next_bar = if beat_in_bar < 0.01 { 0.0 } else { quantum - beat_in_bar };
-
-                            // Calculate time at next bar in microseconds
-                            let next_bar_beat = beat_position + beats_to_next_bar;
-                            let next_bar_micros = session_state.time_at_beat(next_bar_beat, quantum);
-
-                            // Calculate wait time in milliseconds
-                            if next_bar_micros > micros {
-                                (next_bar_micros - micros) / 1000
-                            } else {
-                                0
-                            }
-                        };
-
-                        // Convert to u64 and send time back
-                        let wait_time_ms: u64 = wait_time.try_into().unwrap_or(0);
-                        let _ = event_bus_clone.publish(Event::NextBarTime {
-                            wait_time_ms
-                        });
-                    },
-                    _ => {}
-                }
-            }
-        });
-
         // Start the main Link loop
         tokio::spawn(async move {
             info!("Starting Link synchronizer");
@@ -163,7 +115,7 @@ impl LinkSynchronizer {
                 // Get Link state
                 let mut session_state = SessionState::new();
                 let (time_micros, beats_per_bar) = {
-                    let mut link_guard = link.lock().await;
+                    let link_guard = link.lock().await;
                     link_guard.capture_audio_session_state(&mut session_state);
 
                     // Get host time in microseconds and quantum
@@ -247,7 +199,7 @@ impl LinkSynchronizer {
 
     /// Set the tempo in BPM
     pub async fn set_tempo(&self, bpm: f64) {
-        let mut link_guard = self.link.lock().await;
+        let link_guard = self.link.lock().await;
 
         // Create a session state and apply the tempo change
         let mut session_state = SessionState::new();
@@ -268,9 +220,26 @@ impl LinkSynchronizer {
         info!("Quantum set to {:.1} beats per bar", beats);
     }
 
+    /// Get the quantum (beats per bar)
+    pub async fn quantum(&self) -> f64 {
+        *self.quantum.lock().await
+    }
+
+    /// Get the current session tempo in BPM
+    pub async fn tempo(&self) -> f64 {
+        let mut session_state = SessionState::new();
+
+        {
+            let link_guard = self.link.lock().await;
+            link_guard.capture_app_session_state(&mut session_state);
+        }
+
+        session_state.tempo()
+    }
+
     /// Enable or disable Link
     pub async fn enable(&self, enabled: bool) {
-        let mut link_guard = self.link.lock().await;
+        let link_guard = self.link.lock().await;
         link_guard.enable(enabled);
         let num_peers = link_guard.num_peers();
         let peers_usize: usize = num_peers.try_into().unwrap_or(0);
@@ -279,7 +248,7 @@ impl LinkSynchronizer {
 
     /// Force a transport start on the Link network
     pub async fn start_transport(&self) {
-        let mut link_guard = self.link.lock().await;
+        let link_guard = self.link.lock().await;
         let mut session_state = SessionState::new();
         link_guard.capture_app_session_state(&mut session_state);
 
@@ -301,7 +270,7 @@ impl LinkSynchronizer {
 
     /// Force a transport stop on the Link network
     pub async fn stop_transport(&self) {
-        let mut link_guard = self.link.lock().await;
+        let link_guard = self.link.lock().await;
         let mut session_state = SessionState::new();
         link_guard.capture_app_session_state(&mut session_state);
 
@@ -326,7 +295,7 @@ impl LinkSynchronizer {
         let mut session_state = SessionState::new();
 
         {
-            let mut link_guard = self.link.lock().await;
+            let link_guard = self.link.lock().await;
             link_guard.capture_app_session_state(&mut session_state);
         }
 
@@ -344,13 +313,94 @@ impl LinkSynchronizer {
         converted_peers
     }
 
+    /// Run a query against the Link instance and quantum, bounded by `QUERY_TIMEOUT`
+    async fn query<T>(&self, f: impl FnOnce(&AblLink, f64) -> T) -> Result<T, LinkQueryTimeout> {
+        time::timeout(QUERY_TIMEOUT, async {
+            let link_guard = self.link.lock().await;
+            let quantum = *self.quantum.lock().await;
+            f(&link_guard, quantum)
+        })
+        .await
+        .map_err(|_| LinkQueryTimeout)
+    }
+
+    /// Get the full session status in one consistent read
+    pub async fn status(&self) -> Result<LinkStatus, LinkQueryTimeout> {
+        self.query(|link, quantum| {
+            let mut session_state = SessionState::new();
+            link.capture_app_session_state(&mut session_state);
+            let peers: usize = link.num_peers().try_into().unwrap_or(0);
+            let enabled = link.is_enabled();
+
+            LinkStatus {
+                enabled,
+                connected: enabled && peers > 0,
+                peers,
+                playing: session_state.is_playing(),
+                tempo: session_state.tempo(),
+                quantum,
+            }
+        })
+        .await
+    }
+
+    /// Get the current position on the session's beat timeline
+    pub async fn beat_position(&self) -> Result<f64, LinkQueryTimeout> {
+        self.query(|link, quantum| {
+            let mut session_state = SessionState::new();
+            link.capture_app_session_state(&mut session_state);
+            session_state.beat_at_time(link.clock_micros(), quantum)
+        })
+        .await
+    }
+
+    /// Get how far into a cycle of `beats` beats the session is. Link keeps this the
+    /// same on every peer, even for cycles longer than the quantum such as phrases.
+    pub async fn phase(&self, beats: f64) -> Result<f64, LinkQueryTimeout> {
+        self.query(|link, _| {
+            let mut session_state = SessionState::new();
+            link.capture_app_session_state(&mut session_state);
+            session_state.phase_at_time(link.clock_micros(), beats)
+        })
+        .await
+    }
+
+    /// Get the time until the next launch point on the beat grid, with bars
+    /// of the configured quantum. Zero when already on one, or not quantized.
+    pub async fn time_to_next_launch(&self, quantize: LaunchQuantize) -> Result<Duration, LinkQueryTimeout> {
+        self.query(|link, quantum| {
+            let Some(grid) = quantize.beats(quantum) else {
+                return Duration::ZERO;
+            };
+            let mut session_state = SessionState::new();
+            link.capture_app_session_state(&mut session_state);
+
+            let micros = link.clock_micros();
+            let beat_position = session_state.beat_at_time(micros, quantum);
+
+            // Link's phase rather than the local beat count, so peers agree where a phrase starts
+            let beat_in_grid = session_state.phase_at_time(micros, grid);
+            let beats_to_next = if beat_in_grid < 0.01 { 0.0 } else { grid - beat_in_grid };
+
+            // Calculate time at the next grid point in microseconds
+            let next_micros = session_state.time_at_beat(beat_position + beats_to_next, quantum);
+
+            if next_micros > micros {
+                Duration::from_micros((next_micros - micros) as u64)
+            } else {
+                Duration::ZERO
+            }
+        })
+        .await
+    }
+
     /// Stop the Link synchronizer
     pub async fn stop(&self) {
         let mut running = self.running.lock().await;
         *running = false;
 
         // Disable Link
-        let mut link_guard = self.link.lock().await;
+        let link_guard = self.link.lock().await;
         link_guard.enable(false);
 
         info!("Link synchronizer stopping");
@@ -1,13 +1,20 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use snapblaster::app::App;
-use snapblaster::events::{Event, EventBus, MorphCurve};
+use snapblaster::control::{parse_curve, ControlService};
+use snapblaster::curves::{CurveShape, ResolvedCurve, UserCurve};
+use snapblaster::events::{Event, EventBus, LaunchQuantize, LoopMode, MorphDuration, MorphLoop};
 use snapblaster::midi::manager::MidiManager;
 use snapblaster::model::new_shared_state;
-use snapblaster::model::{Parameter, SharedState, Snap};
+use snapblaster::model::{
+    InterruptPolicy, LaunchQuantization, ManualControl, MorphChain, MorphTiming, ParameterMotion, SharedState,
+};
+use snapblaster::runtime::{RuntimeMessage, RuntimeTracker};
+use snapblaster::supervisor::{supervise, supervise_realtime, EventHandler, Flow};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tauri::{Manager, State, Window};
+use tokio::sync::broadcast::error::RecvError;
 use tracing::{debug, error, info};
 
 // Import the correct LinkSynchronizer directly
@@ -20,6 +27,8 @@ struct AppState {
     shared_state: SharedState,
     midi_manager: Option<Arc<MidiManager>>,
     link_sync: Option<LinkSynchronizer>,
+    control: ControlService,
+    runtime: RuntimeTracker,
 }
 
 // Tauri commands that bridge between the UI and Rust backend
@@ -57,12 +66,20 @@ async fn list_midi_outputs() -> Result<String, String> {
     serde_json::to_string(&ports).map_err(|e| e.to_string())
 }
 
+/// Choose the MIDI input whose control changes drive manual morphs; None closes it
+#[tauri::command]
+async fn set_control_input(port: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let Some(midi_manager) = &state.midi_manager else {
+        return Err("MIDI manager not initialized".to_string());
+    };
+
+    midi_manager.connect_control_input(port.as_deref()).map_err(|e| e.to_string())
+}
+
 /// Set the current MIDI controller
 #[tauri::command]
 async fn set_controller(name: String, state: State<'_, AppState>) -> Result<(), String> {
-    let mut state_guard = state.shared_state.write().unwrap();
-    state_guard.project.controller = name;
-    Ok(())
+    state.control.set_controller(name)
 }
 
 /// Get the current project state
@@ -85,12 +102,9 @@ async fn get_project(state: State<'_, AppState>) -> Result<String, String> {
 /// Save the current project
 #[tauri::command]
 async fn save_project(path: String, state: State<'_, AppState>) -> Result<(), String> {
-    // Get the shared state directly to ensure we're saving the current state
-    let state_guard = state.shared_state.read().unwrap();
-    debug!(
-        "Before save - Project has {} parameters",
-        state_guard.project.parameters.len()
-    );
+    // Count before saving: the state lock must not be held while the save updates the project path
+    let parameter_count = state.shared_state.read().unwrap().project.parameters.len();
+    debug!("Before save - Project has {} parameters", parameter_count);
 
     let app = state.app.lock().unwrap();
     let result = app
@@ -99,10 +113,7 @@ async fn save_project(path: String, state: State<'_, AppState>) -> Result<(), St
 
     // Double check the parameters are being saved
     if result.is_ok() {
-        debug!(
-            "Project saved. Parameters in state: {}",
-            state_guard.project.parameters.len()
-        );
+        debug!("Project saved. Parameters in state: {}", parameter_count);
     }
 
     result
@@ -116,151 +127,158 @@ async fn load_project(path: String, state: State<'_, AppState>) -> Result<(), St
         .map_err(|e| e.to_string())
 }
 
-/// Create a new project
+/// Create a new project, optionally from a named template
 #[tauri::command]
-async fn new_project(state: State<'_, AppState>) -> Result<(), String> {
+async fn new_project(template: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
     let app = state.app.lock().unwrap();
-    app.new_project().map_err(|e| e.to_string())
+    app.new_project(template.as_deref()).map_err(|e| e.to_string())
 }
 
-/// Select a snap
+/// List built-in and user project templates
 #[tauri::command]
-async fn select_snap(
-    bank_id: usize,
-    snap_id: usize,
+async fn list_templates(state: State<'_, AppState>) -> Result<String, String> {
+    let app = state.app.lock().unwrap();
+    serde_json::to_string(&app.list_templates()).map_err(|e| e.to_string())
+}
+
+/// Save the current project as a user template
+#[tauri::command]
+async fn save_template(
+    name: String,
+    description: String,
+    reset_values: bool,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    // Collection of parameter values to send via MIDI
-    let params_to_send: Vec<(u8, u8)>;
-
-    // First validate and update state
-    {
-        let mut state_guard = state.shared_state.write().unwrap();
-
-        // Validate indices
-        if bank_id >= state_guard.project.banks.len() {
-            return Err("Bank ID out of range".to_string());
-        }
-
-        let bank = &state_guard.project.banks[bank_id];
-        if snap_id >= bank.snaps.len() {
-            return Err("Snap ID out of range".to_string());
-        }
-
-        // Update state
-        state_guard.current_bank = bank_id;
-        state_guard.current_snap = snap_id;
+    let app = state.app.lock().unwrap();
+    app.save_template(&name, &description, reset_values)
+        .map_err(|e| e.to_string())
+}
 
-        // Ensure the snap has values for all parameters
-        let param_count = state_guard.project.parameters.len();
+/// Get recently opened projects, newest first
+#[tauri::command]
+async fn get_recent_projects(state: State<'_, AppState>) -> Result<String, String> {
+    let app = state.app.lock().unwrap();
+    serde_json::to_string(&app.recent_projects()).map_err(|e| e.to_string())
+}
 
-        {
-            let bank = &mut state_guard.project.banks[bank_id];
-            let snap = &mut bank.snaps[snap_id];
+/// Get the OSC server settings
+#[tauri::command]
+async fn get_osc_settings(state: State<'_, AppState>) -> Result<String, String> {
+    let app = state.app.lock().unwrap();
+    serde_json::to_string(&app.osc_settings()).map_err(|e| e.to_string())
+}
 
-            // Resize the values array if needed
-            if snap.values.len() < param_count {
-                snap.values.resize(param_count, 64);
-            }
-        }
-    }
+/// Change the OSC server settings; they apply the next time the app starts
+#[tauri::command]
+async fn set_osc_settings(enabled: bool, port: u16, state: State<'_, AppState>) -> Result<(), String> {
+    let app = state.app.lock().unwrap();
+    let mut settings = app.osc_settings();
+    settings.enabled = enabled;
+    settings.port = port;
+    app.set_osc_settings(settings).map_err(|e| e.to_string())
+}
 
-    // Now, in a separate step, collect the parameter values with just a read lock
-    {
-        let state_guard = state.shared_state.read().unwrap();
-
-        params_to_send = state_guard
-            .project
-            .parameters
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, param)| {
-                if idx
-                    < state_guard.project.banks[bank_id].snaps[snap_id]
-                    .values
-                    .len()
-                {
-                    let value = state_guard.project.banks[bank_id].snaps[snap_id].values[idx];
-                    Some((param.cc, value))
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<(u8, u8)>>(); // Explicitly collect into Vec<(u8, u8)>
-    }
+/// Get the control API settings
+#[tauri::command]
+async fn get_api_settings(state: State<'_, AppState>) -> Result<String, String> {
+    let app = state.app.lock().unwrap();
+    serde_json::to_string(&app.api_settings()).map_err(|e| e.to_string())
+}
 
-    // Get the MIDI manager
-    if let Some(midi_manager) = &state.midi_manager {
-        // Send all parameter values via MIDI
-        if let Err(e) = midi_manager.send_snap_values(&params_to_send) {
-            // Log error but continue - MIDI failure shouldn't stop the snap selection
-            error!("Failed to send snap values via MIDI: {}", e);
-        }
-    }
+/// Change the control API settings; they apply the next time the app starts
+#[tauri::command]
+async fn set_api_settings(
+    enabled: bool,
+    port: u16,
+    token: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let app = state.app.lock().unwrap();
+    let mut settings = app.api_settings();
+    settings.enabled = enabled;
+    settings.port = port;
+    settings.token = token.filter(|token| !token.is_empty());
+    app.set_api_settings(settings).map_err(|e| e.to_string())
+}
 
-    // Send the event
-    state
-        .event_bus
-        .publish(Event::SnapSelected {
-            bank: bank_id,
-            snap_id,
-        })
-        .map(|_| ())
-        .map_err(|e| e.to_string())
+/// Get the MCP server settings
+#[tauri::command]
+async fn get_mcp_settings(state: State<'_, AppState>) -> Result<String, String> {
+    let app = state.app.lock().unwrap();
+    serde_json::to_string(&app.mcp_settings()).map_err(|e| e.to_string())
 }
 
-/// Edit a parameter value
+/// Change the MCP server settings; they apply the next time the app starts
 #[tauri::command]
-async fn edit_parameter(
-    param_id: usize,
-    value: u8,
+async fn set_mcp_settings(
+    enabled: bool,
+    port: u16,
+    token: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    // The CC number for this parameter
-    let cc: u8;
-
-    // First update the state directly
-    {
-        let mut state_guard = state.shared_state.write().unwrap();
+    let app = state.app.lock().unwrap();
+    let mut settings = app.mcp_settings();
+    settings.enabled = enabled;
+    settings.port = port;
+    settings.token = token.filter(|token| !token.is_empty());
+    app.set_mcp_settings(settings).map_err(|e| e.to_string())
+}
 
-        // Store these values locally first
-        let current_bank = state_guard.current_bank;
-        let current_snap = state_guard.current_snap;
+/// Export a bank, with the parameters it uses, to a file
+#[tauri::command]
+async fn export_bank(bank_id: usize, path: String, state: State<'_, AppState>) -> Result<(), String> {
+    let app = state.app.lock().unwrap();
+    app.export_bank(bank_id, &PathBuf::from(path))
+        .map_err(|e| e.to_string())
+}
 
-        // Make sure the parameter exists
-        if param_id >= state_guard.project.parameters.len() {
-            return Err("Parameter ID out of range".to_string());
-        }
+/// Import a bank file into the current project, returning the import report as JSON
+#[tauri::command]
+async fn import_bank(path: String, state: State<'_, AppState>) -> Result<String, String> {
+    let app = state.app.lock().unwrap();
+    let report = app
+        .import_bank(&PathBuf::from(path))
+        .map_err(|e| e.to_string())?;
 
-        // Get the CC number for this parameter
-        cc = state_guard.project.parameters[param_id].cc;
+    serde_json::to_string(&report).map_err(|e| e.to_string())
+}
 
-        // Now access the snap with the stored indices
-        let snap = &mut state_guard.project.banks[current_bank].snaps[current_snap];
+/// Merge another project into the current one; with dry_run only the report is returned
+#[tauri::command]
+async fn merge_project(path: String, dry_run: bool, state: State<'_, AppState>) -> Result<String, String> {
+    let app = state.app.lock().unwrap();
+    let report = app
+        .merge_project(&PathBuf::from(path), dry_run)
+        .map_err(|e| e.to_string())?;
 
-        // Ensure the values array is big enough
-        while snap.values.len() <= param_id {
-            snap.values.push(64); // Default value
-        }
+    serde_json::to_string(&report).map_err(|e| e.to_string())
+}
 
-        // Update the value
-        snap.values[param_id] = value;
-    }
+/// Select a snap. `quantize` overrides the bank's recall launch quantization.
+#[tauri::command]
+async fn select_snap(
+    bank_id: usize,
+    snap_id: usize,
+    quantize: Option<LaunchQuantize>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.control.launch_snap(bank_id, snap_id, quantize)
+}
 
-    // Send the MIDI CC value
-    if let Some(midi_manager) = &state.midi_manager {
-        if let Err(e) = midi_manager.send_cc(0, cc, value) {
-            // Log error but continue - MIDI failure shouldn't stop the parameter edit
-            error!("Failed to send parameter CC via MIDI: {}", e);
-        }
-    }
+/// Select a bank
+#[tauri::command]
+async fn select_bank(bank_id: usize, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.select_bank(bank_id)
+}
 
-    // Then publish the event
-    state
-        .event_bus
-        .publish(Event::ParameterEdited { param_id, value })
-        .map(|_| ())
-        .map_err(|e| e.to_string())
+/// Edit a parameter value
+#[tauri::command]
+async fn edit_parameter(
+    param_id: usize,
+    value: u8,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.control.set_parameter(param_id, value)
 }
 
 /// Generate AI values for a snap
@@ -299,39 +317,186 @@ async fn send_wiggle(cc: u8, values: Vec<u8>, state: State<'_, AppState>) -> Res
     Err("MIDI manager not initialized".to_string())
 }
 
-/// Start a morph between two snaps
+/// Start a morph between two snaps. `duration`, such as "3/4bar", "2beats" or
+/// "500ms", takes precedence over `duration_bars`. `quantize` is "none", "beat",
+/// "bar" or a phrase such as "4bars"; without it the bank's setting is used.
 #[tauri::command]
 async fn start_morph(
     from_snap: usize,
     to_snap: usize,
-    duration_bars: u8,
+    duration_bars: f64,
+    duration: Option<String>,
     curve_type: String,
-    quantize: bool, // Add quantize parameter
+    quantize: Option<LaunchQuantize>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    // Map the curve type string to the enum
-    let curve = match curve_type.as_str() {
-        "linear" => MorphCurve::Linear,
-        #[cfg(feature = "pro")]
-        "exponential" => MorphCurve::Exponential,
-        #[cfg(feature = "pro")]
-        "logarithmic" => MorphCurve::Logarithmic,
-        #[cfg(feature = "pro")]
-        "scurve" => MorphCurve::SCurve,
-        _ => MorphCurve::Linear,
+    let duration = match duration {
+        Some(duration) => duration.parse()?,
+        None => MorphDuration::Bars(duration_bars),
     };
-
     state
-        .event_bus
-        .publish(Event::MorphInitiated {
-            from_snap,
-            to_snap,
-            duration_bars,
-            curve_type: curve,
-            quantize, // Pass through the quantize flag
-        })
-        .map(|_| ())
-        .map_err(|e| e.to_string())
+        .control
+        .start_morph(from_snap, to_snap, duration, parse_curve(&curve_type), quantize)
+}
+
+/// Loop between the current snap and another one. `period` is the length of one
+/// pass, such as "2bars" or "750ms"; without `repeats` the loop runs until stopped.
+#[tauri::command]
+async fn start_loop(
+    to_snap: usize,
+    period: String,
+    mode: LoopMode,
+    repeats: Option<u32>,
+    curve_type: String,
+    quantize: Option<LaunchQuantize>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let looping = MorphLoop {
+        mode,
+        period: period.parse()?,
+        repeats,
+    };
+    state.control.loop_to(to_snap, looping, parse_curve(&curve_type), quantize)
+}
+
+/// Stop the running morph, loop or chain where it is
+#[tauri::command]
+async fn stop_morph(state: State<'_, AppState>) -> Result<(), String> {
+    state.control.stop_morph()
+}
+
+/// Start a manual morph from the current snap to another; its position is then
+/// set by `set_morph_position`, the bound fader or pad pressure
+#[tauri::command]
+async fn start_manual_morph(to_snap: usize, curve_type: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.start_manual_morph(to_snap, parse_curve(&curve_type))
+}
+
+/// Scrub the running morph to a position from 0 to 1. A timed morph is taken
+/// over once the position reaches where it is.
+#[tauri::command]
+async fn set_morph_position(position: f64, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.set_morph_position(position)
+}
+
+/// Choose the CC (and channel) and pad pressure that drive manual morphs
+#[tauri::command]
+async fn set_manual_control(manual_control: ManualControl, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.set_manual_control(manual_control)
+}
+
+/// Set the crossfade used when recalling a snap of the current bank; 0 recalls instantly
+#[tauri::command]
+async fn set_recall_crossfade(crossfade_ms: u32, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.set_recall_crossfade(crossfade_ms)
+}
+
+/// List the curves morphs can use: built-in curve IDs and the project's user curves
+#[tauri::command]
+async fn get_curves(state: State<'_, AppState>) -> Result<String, String> {
+    let (builtin, user) = state.control.curves();
+    serde_json::to_string(&serde_json::json!({ "builtin": builtin, "user": user })).map_err(|e| e.to_string())
+}
+
+/// Add a user curve to the project, or replace the one with the same ID
+#[tauri::command]
+async fn save_curve(curve: UserCurve, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.save_curve(curve)
+}
+
+/// Remove a user curve from the project
+#[tauri::command]
+async fn delete_curve(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.delete_curve(&id)
+}
+
+/// Sample a built-in or user curve by ID for drawing
+#[tauri::command]
+async fn sample_curve(id: String, count: usize, state: State<'_, AppState>) -> Result<String, String> {
+    let points = state.control.sample_curve(&id, count)?;
+    serde_json::to_string(&points).map_err(|e| e.to_string())
+}
+
+/// Sample a curve shape that is still being edited
+#[tauri::command]
+async fn sample_curve_shape(shape: CurveShape, count: usize) -> Result<String, String> {
+    shape.validate()?;
+    let points = ResolvedCurve::User(shape).sample(count);
+    serde_json::to_string(&points).map_err(|e| e.to_string())
+}
+
+/// Set the window and curve a parameter follows during morphs; null resets it
+#[tauri::command]
+async fn set_parameter_morph_timing(
+    param_id: usize,
+    timing: Option<MorphTiming>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.control.set_parameter_timing(param_id, timing)
+}
+
+/// Set whether a parameter glides, jumps (discrete) or steps during morphs
+#[tauri::command]
+async fn set_parameter_motion(
+    param_id: usize,
+    motion: ParameterMotion,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.control.set_parameter_motion(param_id, motion)
+}
+
+/// Override a parameter's morph timing for morphs to one snap; null removes the override
+#[tauri::command]
+async fn set_snap_morph_timing(
+    bank_id: usize,
+    snap_id: usize,
+    param_id: usize,
+    timing: Option<MorphTiming>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.control.set_snap_timing(bank_id, snap_id, param_id, timing)
+}
+
+/// List the project's morph chains
+#[tauri::command]
+async fn get_chains(state: State<'_, AppState>) -> Result<String, String> {
+    serde_json::to_string(&state.control.chains()).map_err(|e| e.to_string())
+}
+
+/// Add a morph chain to the project, or replace the one with the same ID
+#[tauri::command]
+async fn save_chain(chain: MorphChain, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.save_chain(chain)
+}
+
+/// Remove a morph chain from the project
+#[tauri::command]
+async fn delete_chain(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.delete_chain(&id)
+}
+
+/// Run a morph chain from the current snap
+#[tauri::command]
+async fn start_chain(id: String, quantize: Option<LaunchQuantize>, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.start_chain(&id, quantize)
+}
+
+/// Set how a running morph reacts to a new morph or recall:
+/// "retarget", "finish_then_go", "jump" or "ignore"
+#[tauri::command]
+async fn set_interrupt_policy(policy: InterruptPolicy, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.set_interrupt_policy(policy)
+}
+
+/// Set when morphs and recalls launch on the beat grid, for the project or,
+/// with `bank_id`, for one bank. A bank without a setting follows the project.
+#[tauri::command]
+async fn set_launch_quantization(
+    bank_id: Option<usize>,
+    launch_quantization: Option<LaunchQuantization>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.control.set_launch_quantization(bank_id, launch_quantization)
 }
 
 /// Set the OpenAI API key
@@ -362,30 +527,7 @@ async fn add_parameter(
     cc: u8,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let mut state_guard = state.shared_state.write().unwrap();
-
-    // Add the parameter to the project
-    state_guard.project.parameters.push(Parameter {
-        name: name.clone(),
-        description: description.clone(),
-        cc,
-    });
-
-    // Add a default value to each snap
-    for bank in &mut state_guard.project.banks {
-        for snap in &mut bank.snaps {
-            snap.values.push(64); // Default to middle value
-        }
-    }
-
-    debug!(
-        "Parameter added: {} (CC: {}), Total parameters: {}",
-        name,
-        cc,
-        state_guard.project.parameters.len()
-    );
-
-    Ok(())
+    state.control.add_parameter(name, description, cc)
 }
 
 /// Update a parameter
@@ -397,23 +539,7 @@ async fn update_parameter(
     cc: u8,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let mut state_guard = state.shared_state.write().unwrap();
-
-    if param_id >= state_guard.project.parameters.len() {
-        return Err("Parameter ID out of range".to_string());
-    }
-
-    let param = &mut state_guard.project.parameters[param_id];
-    param.name = name.clone();
-    param.description = description.clone();
-    param.cc = cc;
-
-    debug!(
-        "Parameter updated: ID {}, name '{}', CC {}",
-        param_id, name, cc
-    );
-
-    Ok(())
+    state.control.update_parameter(param_id, name, description, cc)
 }
 
 /// Add a new snap
@@ -425,38 +551,7 @@ async fn add_snap(
     description: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let mut state_guard = state.shared_state.write().unwrap();
-
-    if bank_id >= state_guard.project.banks.len() {
-        return Err("Bank ID out of range".to_string());
-    }
-
-    // Store the parameter count before the mutable borrow
-    let param_count = state_guard.project.parameters.len();
-
-    // Get the bank
-    let bank = &mut state_guard.project.banks[bank_id];
-
-    // Ensure we have space for this snap position
-    if pad_index >= bank.snaps.len() {
-        bank.snaps.resize(
-            pad_index + 1,
-            Snap {
-                name: String::new(),
-                description: String::new(),
-                values: vec![],
-            },
-        );
-    }
-
-    // Set the snap at the specified pad position
-    bank.snaps[pad_index] = Snap {
-        name,
-        description,
-        values: vec![64; param_count], // Default all values to middle
-    };
-
-    Ok(())
+    state.control.add_snap(bank_id, pad_index, name, description, None)
 }
 
 /// Update a snap's description
@@ -467,21 +562,21 @@ async fn update_snap_description(
     description: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let mut state_guard = state.shared_state.write().unwrap();
-
-    if bank_id >= state_guard.project.banks.len() {
-        return Err("Bank ID out of range".to_string());
-    }
-
-    let bank = &mut state_guard.project.banks[bank_id];
-
-    if snap_id >= bank.snaps.len() {
-        return Err("Snap ID out of range".to_string());
-    }
+    state
+        .control
+        .update_snap(bank_id, snap_id, None, Some(description), None)
+}
 
-    let snap = &mut bank.snaps[snap_id];
-    snap.description = description;
+/// Get event bus metrics: per-type counts, per-subscriber lag and latency
+#[tauri::command]
+async fn get_event_metrics(state: State<'_, AppState>) -> Result<String, String> {
+    serde_json::to_string(&state.event_bus.metrics()).map_err(|e| e.to_string())
+}
 
+/// Reset event bus metrics
+#[tauri::command]
+async fn reset_event_metrics(state: State<'_, AppState>) -> Result<(), String> {
+    state.event_bus.reset_stats();
     Ok(())
 }
 
@@ -489,46 +584,30 @@ async fn update_snap_description(
 #[tauri::command]
 async fn get_link_status(state: State<'_, AppState>) -> Result<String, String> {
     if let Some(link_sync) = &state.link_sync {
-        let peers = link_sync.num_peers().await;
-        let playing = link_sync.is_playing().await;
-
-        // For the tempo, we need to subscribe to the tempo event
-        let mut receiver = state.event_bus.subscribe();
-        let _ = state.event_bus.publish(Event::RequestLinkTempo);
-
-        // Wait for the tempo response with a timeout
-        let timeout = tokio::time::Duration::from_millis(200);
-        let tempo = match tokio::time::timeout(timeout, receiver.recv()).await {
-            Ok(Ok(Event::LinkTempoChanged { tempo })) => tempo,
-            _ => 120.0, // Default tempo if we can't get it
-        };
-
-        let status = serde_json::json!({
-            "connected": peers > 0,
-            "peers": peers,
-            "playing": playing,
-            "tempo": tempo
-        });
+        let status = link_sync.status().await.map_err(|e| e.to_string())?;
 
-        Ok(status.to_string())
+        serde_json::to_string(&status).map_err(|e| e.to_string())
     } else {
         Err("Link synchronizer not initialized".to_string())
     }
 }
 
-/// Set Link tempo
+/// Get the runtime state: selection, morph, modifier, Link, ports and LEDs
 #[tauri::command]
-async fn set_link_tempo(tempo: f64, state: State<'_, AppState>) -> Result<(), String> {
-    if let Some(link_sync) = &state.link_sync {
-        link_sync.set_tempo(tempo).await;
+async fn get_runtime_state(state: State<'_, AppState>) -> Result<String, String> {
+    serde_json::to_string(&state.runtime.snapshot()).map_err(|e| e.to_string())
+}
 
-        // Publish event to notify all components
-        let _ = state.event_bus.publish(Event::LinkTempoChanged { tempo });
+/// Get the runtime state deltas after a version, or a snapshot if they are no longer kept
+#[tauri::command]
+async fn get_runtime_deltas(since: u64, state: State<'_, AppState>) -> Result<String, String> {
+    serde_json::to_string(&state.runtime.resume(Some(since))).map_err(|e| e.to_string())
+}
 
-        Ok(())
-    } else {
-        Err("Link synchronizer not initialized".to_string())
-    }
+/// Set Link tempo
+#[tauri::command]
+async fn set_link_tempo(tempo: f64, state: State<'_, AppState>) -> Result<(), String> {
+    state.control.set_tempo(tempo).await
 }
 
 /// Enable or disable Link
@@ -555,57 +634,191 @@ async fn set_link_enabled(enabled: bool, state: State<'_, AppState>) -> Result<(
 /// Start Link transport
 #[tauri::command]
 async fn start_link_transport(state: State<'_, AppState>) -> Result<(), String> {
-    if let Some(link_sync) = &state.link_sync {
-        link_sync.start_transport().await;
-
-        // Publish event to notify all components
-        let _ = state.event_bus.publish(Event::LinkTransportChanged { playing: true });
-
-        Ok(())
-    } else {
-        Err("Link synchronizer not initialized".to_string())
-    }
+    state.control.set_transport(true).await
 }
 
 /// Stop Link transport
 #[tauri::command]
 async fn stop_link_transport(state: State<'_, AppState>) -> Result<(), String> {
-    if let Some(link_sync) = &state.link_sync {
-        link_sync.stop_transport().await;
-
-        // Publish event to notify all components
-        let _ = state.event_bus.publish(Event::LinkTransportChanged { playing: false });
-
-        Ok(())
-    } else {
-        Err("Link synchronizer not initialized".to_string())
-    }
+    state.control.set_transport(false).await
 }
 
 /// Set quantum (beats per bar) for Link
 #[tauri::command]
 async fn set_link_quantum(beats: f64, state: State<'_, AppState>) -> Result<(), String> {
-    if let Some(link_sync) = &state.link_sync {
-        link_sync.set_quantum(beats).await;
-        Ok(())
-    } else {
-        Err("Link synchronizer not initialized".to_string())
+    state.control.set_quantum(beats).await
+}
+
+/// Forwards bus events to the frontend
+struct UiForwarder {
+    window: Window,
+}
+
+impl EventHandler for UiForwarder {
+    async fn handle(&mut self, event: Event) -> Flow {
+        // Link changes also go out on their own channel for the transport display
+        let link_status = match &event {
+            Event::LinkStatusChanged { connected, peers } => Some(serde_json::json!({
+                "type": "link_status",
+                "connected": connected,
+                "peers": peers
+            })),
+            Event::LinkTempoChanged { tempo } => Some(serde_json::json!({
+                "type": "link_tempo",
+                "tempo": tempo
+            })),
+            Event::LinkTransportChanged { playing } => Some(serde_json::json!({
+                "type": "link_transport",
+                "playing": playing
+            })),
+            _ => None,
+        };
+
+        if let Some(status) = link_status {
+            if let Err(e) = self.window.emit("link-event", status.to_string()) {
+                error!("Failed to emit link event: {}", e);
+            }
+        }
+
+        // Convert event to JSON and emit it to the frontend
+        if let Ok(json) = serde_json::to_string(&event) {
+            let _ = self.window.emit("snap-event", json);
+        }
+
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, missed: u64) {
+        // The frontend reloads the project and status when it may have missed updates
+        let _ = self.window.emit("snap-resync", missed);
     }
 }
 
+// Forward runtime state deltas to the frontend
+fn setup_runtime_forwarder(window: Window, runtime: RuntimeTracker) {
+    tokio::spawn(async move {
+        let mut deltas = runtime.subscribe();
+        loop {
+            let message = match deltas.recv().await {
+                Ok(delta) => RuntimeMessage::Delta(delta),
+                // The frontend replaces its copy instead of applying deltas with a gap
+                Err(RecvError::Lagged(_)) => RuntimeMessage::Snapshot(Box::new(runtime.snapshot())),
+                Err(RecvError::Closed) => break,
+            };
+
+            if let Ok(json) = serde_json::to_string(&message) {
+                let _ = window.emit("runtime-state", json);
+            }
+        }
+    });
+}
+
 // Set up event listeners and forward events to the frontend
 fn setup_event_listener(window: Window, event_bus: EventBus) {
-    let mut rx = event_bus.subscribe();
+    supervise(&event_bus, "ui-forwarder", move || UiForwarder {
+        window: window.clone(),
+    });
+}
 
-    tokio::spawn(async move {
-        while let Ok(event) = rx.recv().await {
-            // Convert event to JSON
-            if let Ok(json) = serde_json::to_string(&event) {
-                // Emit event to frontend
-                let _ = window.emit("snap-event", json);
+/// Keeps the controller LEDs in line with the project state
+struct LedUpdater {
+    midi_manager: Arc<MidiManager>,
+}
+
+impl LedUpdater {
+    fn update(&self) {
+        if let Err(e) = self.midi_manager.update_controller_leds() {
+            error!("Failed to update controller LEDs: {}", e);
+        }
+    }
+}
+
+impl EventHandler for LedUpdater {
+    async fn handle(&mut self, event: Event) -> Flow {
+        match event {
+            // Update controller LEDs when state changes
+            Event::ProjectLoaded
+            | Event::BankImported { .. }
+            | Event::ProjectMerged { .. }
+            | Event::SnapEdited { .. }
+            | Event::SnapSelected { .. }
+            | Event::BankSelected { .. } => self.update(),
+            Event::RequestMIDIUpdate => {
+                self.update();
+                debug!("Updated controller LEDs on request");
             }
+            _ => {}
         }
-    });
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, _missed: u64) {
+        self.update();
+    }
+}
+
+/// Sends CC value changes during morphing to the MIDI output
+struct CcForwarder {
+    midi_manager: Arc<MidiManager>,
+}
+
+impl EventHandler for CcForwarder {
+    async fn handle(&mut self, event: Event) -> Flow {
+        if let Event::CCValueChanged { param_id, value } = event {
+            // Get the CC number for this parameter
+            let cc_number = match self.midi_manager.get_state() {
+                Some(state) => {
+                    let guard = state.read().unwrap();
+                    match guard.project.parameters.get(param_id) {
+                        Some(param) => param.cc,
+                        None => return Flow::Continue, // Skip if parameter doesn't exist
+                    }
+                }
+                None => return Flow::Continue, // Skip if no state
+            };
+
+            // Send the CC value to the MIDI output
+            if let Err(e) = self.midi_manager.send_cc(0, cc_number, value) {
+                error!("Failed to send CC during morph: {}", e);
+            } else {
+                debug!("Sent morph CC: ch=0 cc={} val={}", cc_number, value);
+            }
+        }
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, _missed: u64) {
+        // Dropped value changes leave the DAW behind, so send every value again
+        let Some(state) = self.midi_manager.get_state() else {
+            return;
+        };
+
+        let params: Vec<(u8, u8)> = {
+            let guard = state.read().unwrap();
+            let values = match &guard.active_morph {
+                Some(morph) => morph.current_values.clone(),
+                None => guard
+                    .project
+                    .banks
+                    .get(guard.current_bank)
+                    .and_then(|bank| bank.snaps.get(guard.current_snap))
+                    .map(|snap| snap.values.clone())
+                    .unwrap_or_default(),
+            };
+
+            guard
+                .project
+                .parameters
+                .iter()
+                .zip(values)
+                .map(|(param, value)| (param.cc, value))
+                .collect()
+        };
+
+        if let Err(e) = self.midi_manager.send_snap_values(&params) {
+            error!("Failed to resend CC values after resync: {}", e);
+        }
+    }
 }
 
 #[tokio::main]
@@ -629,125 +842,28 @@ async fn main() {
     // Get the LinkSynchronizer directly from the app
     let link_sync = app.link_sync();
 
+    // Operations shared with remote control surfaces
+    let control = app.control();
+
+    // Runtime state for the frontend, kept up to date by the app
+    let runtime = app.runtime();
+    let setup_runtime = runtime.clone();
+
     // We don't need to start it here - it was already started in app.init()
 
-    // Set up event handler for controller input
+    // Pad events are handled by the MIDI manager itself; keep the LEDs and DAW in sync here
     if let Some(midi_manager) = &midi_manager {
-        let midi_manager_clone = midi_manager.clone();
-        let pad_event_bus = event_bus.clone();
-
-        // Pad events handler
-        tokio::spawn(async move {
-            let mut rx = pad_event_bus.subscribe();
-
-            while let Ok(event) = rx.recv().await {
-                match event {
-                    Event::PadPressed { pad, velocity } => {
-                        debug!(
-                            "Received PadPressed event in main handler: pad={}, velocity={}",
-                            pad, velocity
-                        );
-                        if let Err(e) = midi_manager_clone.handle_pad_pressed(pad, velocity).await {
-                            error!("Error handling pad press: {}", e);
-                        } else {
-                            debug!("Successfully handled pad press");
-                        }
-                    },
-                    Event::PadReleased { pad, velocity } => {
-                        debug!(
-                            "Received PadReleased event in main handler: pad={}, velocity={}",
-                            pad, velocity
-                        );
-                        if let Err(e) = midi_manager_clone.handle_pad_released(pad, velocity).await {
-                            error!("Error handling pad release: {}", e);
-                        } else {
-                            debug!("Successfully handled pad release");
-                        }
-                    },
-                    _ => {}
-                }
-            }
+        let led_manager = midi_manager.clone();
+        supervise(&event_bus, "led-updater", move || LedUpdater {
+            midi_manager: led_manager.clone(),
         });
 
-        // Add a new handler for snap-related events to update LEDs
-        let midi_manager_for_events = midi_manager.clone();
-        let state_events_bus = event_bus.clone();
-
-        tokio::spawn(async move {
-            let mut rx = state_events_bus.subscribe();
-
-            while let Ok(event) = rx.recv().await {
-                // Update controller LEDs when state changes
-                match event {
-                    Event::ProjectLoaded
-                    | Event::SnapSelected { .. }
-                    | Event::BankSelected { .. } => {
-                        // No need for Option pattern - it's an Arc directly
-                        if let Err(e) = midi_manager_for_events.update_controller_leds() {
-                            error!("Failed to update controller LEDs after state change: {}", e);
-                        }
-                    }
-                    _ => {}
-                }
-            }
+        let cc_manager = midi_manager.clone();
+        supervise_realtime(&event_bus, "cc-forwarder", move || CcForwarder {
+            midi_manager: cc_manager.clone(),
         });
     }
 
-    let midi_manager_for_updates = midi_manager.clone();
-    let midi_update_bus = event_bus.clone();
-
-    tokio::spawn(async move {
-        let mut rx = midi_update_bus.subscribe();
-
-        while let Ok(event) = rx.recv().await {
-            if let Event::RequestMIDIUpdate = event {
-                if let Some(ref midi_manager) = midi_manager_for_updates {
-                    if let Err(e) = midi_manager.update_controller_leds() {
-                        error!("Failed to update controller LEDs: {}", e);
-                    } else {
-                        debug!("Updated controller LEDs on request");
-                    }
-                }
-            }
-        }
-    });
-
-    // Add a handler for CC value changes during morphing
-    let midi_manager_for_cc = midi_manager.clone();
-    let cc_events_bus = event_bus.clone();
-
-    tokio::spawn(async move {
-        let mut rx = cc_events_bus.subscribe();
-
-        while let Ok(event) = rx.recv().await {
-            if let Event::CCValueChanged { param_id, value } = event {
-                if let Some(ref midi_manager) = midi_manager_for_cc {
-                    // Get the CC number for this parameter
-                    let cc_number = {
-                        let state = midi_manager.get_state();
-                        if let Some(state) = state {
-                            let guard = state.read().unwrap();
-                            if param_id < guard.project.parameters.len() {
-                                guard.project.parameters[param_id].cc
-                            } else {
-                                continue; // Skip if parameter doesn't exist
-                            }
-                        } else {
-                            continue; // Skip if no state
-                        }
-                    };
-
-                    // Send the CC value to the MIDI output
-                    if let Err(e) = midi_manager.send_cc(0, cc_number, value) {
-                        error!("Failed to send CC during morph: {}", e);
-                    } else {
-                        debug!("Sent morph CC: ch=0 cc={} val={}", cc_number, value);
-                    }
-                }
-            }
-        }
-    });
-
     // Clone event_bus for AppState before we move it into the setup closure
     let app_state_event_bus = event_bus.clone();
 
@@ -758,55 +874,8 @@ async fn main() {
             let window = app_handle.get_window("main").unwrap();
 
             // Set up event listeners - use the cloned event_bus
-            setup_event_listener(window.clone(), setup_event_bus);
-
-            // Set up Link event handler
-            let window_for_link = window.clone();
-            let event_bus_for_link = event_bus.clone();
-
-            tokio::spawn(async move {
-                let mut rx = event_bus_for_link.subscribe();
-
-                while let Ok(event) = rx.recv().await {
-                    match event {
-                        Event::LinkStatusChanged { connected, peers } => {
-                            // Send to frontend
-                            let status = serde_json::json!({
-                                "type": "link_status",
-                                "connected": connected,
-                                "peers": peers
-                            });
-
-                            if let Err(e) = window_for_link.emit("link-event", status.to_string()) {
-                                error!("Failed to emit link status: {}", e);
-                            }
-                        },
-                        Event::LinkTempoChanged { tempo } => {
-                            // Send to frontend
-                            let status = serde_json::json!({
-                                "type": "link_tempo",
-                                "tempo": tempo
-                            });
-
-                            if let Err(e) = window_for_link.emit("link-event", status.to_string()) {
-                                error!("Failed to emit link tempo: {}", e);
-                            }
-                        },
-                        Event::LinkTransportChanged { playing } => {
-                            // Send to frontend
-                            let status = serde_json::json!({
-                                "type": "link_transport",
-                                "playing": playing
-                            });
-
-                            if let Err(e) = window_for_link.emit("link-event", status.to_string()) {
-                                error!("Failed to emit link transport: {}", e);
-                            }
-                        },
-                        _ => {} // Ignore other events
-                    }
-                }
-            });
+            setup_runtime_forwarder(window.clone(), setup_runtime);
+            setup_event_listener(window, setup_event_bus);
 
             Ok(())
         })
@@ -816,15 +885,31 @@ async fn main() {
             shared_state,
             midi_manager,
             link_sync,
+            control,
+            runtime,
         })
         .invoke_handler(tauri::generate_handler![
             list_midi_inputs,
             list_midi_outputs,
+            set_control_input,
             get_project,
             save_project,
             load_project,
             new_project,
+            list_templates,
+            save_template,
+            get_recent_projects,
+            get_osc_settings,
+            set_osc_settings,
+            get_api_settings,
+            set_api_settings,
+            get_mcp_settings,
+            set_mcp_settings,
+            export_bank,
+            import_bank,
+            merge_project,
             select_snap,
+            select_bank,
             edit_parameter,
             generate_ai_values,
             start_morph,
@@ -836,13 +921,43 @@ async fn main() {
             set_controller,
             send_wiggle,
             debug_state,
+            get_event_metrics,
+            reset_event_metrics,
             get_link_status,
+            get_runtime_state,
+            get_curves,
+            save_curve,
+            delete_curve,
+            sample_curve,
+            sample_curve_shape,
+            set_parameter_morph_timing,
+            set_parameter_motion,
+            set_snap_morph_timing,
+            set_recall_crossfade,
+            start_loop,
+            stop_morph,
+            start_manual_morph,
+            set_morph_position,
+            set_manual_control,
+            set_interrupt_policy,
+            set_launch_quantization,
+            get_chains,
+            save_chain,
+            delete_chain,
+            start_chain,
+            get_runtime_deltas,
             set_link_tempo,
             set_link_enabled,
             start_link_transport,
             stop_link_transport,
             set_link_quantum,
         ])
-        .run(tauri::generate_context!())
-        .expect("Error while running Tauri application");
+        .build(tauri::generate_context!())
+        .expect("Error while building Tauri application")
+        .run(|app_handle, event| {
+            // Let the session recorder write the final session before the process ends
+            if let tauri::RunEvent::Exit = event {
+                app_handle.state::<AppState>().app.lock().unwrap().shutdown();
+            }
+        });
 }
\ No newline at end of file
@@ -0,0 +1,586 @@
+// src-tauri/src/mcp.rs
+use crate::api::constant_time_eq;
+use crate::control::{parse_curve, ControlService};
+use crate::events::{LaunchQuantize, MorphDuration};
+use crate::model::SNAP_PADS;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+/// MCP revision this server speaks
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Model Context Protocol server settings, stored with the app settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct McpSettings {
+    /// Serve MCP on localhost
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// TCP port on 127.0.0.1; the `mcp_bridge` binary connects here
+    #[serde(default = "default_port")]
+    pub port: u16,
+
+    /// Token clients must send as `{"token": "..."}` before any other message;
+    /// `mcp_bridge` sends the one in `SNAPBLASTER_MCP_TOKEN`
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+fn default_port() -> u16 {
+    9200
+}
+
+impl Default for McpSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_port(),
+            token: None,
+        }
+    }
+}
+
+/// Start serving MCP over newline-delimited JSON-RPC on a localhost socket.
+///
+/// Agents that only speak stdio connect through the `mcp_bridge` binary. A connection
+/// is closed on the first line that is not JSON-RPC, so that other protocols, such as
+/// a web page posting to localhost, cannot get a request through.
+pub fn start(settings: &McpSettings, control: ControlService) -> Result<JoinHandle<()>, Box<dyn Error>> {
+    // Agents can edit the project, so the server is never exposed beyond this machine
+    let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, settings.port))?;
+    listener.set_nonblocking(true)?;
+    let listener = TcpListener::from_std(listener)?;
+    info!("MCP server listening on {}", listener.local_addr()?);
+    let token: Option<Arc<str>> = settings.token.as_deref().map(Arc::from);
+
+    Ok(tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => {
+                    info!("MCP client connected from {}", addr);
+                    let control = control.clone();
+                    let token = token.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve_connection(stream, control, token).await {
+                            debug!("MCP connection from {} closed: {}", addr, e);
+                        }
+                    });
+                }
+                Err(e) => error!("Failed to accept MCP connection: {}", e),
+            }
+        }
+    }))
+}
+
+async fn serve_connection(
+    stream: TcpStream,
+    control: ControlService,
+    token: Option<Arc<str>>,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut authenticated = token.is_none();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let message = serde_json::from_str::<Value>(&line).ok();
+
+        if !authenticated {
+            let presented = message.as_ref().and_then(|m| m.get("token")).and_then(Value::as_str);
+            match (presented, &token) {
+                (Some(presented), Some(expected)) if constant_time_eq(presented.as_bytes(), expected.as_bytes()) => {
+                    authenticated = true;
+                    continue;
+                }
+                _ => {
+                    let response = error_response(Value::Null, INVALID_REQUEST, "Missing or invalid token".to_string());
+                    write_message(&mut writer, &response).await?;
+                    return Err("client did not present the token".into());
+                }
+            }
+        }
+
+        let Some(message) = message.filter(|m| m.get("jsonrpc").and_then(Value::as_str) == Some("2.0")) else {
+            let response = error_response(Value::Null, PARSE_ERROR, "Not a JSON-RPC 2.0 message".to_string());
+            write_message(&mut writer, &response).await?;
+            return Err("client sent a line that is not JSON-RPC".into());
+        };
+
+        if let Some(response) = handle_message(&message, &control).await {
+            write_message(&mut writer, &response).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn write_message(
+    writer: &mut tokio::net::tcp::OwnedWriteHalf,
+    message: &Value,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut bytes = serde_json::to_vec(message)?;
+    bytes.push(b'\n');
+    writer.write_all(&bytes).await?;
+    Ok(())
+}
+
+/// Handle one message, returning the response unless it was a notification
+async fn handle_message(message: &Value, control: &ControlService) -> Option<Value> {
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        // Responses to requests we never make are dropped
+        if message.get("id").is_some() && message.get("result").is_none() && message.get("error").is_none() {
+            return Some(error_response(message["id"].clone(), INVALID_REQUEST, "Missing method".to_string()));
+        }
+        return None;
+    };
+
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let Some(id) = message.get("id").cloned() else {
+        debug!("MCP notification: {}", method);
+        return None;
+    };
+
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {}, "resources": {} },
+            "serverInfo": { "name": "snap-blaster", "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+        "tools/call" => call_tool(&params, control).await,
+        "resources/list" => Ok(json!({ "resources": resource_definitions() })),
+        "resources/read" => read_resource(&params, control),
+        _ => Err((METHOD_NOT_FOUND, format!("Unknown method: {}", method))),
+    };
+
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => error_response(id, code, message),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Tools agents can call, with JSON schemas for their arguments
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "get_project",
+            "description": "The open project: parameters, and every bank with its snaps and values",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "get_parameters",
+            "description": "The project's parameters; snap values are listed in this order",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "get_state",
+            "description": "The current bank, snap, morph duration and morph progress",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "select_bank",
+            "description": "Make a bank current",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "bank_id": { "type": "integer", "minimum": 0 } },
+                "required": ["bank_id"],
+            },
+        },
+        {
+            "name": "select_snap",
+            "description": "Recall a snap, sending all its values to the DAW",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "bank_id": { "type": "integer", "minimum": 0, "description": "Defaults to the current bank" },
+                    "snap_id": { "type": "integer", "minimum": 0 },
+                },
+                "required": ["snap_id"],
+            },
+        },
+        {
+            "name": "create_snap",
+            "description": "Put a new snap on a pad of a bank",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "bank_id": { "type": "integer", "minimum": 0 },
+                    "pad_index": { "type": "integer", "minimum": 0, "maximum": SNAP_PADS - 1 },
+                    "name": { "type": "string" },
+                    "description": { "type": "string" },
+                    "values": {
+                        "type": "array",
+                        "items": { "type": "integer", "minimum": 0, "maximum": 127 },
+                        "description": "One value per parameter; all 64 if omitted",
+                    },
+                },
+                "required": ["bank_id", "pad_index", "name"],
+            },
+        },
+        {
+            "name": "edit_snap",
+            "description": "Change the name, description or values of a snap",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "bank_id": { "type": "integer", "minimum": 0 },
+                    "snap_id": { "type": "integer", "minimum": 0 },
+                    "name": { "type": "string" },
+                    "description": { "type": "string" },
+                    "values": {
+                        "type": "array",
+                        "items": { "type": "integer", "minimum": 0, "maximum": 127 },
+                        "description": "One value per parameter",
+                    },
+                },
+                "required": ["bank_id", "snap_id"],
+            },
+        },
+        {
+            "name": "set_parameter",
+            "description": "Set one parameter of the current snap and send it to the DAW",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "param_id": { "type": "integer", "minimum": 0 },
+                    "value": { "type": "integer", "minimum": 0, "maximum": 127 },
+                },
+                "required": ["param_id", "value"],
+            },
+        },
+        {
+            "name": "start_morph",
+            "description": "Morph from the current snap to another snap of the current bank",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "to_snap": { "type": "integer", "minimum": 0 },
+                    "duration": { "type": "string", "description": "Bars, a bar fraction, beats or milliseconds, such as 4bars, 3/4bar, 2beats or 500ms; a plain number is in bars. Defaults to the duration picked on the controller" },
+                    "curve": { "type": "string", "description": "Built-in curve (linear, exponential, logarithmic, scurve) or a user curve ID" },
+                    "quantize": { "type": ["boolean", "string"], "description": "When to start: none, beat, bar, 2bars, 4bars, 8bars or 16bars (true means bar). Defaults to the bank's setting" },
+                },
+                "required": ["to_snap"],
+            },
+        },
+    ])
+}
+
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+struct BankArgs {
+    bank_id: usize,
+}
+
+#[derive(Deserialize)]
+struct SnapArgs {
+    bank_id: Option<usize>,
+    snap_id: usize,
+}
+
+#[derive(Deserialize)]
+struct CreateSnapArgs {
+    bank_id: usize,
+    pad_index: usize,
+    name: String,
+    #[serde(default)]
+    description: String,
+    values: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct EditSnapArgs {
+    bank_id: usize,
+    snap_id: usize,
+    name: Option<String>,
+    description: Option<String>,
+    values: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct ParameterArgs {
+    param_id: usize,
+    value: u8,
+}
+
+#[derive(Deserialize)]
+struct MorphArgs {
+    to_snap: usize,
+    duration: Option<String>,
+    curve: Option<String>,
+    quantize: Option<LaunchQuantize>,
+}
+
+/// Run a tool. Failures of the operation itself are reported in the result so
+/// the agent can see and correct them; only malformed calls are protocol errors.
+async fn call_tool(params: &Value, control: &ControlService) -> Result<Value, (i64, String)> {
+    let call: ToolCall = serde_json::from_value(params.clone()).map_err(|e| (INVALID_PARAMS, e.to_string()))?;
+    let arguments = if call.arguments.is_null() { json!({}) } else { call.arguments };
+    debug!("MCP tool call: {} {}", call.name, arguments);
+
+    let outcome: Result<Value, String> = match call.name.as_str() {
+        "get_project" => Ok(json!(control.project())),
+        "get_parameters" => Ok(json!(control.project().parameters)),
+        "get_state" => Ok(json!(control.selection())),
+        "select_bank" => {
+            let args: BankArgs = parse_args(arguments)?;
+            control.select_bank(args.bank_id).map(|_| json!("ok"))
+        }
+        "select_snap" => {
+            let args: SnapArgs = parse_args(arguments)?;
+            let bank_id = args.bank_id.unwrap_or_else(|| control.current_bank());
+            control.select_snap(bank_id, args.snap_id).map(|_| json!("ok"))
+        }
+        "create_snap" => {
+            let args: CreateSnapArgs = parse_args(arguments)?;
+            control
+                .add_snap(args.bank_id, args.pad_index, args.name, args.description, args.values)
+                .map(|_| json!("ok"))
+        }
+        "edit_snap" => {
+            let args: EditSnapArgs = parse_args(arguments)?;
+            control
+                .update_snap(args.bank_id, args.snap_id, args.name, args.description, args.values)
+                .map(|_| json!("ok"))
+        }
+        "set_parameter" => {
+            let args: ParameterArgs = parse_args(arguments)?;
+            control.set_parameter(args.param_id, args.value).map(|_| json!("ok"))
+        }
+        "start_morph" => {
+            let args: MorphArgs = parse_args(arguments)?;
+            let curve = parse_curve(args.curve.as_deref().unwrap_or("linear"));
+            args.duration
+                .map(|duration| duration.parse::<MorphDuration>())
+                .transpose()
+                .and_then(|duration| control.morph_to(args.to_snap, duration, curve, args.quantize))
+                .map(|_| json!("ok"))
+        }
+        _ => return Err((INVALID_PARAMS, format!("Unknown tool: {}", call.name))),
+    };
+
+    Ok(match outcome {
+        Ok(value) => json!({
+            "content": [{ "type": "text", "text": value.to_string() }],
+            "isError": false,
+        }),
+        Err(e) => {
+            warn!("MCP tool {} failed: {}", call.name, e);
+            json!({
+                "content": [{ "type": "text", "text": e }],
+                "isError": true,
+            })
+        }
+    })
+}
+
+fn parse_args<T: for<'de> Deserialize<'de>>(arguments: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(arguments).map_err(|e| (INVALID_PARAMS, format!("Invalid arguments: {}", e)))
+}
+
+/// Read-only views of the running app
+fn resource_definitions() -> Value {
+    json!([
+        {
+            "uri": "snapblaster://project",
+            "name": "Project",
+            "description": "The open project with all banks, snaps and parameters",
+            "mimeType": "application/json",
+        },
+        {
+            "uri": "snapblaster://parameters",
+            "name": "Parameters",
+            "description": "The project's parameters and their CC numbers",
+            "mimeType": "application/json",
+        },
+        {
+            "uri": "snapblaster://state",
+            "name": "Current state",
+            "description": "The current bank, snap and morph",
+            "mimeType": "application/json",
+        },
+    ])
+}
+
+fn read_resource(params: &Value, control: &ControlService) -> Result<Value, (i64, String)> {
+    let uri = params
+        .get("uri")
+        .and_then(Value::as_str)
+        .ok_or_else(|| (INVALID_PARAMS, "Missing uri".to_string()))?;
+
+    let contents = match uri {
+        "snapblaster://project" => json!(control.project()),
+        "snapblaster://parameters" => json!(control.project().parameters),
+        "snapblaster://state" => json!(control.selection()),
+        _ => return Err((INVALID_PARAMS, format!("Unknown resource: {}", uri))),
+    };
+
+    Ok(json!({
+        "contents": [{
+            "uri": uri,
+            "mimeType": "application/json",
+            "text": contents.to_string(),
+        }],
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBus;
+    use crate::model::new_shared_state;
+
+    fn control() -> ControlService {
+        ControlService::new(new_shared_state(), EventBus::new(64, "test"), None, None)
+    }
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 7, "method": method, "params": params })
+    }
+
+    /// Connect a client to `serve_connection` over a loopback socket
+    async fn connect(token: Option<&str>) -> (BufReader<TcpStream>, JoinHandle<bool>) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let token = token.map(Arc::from);
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve_connection(stream, control(), token).await.is_ok()
+        });
+        (BufReader::new(TcpStream::connect(addr).await.unwrap()), server)
+    }
+
+    async fn send(client: &mut BufReader<TcpStream>, line: &str) {
+        client.get_mut().write_all(format!("{}\n", line).as_bytes()).await.unwrap();
+    }
+
+    async fn receive(client: &mut BufReader<TcpStream>) -> Value {
+        let mut line = String::new();
+        client.read_line(&mut line).await.unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[tokio::test]
+    async fn notifications_get_no_response() {
+        let control = control();
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(handle_message(&notification, &control).await.is_none());
+
+        // Nor do responses to requests the server never made
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": {} });
+        assert!(handle_message(&response, &control).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn requests_are_answered_with_their_id() {
+        let control = control();
+        let response = handle_message(&request("initialize", json!({})), &control).await.unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        let response = handle_message(&request("tools/list", Value::Null), &control).await.unwrap();
+        assert!(response["result"]["tools"].as_array().unwrap().len() > 1);
+    }
+
+    #[tokio::test]
+    async fn malformed_requests_get_error_codes() {
+        let control = control();
+        let missing_method = json!({ "jsonrpc": "2.0", "id": 3 });
+        let response = handle_message(&missing_method, &control).await.unwrap();
+        assert_eq!(response["id"], 3);
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let response = handle_message(&request("tools/unknown", json!({})), &control).await.unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = handle_message(&request("resources/read", json!({})), &control).await.unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn malformed_tool_calls_are_protocol_errors() {
+        let control = control();
+        let unknown = call_tool(&json!({ "name": "delete_everything" }), &control).await;
+        assert_eq!(unknown.unwrap_err().0, INVALID_PARAMS);
+
+        let bad_arguments = json!({ "name": "select_bank", "arguments": { "bank_id": "first" } });
+        assert_eq!(call_tool(&bad_arguments, &control).await.unwrap_err().0, INVALID_PARAMS);
+
+        assert_eq!(call_tool(&json!({}), &control).await.unwrap_err().0, INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn failed_operations_are_reported_in_the_result() {
+        let bus = EventBus::new(64, "test");
+        let _receiver = bus.subscribe();
+        let control = ControlService::new(new_shared_state(), bus, None, None);
+        let result = call_tool(&json!({ "name": "select_bank", "arguments": { "bank_id": 99 } }), &control)
+            .await
+            .unwrap();
+        assert_eq!(result["isError"], true);
+        assert_eq!(result["content"][0]["text"], "Bank ID out of range");
+
+        let result = call_tool(&json!({ "name": "select_bank", "arguments": { "bank_id": 0 } }), &control)
+            .await
+            .unwrap();
+        assert_eq!(result["isError"], false);
+    }
+
+    #[tokio::test]
+    async fn the_token_must_come_first() {
+        let (mut client, server) = connect(Some("secret")).await;
+        send(&mut client, &request("ping", json!({})).to_string()).await;
+        assert_eq!(receive(&mut client).await["error"]["code"], INVALID_REQUEST);
+        assert!(!server.await.unwrap());
+
+        let (mut client, server) = connect(Some("secret")).await;
+        send(&mut client, r#"{"token": "wrong"}"#).await;
+        assert_eq!(receive(&mut client).await["error"]["code"], INVALID_REQUEST);
+        assert!(!server.await.unwrap());
+
+        let (mut client, server) = connect(Some("secret")).await;
+        send(&mut client, r#"{"token": "secret"}"#).await;
+        send(&mut client, &request("ping", json!({})).to_string()).await;
+        assert_eq!(receive(&mut client).await["result"], json!({}));
+        drop(client);
+        assert!(server.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn lines_that_are_not_json_rpc_close_the_connection() {
+        let (mut client, server) = connect(None).await;
+        send(&mut client, "POST / HTTP/1.1").await;
+        assert_eq!(receive(&mut client).await["error"]["code"], PARSE_ERROR);
+        assert!(!server.await.unwrap());
+
+        let (mut client, server) = connect(None).await;
+        send(&mut client, r#"{"id": 1, "method": "ping"}"#).await;
+        assert_eq!(receive(&mut client).await["error"]["code"], PARSE_ERROR);
+        assert!(!server.await.unwrap());
+    }
+}
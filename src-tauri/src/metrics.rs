@@ -0,0 +1,349 @@
+// src-tauri/src/metrics.rs
+use crate::events::{Event, EventBus, EventStats};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::info;
+
+/// Upper bounds of the latency histogram buckets in microseconds; the last bucket is open
+const LATENCY_BUCKETS_US: [u64; 10] = [100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000];
+
+/// How often the metrics event is published by default
+pub const DEFAULT_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Latency histogram with fixed buckets, safe to record into from any thread
+#[derive(Debug, Default)]
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS_US.len() + 1],
+    count: AtomicU64,
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl LatencyHistogram {
+    /// Record one measurement
+    pub fn record(&self, latency: Duration) {
+        let us = latency.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = LATENCY_BUCKETS_US
+            .iter()
+            .position(|&bound| us <= bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(us, Ordering::Relaxed);
+        self.max_us.fetch_max(us, Ordering::Relaxed);
+    }
+
+    /// Take a consistent-enough copy for reporting
+    pub fn snapshot(&self) -> LatencySnapshot {
+        let buckets: Vec<u64> = self.buckets.iter().map(|b| b.load(Ordering::Relaxed)).collect();
+        let count = self.count.load(Ordering::Relaxed);
+        let sum_us = self.sum_us.load(Ordering::Relaxed);
+        let max_us = self.max_us.load(Ordering::Relaxed);
+
+        LatencySnapshot {
+            count,
+            mean_us: sum_us.checked_div(count).unwrap_or(0),
+            max_us,
+            p50_us: percentile(&buckets, count, 0.50, max_us),
+            p95_us: percentile(&buckets, count, 0.95, max_us),
+            p99_us: percentile(&buckets, count, 0.99, max_us),
+            bucket_bounds_us: LATENCY_BUCKETS_US.to_vec(),
+            buckets,
+        }
+    }
+
+    fn reset(&self) {
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.count.store(0, Ordering::Relaxed);
+        self.sum_us.store(0, Ordering::Relaxed);
+        self.max_us.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Upper bound of the bucket containing the given percentile, capped at the maximum seen
+fn percentile(buckets: &[u64], count: u64, fraction: f64, max_us: u64) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+
+    let rank = ((count as f64) * fraction).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    for (idx, &bucket) in buckets.iter().enumerate() {
+        seen += bucket;
+        if seen >= rank {
+            return LATENCY_BUCKETS_US.get(idx).map_or(max_us, |&bound| bound.min(max_us));
+        }
+    }
+    max_us
+}
+
+/// Reported view of a latency histogram
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LatencySnapshot {
+    pub count: u64,
+    pub mean_us: u64,
+    pub max_us: u64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    /// Upper bound of each bucket but the last, which is open-ended
+    pub bucket_bounds_us: Vec<u64>,
+    pub buckets: Vec<u64>,
+}
+
+/// Counters for one subscription
+#[derive(Debug)]
+pub struct SubscriberMetrics {
+    name: String,
+    received: AtomicU64,
+    lag_events: AtomicU64,
+    dropped: AtomicU64,
+
+    /// Time from an event being published to this subscriber having handled it
+    latency: LatencyHistogram,
+}
+
+impl SubscriberMetrics {
+    /// Count a received event
+    pub fn record_received(&self) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a lag during which `missed` events were dropped
+    pub fn record_lag(&self, missed: u64) {
+        self.lag_events.fetch_add(1, Ordering::Relaxed);
+        self.dropped.fetch_add(missed, Ordering::Relaxed);
+    }
+
+    /// Record how long after publishing an event was handled
+    pub fn record_latency(&self, latency: Duration) {
+        self.latency.record(latency);
+    }
+
+    fn snapshot(&self) -> SubscriberSnapshot {
+        SubscriberSnapshot {
+            name: self.name.clone(),
+            received: self.received.load(Ordering::Relaxed),
+            lag_events: self.lag_events.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            latency: self.latency.snapshot(),
+        }
+    }
+
+    fn reset(&self) {
+        self.received.store(0, Ordering::Relaxed);
+        self.lag_events.store(0, Ordering::Relaxed);
+        self.dropped.store(0, Ordering::Relaxed);
+        self.latency.reset();
+    }
+}
+
+/// Reported view of a subscription
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SubscriberSnapshot {
+    pub name: String,
+    pub received: u64,
+    /// Number of times the subscriber fell behind
+    pub lag_events: u64,
+    /// Events lost while behind
+    pub dropped: u64,
+    /// Publish-to-handled latency
+    pub latency: LatencySnapshot,
+}
+
+/// Reported view of one lane's totals
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LaneSnapshot {
+    pub sent: usize,
+    pub received: usize,
+    pub dropped: usize,
+}
+
+impl LaneSnapshot {
+    fn from_stats(stats: &EventStats) -> Self {
+        Self {
+            sent: stats.messages_sent.load(Ordering::Relaxed),
+            received: stats.messages_received.load(Ordering::Relaxed),
+            dropped: stats.events_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Everything the bus knows about its own health
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub normal: LaneSnapshot,
+    pub realtime: LaneSnapshot,
+    pub subscribers_peak: usize,
+    pub handler_restarts: usize,
+    /// Time from a pad press being published to the first CC of the snap it recalls.
+    /// Only unquantized recalls are timed: a quantized one waits for the beat grid on purpose.
+    #[serde(default)]
+    pub pad_to_cc: LatencySnapshot,
+    /// Events published per event type
+    pub event_types: BTreeMap<String, u64>,
+    pub subscribers: Vec<SubscriberSnapshot>,
+}
+
+/// BusMetrics holds per-type and per-subscriber counters for an event bus
+#[derive(Debug, Default)]
+pub struct BusMetrics {
+    event_types: Mutex<HashMap<&'static str, u64>>,
+    subscribers: Mutex<Vec<Weak<SubscriberMetrics>>>,
+
+    /// When the latest pad press was published, until a CC answers it
+    pad_pressed: Mutex<Option<Instant>>,
+    pad_to_cc: LatencyHistogram,
+}
+
+impl BusMetrics {
+    /// Count a published event
+    pub fn record_published(&self, event: &Event) {
+        *self.event_types.lock().unwrap().entry(event.event_type()).or_insert(0) += 1;
+        if let Event::PadPressed { .. } = event {
+            *self.pad_pressed.lock().unwrap() = Some(Instant::now());
+        }
+    }
+
+    /// Record the first CC sent in answer to the latest pad press; later calls for
+    /// the same press are ignored
+    pub fn record_pad_to_cc(&self) {
+        if let Some(pressed) = self.pad_pressed.lock().unwrap().take() {
+            self.pad_to_cc.record(pressed.elapsed());
+        }
+    }
+
+    /// Register a subscription; its counters are reported for as long as it is alive
+    pub fn register_subscriber(&self, name: &str) -> Arc<SubscriberMetrics> {
+        let metrics = Arc::new(SubscriberMetrics {
+            name: name.to_string(),
+            received: AtomicU64::new(0),
+            lag_events: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            latency: LatencyHistogram::default(),
+        });
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|s| s.strong_count() > 0);
+        subscribers.push(Arc::downgrade(&metrics));
+        metrics
+    }
+
+    /// Report the counters together with the lane totals
+    pub fn snapshot(&self, stats: &EventStats, realtime_stats: &EventStats) -> MetricsSnapshot {
+        let event_types = self
+            .event_types
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, count)| (name.to_string(), *count))
+            .collect();
+
+        let mut subscribers: Vec<SubscriberSnapshot> = self
+            .subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|s| s.snapshot())
+            .collect();
+        subscribers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        MetricsSnapshot {
+            normal: LaneSnapshot::from_stats(stats),
+            realtime: LaneSnapshot::from_stats(realtime_stats),
+            subscribers_peak: stats.subscribers_peak.load(Ordering::Relaxed),
+            handler_restarts: stats.handler_restarts.load(Ordering::Relaxed),
+            pad_to_cc: self.pad_to_cc.snapshot(),
+            event_types,
+            subscribers,
+        }
+    }
+
+    /// Clear all counters
+    pub fn reset(&self) {
+        self.event_types.lock().unwrap().clear();
+        self.pad_to_cc.reset();
+        for subscriber in self.subscribers.lock().unwrap().iter().filter_map(Weak::upgrade) {
+            subscriber.reset();
+        }
+    }
+}
+
+/// Publish a metrics snapshot on the bus at a fixed interval
+pub fn start_reporter(event_bus: EventBus, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        info!("Metrics reporter started, every {:?}", interval);
+        let mut ticker = time::interval(interval);
+        ticker.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+        // The first tick fires immediately; skip it so the first report covers a full interval
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let metrics = Box::new(event_bus.metrics());
+            event_bus.try_publish(Event::MetricsReported { metrics });
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_by_upper_bound() {
+        let histogram = LatencyHistogram::default();
+        for us in [50, 100, 300, 300, 2_000, 200_000] {
+            histogram.record(Duration::from_micros(us));
+        }
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.buckets, vec![2, 0, 2, 0, 1, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(snapshot.bucket_bounds_us.len() + 1, snapshot.buckets.len());
+        assert_eq!(snapshot.count, 6);
+        assert_eq!(snapshot.mean_us, 202_750 / 6);
+        assert_eq!(snapshot.max_us, 200_000);
+        assert_eq!(snapshot.p50_us, 500);
+        assert_eq!(snapshot.p95_us, 200_000);
+        assert_eq!(snapshot.p99_us, 200_000);
+
+        histogram.reset();
+        assert_eq!(histogram.snapshot().count, 0);
+        assert_eq!(histogram.snapshot().p50_us, 0);
+    }
+
+    #[test]
+    fn percentiles_are_capped_at_the_maximum_seen() {
+        let histogram = LatencyHistogram::default();
+        histogram.record(Duration::from_micros(300));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.p50_us, 300);
+        assert_eq!(snapshot.p99_us, 300);
+    }
+
+    #[test]
+    fn percentile_ranks_count_from_the_fastest() {
+        let mut buckets = vec![0; LATENCY_BUCKETS_US.len() + 1];
+        buckets[0] = 90;
+        buckets[3] = 9;
+        buckets[LATENCY_BUCKETS_US.len()] = 1;
+
+        assert_eq!(percentile(&buckets, 0, 0.5, 0), 0);
+        assert_eq!(percentile(&buckets, 100, 0.5, 500_000), 100);
+        assert_eq!(percentile(&buckets, 100, 0.9, 500_000), 100);
+        assert_eq!(percentile(&buckets, 100, 0.95, 500_000), 1_000);
+        assert_eq!(percentile(&buckets, 100, 0.99, 500_000), 1_000);
+        assert_eq!(percentile(&buckets, 100, 1.0, 500_000), 500_000);
+    }
+}
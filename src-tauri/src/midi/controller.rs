@@ -1,11 +1,12 @@
-use crate::events::{Event, EventBus};
+use crate::events::EventBus;
 use crate::midi::controllers::generic::GenericController;
 use crate::midi::controllers::launchpad_x::LaunchpadX;
+use serde::{Deserialize, Serialize};
 use std::error::Error;
 use tracing::{info, warn};
 
 /// RGB color representation for controller LEDs
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Rgb {
     pub r: u8,
     pub g: u8,
@@ -107,6 +108,9 @@ pub trait MidiGridController: Send + 'static {
     /// Update all LEDs to match the current state
     fn refresh_state(&mut self);
 
+    /// Colors last set on the grid, by row and column (pad = row * 8 + column)
+    fn led_grid(&self) -> [[Rgb; 8]; 8];
+
     /// Gets the name of this controller
     fn get_name(&self) -> &str;
     fn set_progress_led(&mut self, pad: u8, progress: f64) -> Result<(), Box<dyn Error>>;
@@ -132,7 +136,7 @@ pub fn create_controller(
                 fallback_controller(event_bus)
             }
         },
-        "Generic" | _ => fallback_controller(event_bus),
+        _ => fallback_controller(event_bus),
     }
 }
 
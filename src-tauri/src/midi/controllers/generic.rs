@@ -1,7 +1,7 @@
 use crate::events::{Event, EventBus};
 use crate::midi::controller::{MidiGridController, Rgb};
 use std::error::Error;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
 
 /// A generic controller implementation that can be used when no hardware is available
 /// or when the specific controller could not be initialized
@@ -68,19 +68,23 @@ impl MidiGridController for GenericController {
         debug!("Generic controller refreshing state");
     }
 
+    fn led_grid(&self) -> [[Rgb; 8]; 8] {
+        self.led_state
+    }
+
     fn get_name(&self) -> &str {
         "Generic Controller"
     }
 
-    fn set_progress_led(&mut self, pad: u8, progress: f64) -> Result<(), Box<dyn Error>> {
+    fn set_progress_led(&mut self, _pad: u8, _progress: f64) -> Result<(), Box<dyn Error>> {
         todo!()
     }
 
-    fn set_morph_target_led(&mut self, pad: u8) -> Result<(), Box<dyn Error>> {
+    fn set_morph_target_led(&mut self, _pad: u8) -> Result<(), Box<dyn Error>> {
         todo!()
     }
 
-    fn set_active_modifier_led(&mut self, pad: u8) -> Result<(), Box<dyn Error>> {
+    fn set_active_modifier_led(&mut self, _pad: u8) -> Result<(), Box<dyn Error>> {
         todo!()
     }
 
@@ -6,7 +6,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::thread;
 use tracing::{debug, error, info, warn};
-use wmidi::{MidiMessage, Note, U7};
+use wmidi::{MidiMessage, U7};
 
 /// LaunchpadX Controller Implementation
 pub struct LaunchpadX {
@@ -117,9 +117,15 @@ impl LaunchpadX {
                             // Handle control change messages if needed
                             debug!("Received CC: cc={:?}, value={:?}", cc, value);
                         },
+                        MidiMessage::PolyphonicKeyPressure(_, note, pressure) => {
+                            // Pressure on a held pad can drive a manual morph
+                            if let Some(pad) = note_to_pad_index(u8::from(note)) {
+                                let _ = event_bus.publish(Event::PadPressure { pad, pressure: u7_to_u8(pressure) });
+                            }
+                        },
                         _ => {
                             //debug!("Received other MIDI message: {:?}", midi_msg);
-                        } // Ignore other message types like aftertouch
+                        } // Ignore other message types like channel pressure
                     }
                 }
             },
@@ -209,7 +215,7 @@ impl LaunchpadX {
 
         // Convert to Launchpad X programmer mode format
         // Invert the row mapping (0->8, 1->7, 2->6, etc.) to match physical layout
-        (10 * (8 - row) + (col + 1)) as u8
+        10 * (8 - row) + (col + 1)
     }
 
     /// Internal method to clear all LEDs
@@ -302,27 +308,23 @@ impl LaunchpadX {
         }
 
         // Fallback - dim white
-        return 0;
+        0
     }
 }
 
 impl MidiGridController for LaunchpadX {
     fn handle_note_input(&mut self, note: u8, velocity: u8) {
-        // Only process note-on events (velocity > 0)
-        if velocity >= 0 {
-            if let Some(pad) = note_to_pad_index(note) {
-                debug!("Received note: {}, mapped to pad: {}", note, pad);
-
-                // Publish event through the event bus
-                self.event_bus.try_publish(Event::PadPressed {
-                    pad,
-                    velocity
-                });
-            } else {
-                debug!("Note {} does not map to a valid pad", note);
-            }
+        // Note-offs are published too, with velocity 0, so modifier pads can be released
+        if let Some(pad) = note_to_pad_index(note) {
+            debug!("Received note: {}, mapped to pad: {}", note, pad);
+
+            // Publish event through the event bus
+            self.event_bus.try_publish(Event::PadPressed {
+                pad,
+                velocity
+            });
         } else {
-            debug!("Received note {}, velocity {}", note, 0);
+            debug!("Note {} does not map to a valid pad", note);
         }
     }
 
@@ -397,6 +399,10 @@ impl MidiGridController for LaunchpadX {
         }
     }
 
+    fn led_grid(&self) -> [[Rgb; 8]; 8] {
+        self.led_buffer
+    }
+
     fn get_name(&self) -> &str {
         "Launchpad X"
     }
@@ -409,7 +415,7 @@ impl MidiGridController for LaunchpadX {
             let green = (progress * 255.0) as u8;
 
             // Add a pulse effect based on progress
-            let pulse = (((progress * 10.0) % 1.0) * 0.5 + 0.5) as f64; // Oscillates between 0.5 and 1.0
+            let pulse = ((progress * 10.0) % 1.0) * 0.5 + 0.5; // Oscillates between 0.5 and 1.0
 
             // Apply pulse to brightness
             let b = (blue as f64 * pulse) as u8;
@@ -511,14 +517,6 @@ impl PortInfos<midir::MidiOutputPort> for MidiOutput {
     }
 }
 
-/// Convert a pad index (0-63) to row and column
-fn pad_to_row_col(pad: u8) -> (usize, usize) {
-    let row = (pad / 8) as usize;
-    let col = (pad % 8) as usize;
-
-    (row, col)
-}
-
 /// Convert a MIDI note number to a pad index
 fn note_to_pad_index(note: u8) -> Option<u8> {
     // For Programmer Mode, the pads are mapped as follows:
@@ -527,7 +525,7 @@ fn note_to_pad_index(note: u8) -> Option<u8> {
     // and so on...
 
     // Check if it's in programmer mode format
-    if note >= 11 && note <= 99 {
+    if (11..=99).contains(&note) {
         let row = (note / 10) - 1;
         let col = (note % 10) - 1;
 
@@ -1,20 +1,63 @@
-use crate::events::{Event, EventBus, MorphCurve};
+use crate::control::ControlService;
+use crate::events::{Event, EventBus, MorphCurve, MorphDuration};
 use crate::midi::controller::{create_controller, MidiGridController, Rgb};
 use crate::model::SharedState;
+use crate::supervisor::{supervise_realtime, EventHandler, Flow};
 use midir::{Ignore, MidiInput, MidiInputConnection, MidiOutput, MidiOutputConnection};
 use std::error::Error;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::task::JoinHandle;
 use midir::os::unix::VirtualOutput;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, warn};
 
+/// Hardware MIDI ports selected for the controller, remembered across sessions
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MidiPortSelection {
+    /// Name of the controller input port
+    pub input: Option<String>,
+
+    /// Name of the controller output port
+    pub output: Option<String>,
+
+    /// Name of a separate input whose control changes drive manual morphs
+    #[serde(default)]
+    pub control_input: Option<String>,
+}
+
+/// Which MIDI connections are open
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PortStatus {
+    /// Open output ports, the virtual DAW port included
+    pub outputs: Vec<String>,
+
+    /// Whether the virtual port the DAW listens to is open
+    pub daw_output: bool,
+
+    /// Controller input port, if connected
+    pub controller_input: Option<String>,
+
+    /// Whether a grid controller is driving the LEDs
+    pub controller_connected: bool,
+
+    /// Manual-control input port, if connected
+    #[serde(default)]
+    pub control_input: Option<String>,
+}
+
 /// Main MIDI manager for Snap-Blaster with both virtual and hardware I/O
 pub struct MidiManager {
     event_bus: EventBus,
     controller: Arc<Mutex<Option<Box<dyn MidiGridController>>>>,
     input_connection: Arc<Mutex<Option<MidiInputConnection<()>>>>,
+    control_connection: Arc<Mutex<Option<MidiInputConnection<()>>>>,
     output_connections: Arc<Mutex<Vec<(String, MidiOutputConnection)>>>,
+    port_selection: Arc<Mutex<MidiPortSelection>>,
     state: Option<SharedState>,
+
+    /// Whether snap values sent through this handle answer a pad press, for latency metrics
+    from_pad: bool,
 }
 
 impl Clone for MidiManager {
@@ -23,8 +66,11 @@ impl Clone for MidiManager {
             event_bus: self.event_bus.clone(),
             controller: self.controller.clone(),
             input_connection: self.input_connection.clone(),
+            control_connection: self.control_connection.clone(),
             output_connections: self.output_connections.clone(),
+            port_selection: self.port_selection.clone(),
             state: self.state.clone(),
+            from_pad: self.from_pad,
         }
     }
 }
@@ -36,8 +82,11 @@ impl MidiManager {
             event_bus,
             controller: Arc::new(Mutex::new(None)),
             input_connection: Arc::new(Mutex::new(None)),
+            control_connection: Arc::new(Mutex::new(None)),
             output_connections: Arc::new(Mutex::new(Vec::new())),
+            port_selection: Arc::new(Mutex::new(MidiPortSelection::default())),
             state,
+            from_pad: false,
         }
     }
 
@@ -45,6 +94,55 @@ impl MidiManager {
         self.state.clone()
     }
 
+    /// Prefer specific hardware ports over matching by controller name.
+    /// Must be called before `initialize_controller` to take effect.
+    pub fn set_port_selection(&self, selection: MidiPortSelection) {
+        *self.port_selection.lock().unwrap() = selection;
+    }
+
+    /// The hardware ports currently selected for the controller
+    pub fn port_selection(&self) -> MidiPortSelection {
+        self.port_selection.lock().unwrap().clone()
+    }
+
+    /// Which MIDI connections are currently open
+    pub fn port_status(&self) -> PortStatus {
+        let outputs: Vec<String> = self
+            .output_connections
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect();
+        let controller_input = if self.input_connection.lock().unwrap().is_some() {
+            self.port_selection.lock().unwrap().input.clone()
+        } else {
+            None
+        };
+        let control_input = if self.control_connection.lock().unwrap().is_some() {
+            self.port_selection.lock().unwrap().control_input.clone()
+        } else {
+            None
+        };
+
+        PortStatus {
+            daw_output: outputs.iter().any(|name| name == "Snap-Blaster"),
+            outputs,
+            controller_input,
+            controller_connected: self.controller.lock().unwrap().is_some(),
+            control_input,
+        }
+    }
+
+    /// The controller's LED colors indexed by pad, or None without a controller
+    pub fn led_grid(&self) -> Option<Vec<Rgb>> {
+        self.controller
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|ctrl| ctrl.led_grid().iter().flatten().copied().collect())
+    }
+
     /// Create a virtual MIDI port for other apps
     pub fn create_virtual_port(&self, port_name: &str) -> Result<(), Box<dyn Error>> {
         let midi_out = MidiOutput::new("Snap-Blaster Virtual")?;
@@ -74,17 +172,69 @@ impl MidiManager {
         Ok(port_names)
     }
 
-    /// Open hardware MIDI ports and wire up callbacks that publish PadPressed events
+    /// Listen for manual-control CCs on a port other than the controller's,
+    /// such as a fader box; None closes the port
+    pub fn connect_control_input(&self, port: Option<&str>) -> Result<(), Box<dyn Error>> {
+        self.control_connection.lock().unwrap().take();
+        self.port_selection.lock().unwrap().control_input = None;
+
+        let Some(port_name) = port else {
+            info!("Closed the manual-control MIDI input");
+            let _ = self.event_bus.publish(Event::MidiPortsChanged);
+            return Ok(());
+        };
+
+        let mut midi_in = MidiInput::new("Snap-Blaster Control")?;
+        midi_in.ignore(Ignore::All);
+
+        let port = midi_in
+            .ports()
+            .into_iter()
+            .find(|port| midi_in.port_name(port).is_ok_and(|name| name == port_name))
+            .ok_or_else(|| format!("MIDI input port not found: {}", port_name))?;
+
+        let eb = self.event_bus.clone();
+        let conn = midi_in.connect(
+            &port,
+            "snapblaster-control",
+            move |_ts, msg: &[u8], _| {
+                if msg.len() >= 3 && (msg[0] & 0xF0) == 0xB0 {
+                    let _ = eb.publish(Event::ControlInput {
+                        channel: msg[0] & 0x0F,
+                        cc: msg[1],
+                        value: msg[2],
+                    });
+                }
+            },
+            (),
+        )?;
+
+        *self.control_connection.lock().unwrap() = Some(conn);
+        self.port_selection.lock().unwrap().control_input = Some(port_name.to_string());
+        info!("Connected manual-control MIDI input: {}", port_name);
+        let _ = self.event_bus.publish(Event::MidiPortsChanged);
+        Ok(())
+    }
+
+    /// Open hardware MIDI ports and wire up callbacks that publish pad and control events
     fn connect_hardware_ports(&self, controller_name: &str) -> Result<(), Box<dyn Error>> {
         // INPUT - Connect to the hardware controller's input port
         // This allows us to receive note messages from the controller
         let mut midi_in = MidiInput::new("Snap-Blaster Input")?;
         midi_in.ignore(Ignore::None);
 
+        let preferred_input = self.port_selection.lock().unwrap().input.clone();
+        let input_names: Vec<String> = midi_in
+            .ports()
+            .iter()
+            .filter_map(|port| midi_in.port_name(port).ok())
+            .collect();
+        let input_name = choose_port(&input_names, preferred_input.as_deref(), controller_name);
+
         let mut found_input = false;
         for port in midi_in.ports() {
             let name = midi_in.port_name(&port)?;
-            if name.contains(controller_name) {
+            if Some(&name) == input_name.as_ref() {
                 let eb = self.event_bus.clone();
                 let conn = midi_in.connect(
                     &port,
@@ -110,10 +260,19 @@ impl MidiManager {
                             debug!("Received note-off from hardware: note={}, vel={}", note, vel);
                             let _ = eb.publish(Event::PadReleased { pad: note, velocity: vel });
                         }
+                        // Control changes (0xBn) can drive a manual morph
+                        else if msg.len() >= 3 && (msg[0] & 0xF0) == 0xB0 {
+                            let _ = eb.publish(Event::ControlInput {
+                                channel: msg[0] & 0x0F,
+                                cc: msg[1],
+                                value: msg[2],
+                            });
+                        }
                     },
                     (),
                 )?;
                 *self.input_connection.lock().unwrap() = Some(conn);
+                self.port_selection.lock().unwrap().input = Some(name.clone());
                 info!("Connected MIDI input port: {}", name);
                 found_input = true;
                 break;
@@ -128,12 +287,21 @@ impl MidiManager {
         // This allows us to send LED updates to the controller
         let midi_out = MidiOutput::new("Snap-Blaster Output")?;
 
+        let preferred_output = self.port_selection.lock().unwrap().output.clone();
+        let output_names: Vec<String> = midi_out
+            .ports()
+            .iter()
+            .filter_map(|port| midi_out.port_name(port).ok())
+            .collect();
+        let output_name = choose_port(&output_names, preferred_output.as_deref(), controller_name);
+
         let mut found_output = false;
         for port in midi_out.ports() {
             let name = midi_out.port_name(&port)?;
-            if name.contains(controller_name) {
+            if Some(&name) == output_name.as_ref() {
                 let conn = midi_out.connect(&port, "snapblaster-out")?;
                 self.output_connections.lock().unwrap().push((name.clone(), conn));
+                self.port_selection.lock().unwrap().output = Some(name.clone());
                 info!("Connected MIDI output port: {}", name);
                 found_output = true;
                 break;
@@ -144,6 +312,7 @@ impl MidiManager {
             warn!("Could not find MIDI output port for {}", controller_name);
         }
 
+        let _ = self.event_bus.publish(Event::MidiPortsChanged);
         Ok(())
     }
 
@@ -185,28 +354,7 @@ impl MidiManager {
 
                 // 4) Subscribe to PadPressed events and route to handle_pad_pressed
                 // This handles user interaction with the controller
-                let mut subscriber = self.event_bus.subscribe();
-                let manager = self.clone();
-                tokio::spawn(async move {
-                    info!("Started event handler for PadPressed events");
-                    while let Ok(event) = subscriber.recv().await {
-                        match event {
-                            Event::PadPressed { pad, velocity } => {
-                                debug!("Received PadPressed event: pad={}, velocity={}", pad, velocity);
-                                if let Err(err) = manager.handle_pad_pressed(pad, velocity).await {
-                                    error!("Error handling pad press: {}", err);
-                                }
-                            }
-                            Event::PadReleased { pad, velocity } => {
-                                debug!("Received PadReleased event: pad={}, velocity={}", pad, velocity);
-                                if let Err(err) = manager.handle_pad_released(pad, velocity).await {
-                                    error!("Error handling pad release: {}", err);
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                });
+                self.start_pad_handler();
 
                 Ok(())
             }
@@ -217,6 +365,14 @@ impl MidiManager {
         }
     }
 
+    /// Route pad events from the bus to this manager
+    pub fn start_pad_handler(&self) -> JoinHandle<()> {
+        let manager = self.clone();
+        supervise_realtime(&self.event_bus, "pad-handler", move || PadHandler {
+            manager: manager.clone(),
+        })
+    }
+
     /// Send a CC message to grid controller and all outputs
     pub fn send_cc(&self, channel: u8, cc: u8, value: u8) -> Result<(), Box<dyn Error>> {
         // Only send to virtual MIDI port, not hardware controllers
@@ -234,7 +390,9 @@ impl MidiManager {
         Ok(())
     }
 
-    /// Send a batch of parameter CCs for a snap
+    /// Send a batch of parameter CCs for a snap; for an unquantized recall from a
+    /// pad, the first CC sent is timed against the pad press. Quantized recalls go
+    /// through the launcher and are not timed, as they wait for the beat grid.
     pub fn send_snap_values(&self, params: &[(u8, u8)]) -> Result<(), Box<dyn Error>> {
         info!("Sending {} CC values for snap", params.len());
 
@@ -250,6 +408,9 @@ impl MidiManager {
                         warn!("Failed to send CC {} value {} to {}: {}", cc, val, name, e);
                     } else {
                         debug!("Sent CC ch=0 cc={} val={} to {}", cc, val, name);
+                        if self.from_pad {
+                            self.event_bus.record_pad_to_cc();
+                        }
                     }
                 }
             }
@@ -291,7 +452,7 @@ impl MidiManager {
                         Rgb::new(64, 0, 0)
                     };
 
-                    ctrl.set_led(i as u8, color);
+                    ctrl.set_led(i, color);
                 }
 
                 // Snap pads (8-63)
@@ -311,12 +472,25 @@ impl MidiManager {
                             false
                         };
 
-                        let color = if is_current {
+                        let starts_chain = st.project.chains.iter().any(|chain| chain.pad == Some(idx));
+
+                        // Waiting for its launch point on the beat grid
+                        let is_pending = st.pending_launch.is_some_and(|pending| {
+                            pending.bank_id == st.current_bank && pending.snap_id == idx
+                        });
+
+                        let color = if is_pending {
+                            // Pending launch: ORANGE until the grid point arrives
+                            Rgb::orange()
+                        } else if is_current {
                             // Current snap: GREEN (selected)
                             Rgb::green()
                         } else if is_morph_target {
                             // Morph target: PURPLE (or another distinctive color)
                             Rgb::purple()
+                        } else if starts_chain {
+                            // Starts a morph chain: CYAN
+                            Rgb::cyan()
                         } else if has_snap {
                             // Available snap: YELLOW
                             Rgb::yellow()
@@ -381,9 +555,16 @@ impl MidiManager {
 
                 // Store the active modifier and duration
                 state_guard.active_modifier = Some(pad);
+                let changed = state_guard.morph_duration != duration_bars;
                 state_guard.morph_duration = duration_bars;
                 drop(state_guard); // Release the lock before updating LEDs
 
+                if changed {
+                    let _ = self.event_bus.publish(Event::MorphDurationChanged {
+                        bars: duration_bars,
+                    });
+                }
+
                 // Color the modifier pad green to indicate it's active
                 if let Some(ref mut ctrl) = *self.controller.lock().unwrap() {
                     ctrl.set_led(pad, Rgb::green());
@@ -394,7 +575,7 @@ impl MidiManager {
             }
 
             // Check bank selection (pads 5-7)
-            if pad >= 5 && pad < 8 && velocity > 0 {
+            if (5..8).contains(&pad) && velocity > 0 {
                 let mut state_guard = state.write().unwrap();
 
                 if pad < state_guard.project.banks.len() as u8 {
@@ -423,28 +604,34 @@ impl MidiManager {
                 guard.active_modifier
             };
 
-            // Check if an active morph is in progress (before handling any new pad press)
-            let morph_in_progress = {
+            // Launch quantization of the current bank
+            let launch = {
                 let guard = state.read().unwrap();
-                guard.active_morph.is_some()
+                guard.project.launch_quantization_for(guard.current_bank)
             };
 
-            // If a morph is in progress and this is a snap selection (not a modifier),
-            // cancel the morph by sending a MorphCompleted event
-            if morph_in_progress && pad >= 8 && velocity > 0 {
-                info!("Canceling active morph because a new snap was selected");
-
-                // Clear the active morph state
-                {
-                    let mut state_guard = state.write().unwrap();
-                    state_guard.active_morph = None;
-                }
-
-                // Send event to notify morph engine and other components
-                let _ = self.event_bus.publish(Event::MorphCompleted);
+            // A chain pad starts its chain whether or not a modifier is held
+            let chain_id = {
+                let guard = state.read().unwrap();
+                guard.project.chains.iter().find(|chain| chain.pad == Some(snap_id)).map(|chain| chain.id.clone())
+            };
+            if let Some(chain_id) = chain_id {
+                info!("Starting morph chain {} from pad {}", chain_id, pad);
+                let _ = self.event_bus.publish(Event::ChainInitiated {
+                    chain_id,
+                    quantize: launch.morph,
+                });
+                return Ok(());
             }
 
-            if let Some(modifier_pad) = active_modifier {
+            // A press during a morph goes to the morph engine, which applies the
+            // project's interrupt policy
+            let morph_in_progress = {
+                let guard = state.read().unwrap();
+                guard.active_morph.is_some()
+            };
+
+            if active_modifier.is_some() {
                 // This is a morph target selection
                 info!("Morph target selected: pad={}, snap_id={}", pad, snap_id);
 
@@ -472,19 +659,19 @@ impl MidiManager {
                     from_snap = guard.current_snap;
                     duration_bars = guard.morph_duration;
 
-                    // Don't morph to the same snap
-                    if from_snap == snap_id {
+                    // Don't morph to the same snap, unless heading back to it mid-morph
+                    if from_snap == snap_id && !morph_in_progress {
                         return Ok(());
                     }
                 }
 
-                // Start the morph (use Link-quantized morphing)
+                // Start the morph on the bank's launch grid
                 let _ = self.event_bus.publish(Event::MorphInitiated {
                     from_snap,
                     to_snap: snap_id,
-                    duration_bars,
+                    duration: MorphDuration::Bars(duration_bars as f64),
                     curve_type: MorphCurve::Linear,
-                    quantize: true, // Always quantize to next bar
+                    quantize: launch.morph,
                 });
 
                 info!("Started morph from snap {} to {} over {} bars", 
@@ -492,10 +679,8 @@ impl MidiManager {
 
                 return Ok(());
             } else {
-                // Regular snap selection (no modifier active)
-                let cc_values: Vec<(u8, u8)>;
-
-                // First check if this is a valid snap
+                // Regular snap selection (no modifier active), recalled the way the UI
+                // and remote surfaces recall snaps, on the bank's recall grid
                 {
                     let guard = state.read().unwrap();
                     bank_id = guard.current_bank;
@@ -508,42 +693,15 @@ impl MidiManager {
                     if snap_id >= bank.snaps.len() || bank.snaps[snap_id].name.is_empty() {
                         return Ok(());  // Invalid snap
                     }
-
-                    // Collect the parameter values we'll need to send
-                    cc_values = guard.project.parameters.iter().enumerate()
-                        .filter_map(|(idx, param)| {
-                            if idx < bank.snaps[snap_id].values.len() {
-                                let value = bank.snaps[snap_id].values[idx];
-                                Some((param.cc, value))
-                            } else {
-                                None
-                            }
-                        })
-                        .collect();
-                }
-
-                // Update the current state (snap selection)
-                {
-                    let mut state_guard = state.write().unwrap();
-                    state_guard.current_snap = snap_id;
-                }
-
-                // Add more logging to debug
-                info!("Ready to send CC values for snap {}", snap_id);
-
-                // Send the selected snap's values via MIDI CCs - to the VIRTUAL port, not the hardware
-                if !cc_values.is_empty() {
-                    info!("Sending {} CC values for snap {}", cc_values.len(), snap_id);
-
-                    // Make sure this is correctly sending to your virtual MIDI port
-                    self.send_snap_values(&cc_values)?;
                 }
 
-                // Publish the event
-                let _ = self.event_bus.publish(Event::SnapSelected {
-                    bank: bank_id,
-                    snap_id
-                });
+                // Pad presses are journaled as input, so the recall is not recorded as a command
+                let pad_manager = MidiManager {
+                    from_pad: true,
+                    ..self.clone()
+                };
+                let control = ControlService::new(state.clone(), self.event_bus.clone(), Some(Arc::new(pad_manager)), None);
+                control.internal().launch_snap(bank_id, snap_id, None)?;
 
                 // Update the controller LEDs
                 self.update_controller_leds()?;
@@ -579,4 +737,50 @@ impl MidiManager {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Routes pad events from the bus to the manager
+struct PadHandler {
+    manager: MidiManager,
+}
+
+impl EventHandler for PadHandler {
+    async fn handle(&mut self, event: Event) -> Flow {
+        match event {
+            Event::PadPressed { pad, velocity } => {
+                debug!("Received PadPressed event: pad={}, velocity={}", pad, velocity);
+                if let Err(err) = self.manager.handle_pad_pressed(pad, velocity).await {
+                    error!("Error handling pad press: {}", err);
+                }
+            }
+            Event::PadReleased { pad, velocity } => {
+                debug!("Received PadReleased event: pad={}, velocity={}", pad, velocity);
+                if let Err(err) = self.manager.handle_pad_released(pad, velocity).await {
+                    error!("Error handling pad release: {}", err);
+                }
+            }
+            _ => {}
+        }
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, _missed: u64) {
+        // A missed release would leave a modifier stuck on
+        if let Some(ref state) = self.manager.state {
+            state.write().unwrap().active_modifier = None;
+        }
+
+        if let Err(e) = self.manager.update_controller_leds() {
+            warn!("Failed to update controller LEDs after resync: {}", e);
+        }
+    }
+}
+
+/// Pick a port: the remembered one if it is still present, otherwise the first
+/// one whose name contains the controller name
+fn choose_port(names: &[String], preferred: Option<&str>, controller_name: &str) -> Option<String> {
+    preferred
+        .and_then(|preferred| names.iter().find(|name| name.as_str() == preferred))
+        .or_else(|| names.iter().find(|name| name.contains(controller_name)))
+        .cloned()
+}
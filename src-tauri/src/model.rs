@@ -1,4 +1,7 @@
+use crate::curves::UserCurve;
+use crate::events::{LaunchQuantize, MorphCurve, MorphDuration, MorphLoop};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
 /// A Parameter represents a single MIDI CC control
@@ -12,6 +15,257 @@ pub struct Parameter {
 
     /// MIDI CC number (0-127)
     pub cc: u8,
+
+    /// When this parameter moves during morphs, unless the target snap says otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub morph_timing: Option<MorphTiming>,
+
+    /// Whether this parameter glides, jumps or steps during morphs
+    #[serde(default, skip_serializing_if = "ParameterMotion::is_continuous")]
+    pub motion: ParameterMotion,
+}
+
+/// How a parameter's value gets from the source to the target during a morph
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum ParameterMotion {
+    /// Glide through every value in between
+    #[default]
+    Continuous,
+
+    /// Never pass through values in between; for switches and selectors such as
+    /// waveform, filter type or effect on/off
+    Discrete {
+        /// Point of the parameter's window at which it jumps to the target
+        #[serde(default)]
+        at: JumpPoint,
+    },
+
+    /// Move only between this many fixed positions, spread evenly across 0-127,
+    /// on the way from the source to the target value
+    Stepped { steps: u8 },
+}
+
+impl ParameterMotion {
+    pub fn is_continuous(&self) -> bool {
+        *self == ParameterMotion::Continuous
+    }
+
+    /// Check the motion is usable
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            ParameterMotion::Stepped { steps } if !(2..=128).contains(&steps) => {
+                Err("Steps must be between 2 and 128".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Where in its window a discrete parameter jumps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JumpPoint {
+    /// As soon as the window begins
+    Start,
+    /// Halfway through the window
+    #[default]
+    Middle,
+    /// When the window ends
+    End,
+}
+
+impl JumpPoint {
+    /// Whether the jump has happened at a point of the window (0-1)
+    pub fn reached(&self, local_progress: f64) -> bool {
+        match self {
+            JumpPoint::Start => local_progress > 0.0,
+            JumpPoint::Middle => local_progress >= 0.5,
+            JumpPoint::End => local_progress >= 1.0,
+        }
+    }
+}
+
+/// The part of a morph during which a parameter moves, and how
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MorphTiming {
+    /// Fraction of the morph at which the parameter starts moving (0-1)
+    #[serde(default)]
+    pub start: f64,
+
+    /// Fraction of the morph at which the parameter arrives (0-1)
+    #[serde(default = "default_window_end")]
+    pub end: f64,
+
+    /// Curve ID used instead of the morph's curve
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<String>,
+}
+
+fn default_window_end() -> f64 {
+    1.0
+}
+
+impl Default for MorphTiming {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            end: default_window_end(),
+            curve: None,
+        }
+    }
+}
+
+impl MorphTiming {
+    /// Check that the window lies within the morph
+    pub fn validate(&self) -> Result<(), String> {
+        let unit = |v: f64| v.is_finite() && (0.0..=1.0).contains(&v);
+        if !unit(self.start) || !unit(self.end) {
+            return Err("Morph window must lie between 0 and 1".to_string());
+        }
+        if self.end < self.start {
+            return Err("Morph window must end after it starts".to_string());
+        }
+        Ok(())
+    }
+
+    /// Progress within the window for a point of the whole morph, from 0 to 1
+    pub fn local_progress(&self, progress: f64) -> f64 {
+        if self.end <= self.start {
+            // A zero-length window jumps at its start
+            return if progress >= self.start { 1.0 } else { 0.0 };
+        }
+        ((progress - self.start) / (self.end - self.start)).clamp(0.0, 1.0)
+    }
+}
+
+/// What happens when a morph or recall is requested while a morph is running
+/// A manual morph never arrives on its own, so it is always retargeted instead of waited for or kept
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptPolicy {
+    /// Head for the new target from the values reached so far
+    #[default]
+    Retarget,
+
+    /// Let the running morph arrive, then start the new one; a later request replaces a waiting one
+    FinishThenGo,
+
+    /// Stop the running morph and recall the new target at once
+    Jump,
+
+    /// Keep the running morph and drop the request
+    Ignore,
+}
+
+/// Where the position of a manual morph comes from, besides the UI
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ManualControl {
+    /// Incoming CC that sets the position, such as a fader on another controller
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cc: Option<u8>,
+
+    /// MIDI channel of that CC (0-15); any channel when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+
+    /// Whether pressure on the target snap's pad sets the position
+    #[serde(default)]
+    pub pressure: bool,
+}
+
+impl ManualControl {
+    /// Check the CC binding is valid MIDI
+    pub fn validate(&self) -> Result<(), String> {
+        if matches!(self.cc, Some(cc) if cc > 127) {
+            return Err("CC number must be 0-127".to_string());
+        }
+        if matches!(self.channel, Some(channel) if channel > 15) {
+            return Err("MIDI channel must be 0-15".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Most steps in one morph chain
+const MAX_CHAIN_STEPS: usize = 64;
+
+/// Snap pads on the controller, and so snaps a chain can reach
+pub const SNAP_PADS: usize = 56;
+
+/// One leg of a morph chain
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChainStep {
+    /// Snap of the current bank to morph to
+    pub to_snap: usize,
+
+    pub duration: MorphDuration,
+
+    /// Curve ID; linear when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curve: Option<String>,
+
+    /// How long to stay on the snap before the next step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hold: Option<MorphDuration>,
+}
+
+/// A journey through several snaps, run as one sequence of morphs
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MorphChain {
+    pub id: String,
+
+    /// Name shown in the UI
+    pub name: String,
+
+    pub steps: Vec<ChainStep>,
+
+    /// Snap pad that starts the chain instead of recalling its snap
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pad: Option<usize>,
+}
+
+impl MorphChain {
+    /// Check the ID, steps and pad; curves are checked against the project separately
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Chain ID '{}' must be lowercase letters, digits, '-' or '_'",
+                self.id
+            ));
+        }
+        if self.steps.is_empty() || self.steps.len() > MAX_CHAIN_STEPS {
+            return Err(format!("A chain needs between 1 and {} steps", MAX_CHAIN_STEPS));
+        }
+        for step in &self.steps {
+            if step.to_snap >= SNAP_PADS {
+                return Err(format!("Snap {} is out of range", step.to_snap));
+            }
+            step.duration.validate()?;
+            if let Some(hold) = &step.hold {
+                hold.validate()?;
+            }
+        }
+        if matches!(self.pad, Some(pad) if pad >= SNAP_PADS) {
+            return Err("Chain pad is out of range".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A morph timing for one parameter, stored on a snap
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParameterTiming {
+    /// Index of the parameter
+    pub param_id: usize,
+
+    #[serde(flatten)]
+    pub timing: MorphTiming,
 }
 
 /// A Snap represents a complete state of all parameters
@@ -25,6 +279,22 @@ pub struct Snap {
 
     /// CC values for each parameter (index corresponds to parameter index)
     pub values: Vec<u8>,
+
+    /// Morph timings used when morphing to this snap, overriding the parameters' own
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub morph_timings: Vec<ParameterTiming>,
+}
+
+impl Snap {
+    /// The timing a parameter follows when morphing to this snap
+    pub fn timing_for(&self, param_id: usize, parameter: Option<&Parameter>) -> MorphTiming {
+        self.morph_timings
+            .iter()
+            .find(|timing| timing.param_id == param_id)
+            .map(|timing| timing.timing.clone())
+            .or_else(|| parameter.and_then(|param| param.morph_timing.clone()))
+            .unwrap_or_default()
+    }
 }
 
 /// A Bank contains multiple snaps
@@ -35,6 +305,35 @@ pub struct Bank {
 
     /// Collection of snaps in this bank
     pub snaps: Vec<Snap>,
+
+    /// Launch quantization for this bank, instead of the project's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_quantization: Option<LaunchQuantization>,
+}
+
+/// When morphs and snap recalls launch on the Link beat grid
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LaunchQuantization {
+    /// Morphs, loops and chains; the next bar unless set
+    #[serde(default = "default_morph_launch")]
+    pub morph: LaunchQuantize,
+
+    /// Plain snap recalls; at once unless set
+    #[serde(default)]
+    pub recall: LaunchQuantize,
+}
+
+fn default_morph_launch() -> LaunchQuantize {
+    LaunchQuantize::Bar
+}
+
+impl Default for LaunchQuantization {
+    fn default() -> Self {
+        Self {
+            morph: default_morph_launch(),
+            recall: LaunchQuantize::Immediate,
+        }
+    }
 }
 
 /// Project is the main data container for all snap-blaster settings
@@ -54,6 +353,30 @@ pub struct Project {
 
     /// All parameters configured for this project
     pub parameters: Vec<Parameter>,
+
+    /// Morph curves defined for this project
+    #[serde(default)]
+    pub curves: Vec<UserCurve>,
+
+    /// Crossfade when recalling a snap of the current bank, in milliseconds; 0 recalls instantly
+    #[serde(default)]
+    pub recall_crossfade_ms: u32,
+
+    /// How a running morph reacts to a new morph or recall
+    #[serde(default)]
+    pub interrupt_policy: InterruptPolicy,
+
+    /// When morphs and recalls launch; banks can override it
+    #[serde(default)]
+    pub launch_quantization: LaunchQuantization,
+
+    /// Morph chains defined for this project
+    #[serde(default)]
+    pub chains: Vec<MorphChain>,
+
+    /// Inputs that drive manual morphs
+    #[serde(default)]
+    pub manual_control: ManualControl,
 }
 
 /// Default implementation creates an empty project
@@ -68,19 +391,49 @@ impl Default for Project {
                 snaps: vec![Snap {
                     name: "Initial Snap".to_string(),
                     description: "A starting point".to_string(),
-                    values: vec![64; 64], // Default all values to 64 (middle)
+                    values: Vec::new(), // One value per parameter, and there are none yet
+                    morph_timings: Vec::new(),
                 }],
+                launch_quantization: None,
             }],
             parameters: Vec::new(),
+            curves: Vec::new(),
+            recall_crossfade_ms: 0,
+            interrupt_policy: InterruptPolicy::default(),
+            chains: Vec::new(),
+            manual_control: ManualControl::default(),
+            launch_quantization: LaunchQuantization::default(),
         }
     }
 }
 
+impl Project {
+    /// Launch quantization in a bank: its own, or the project's
+    pub fn launch_quantization_for(&self, bank_id: usize) -> LaunchQuantization {
+        self.banks
+            .get(bank_id)
+            .and_then(|bank| bank.launch_quantization)
+            .unwrap_or(self.launch_quantization)
+    }
+}
+
+/// Morph durations the modifier pads select, in bars
+pub const MORPH_DURATION_BARS: [u8; 5] = [1, 2, 4, 8, 16];
+
+/// Link tempos accepted from the front ends and a saved session, in BPM
+pub const TEMPO_RANGE: std::ops::RangeInclusive<f64> = 20.0..=999.0;
+
+/// Link quanta accepted from the front ends and a saved session, in beats per bar
+pub const QUANTUM_RANGE: std::ops::RangeInclusive<f64> = 1.0..=16.0;
+
 /// ProjectState holds the current state of the project and runtime information
 pub struct ProjectState {
     /// The project data
     pub project: Project,
 
+    /// File or directory the project was last loaded from or saved to
+    pub project_path: Option<PathBuf>,
+
     /// Currently selected bank
     pub current_bank: usize,
 
@@ -95,17 +448,32 @@ pub struct ProjectState {
 
     /// Current morph duration in bars (1, 2, 4, 8, or 16)
     pub morph_duration: u8,
+
+    /// Snap waiting for its launch point on the beat grid, if any
+    pub pending_launch: Option<PendingLaunch>,
+}
+
+/// A recall or morph that will launch on the next grid point
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct PendingLaunch {
+    pub bank_id: usize,
+    pub snap_id: usize,
+
+    /// Whether a morph is waiting, rather than a recall
+    pub morph: bool,
 }
 
 impl Default for ProjectState {
     fn default() -> Self {
         Self {
             project: Project::default(),
+            project_path: None,
             current_bank: 0,
             current_snap: 0,
             active_morph: None,
             active_modifier: None,
             morph_duration: 4, // Default to 4 bars
+            pending_launch: None,
         }
     }
 }
@@ -118,8 +486,20 @@ pub struct ActiveMorph {
     /// Target snap index
     pub to_snap: usize,
 
-    /// Total duration in bars
-    pub duration_bars: u8,
+    /// Total duration
+    pub duration: MorphDuration,
+
+    /// Step of the morph chain this morph belongs to
+    pub step: Option<usize>,
+
+    /// How the morph loops, if it does
+    pub looping: Option<MorphLoop>,
+
+    /// Whether the position is set by hand rather than by time
+    pub manual: bool,
+
+    /// Curve the morph follows
+    pub curve_type: MorphCurve,
 
     /// Current progress (0.0 - 1.0)
     pub progress: f64,
@@ -141,3 +521,51 @@ pub type SharedState = Arc<RwLock<ProjectState>>;
 pub fn new_shared_state() -> SharedState {
     Arc::new(RwLock::new(ProjectState::default()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: f64, end: f64) -> MorphTiming {
+        MorphTiming {
+            start,
+            end,
+            curve: None,
+        }
+    }
+
+    #[test]
+    fn local_progress_runs_across_the_window() {
+        let timing = window(0.25, 0.75);
+
+        assert_eq!(timing.local_progress(0.0), 0.0);
+        assert_eq!(timing.local_progress(0.25), 0.0);
+        assert_eq!(timing.local_progress(0.5), 0.5);
+        assert_eq!(timing.local_progress(0.75), 1.0);
+        assert_eq!(timing.local_progress(1.0), 1.0);
+    }
+
+    #[test]
+    fn local_progress_of_a_zero_length_window_jumps_at_its_start() {
+        let timing = window(0.5, 0.5);
+
+        assert_eq!(timing.local_progress(0.49), 0.0);
+        assert_eq!(timing.local_progress(0.5), 1.0);
+    }
+
+    #[test]
+    fn morph_timing_validate_rejects_bad_windows() {
+        assert!(window(0.0, 1.0).validate().is_ok());
+        assert!(window(0.8, 0.2).validate().is_err());
+        assert!(window(-0.1, 0.5).validate().is_err());
+        assert!(window(0.0, f64::NAN).validate().is_err());
+    }
+
+    #[test]
+    fn stepped_motion_needs_two_to_128_positions() {
+        assert!(ParameterMotion::Stepped { steps: 2 }.validate().is_ok());
+        assert!(ParameterMotion::Stepped { steps: 128 }.validate().is_ok());
+        assert!(ParameterMotion::Stepped { steps: 1 }.validate().is_err());
+        assert!(ParameterMotion::Stepped { steps: 129 }.validate().is_err());
+    }
+}
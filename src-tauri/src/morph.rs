@@ -1,411 +1,1014 @@
 // src-tauri/src/morph.rs
-use crate::events::{Event, EventBus, MorphCurve};
-use crate::model::{ActiveMorph, Parameter, SharedState};
-use std::f64::consts::PI;
-use std::sync::Arc;
+use crate::curves::{curve_by_id, ResolvedCurve};
+use crate::events::{Event, EventBus, LaunchQuantize, LoopMode, MorphCurve, MorphDuration, MorphLoop};
+use crate::link::LinkSynchronizer;
+use crate::model::{
+    ActiveMorph, InterruptPolicy, MorphChain, MorphTiming, Parameter, ParameterMotion, PendingLaunch, SharedState,
+    QUANTUM_RANGE,
+};
+use crate::supervisor::{supervise, EventHandler, Flow};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::sync::{broadcast, Mutex as TokioMutex};
 use tokio::task::JoinHandle;
 use tokio::time::{self, Instant};
 use tracing::{debug, error, info, warn};
 
+/// Tempo used when Link is unavailable
+const DEFAULT_TEMPO: f64 = 120.0;
+
+/// How often a running morph sends values; 30 fps is smooth enough
+const UPDATE_INTERVAL: Duration = Duration::from_millis(33);
+
+/// How close manual input must come to the morph position to take it over
+const PICKUP_TOLERANCE: f64 = 0.02;
+
 /// MorphEngine handles interpolation between snaps
 pub struct MorphEngine {
     state: SharedState,
     event_bus: EventBus,
-    event_receiver: broadcast::Receiver<Event>,
+    link: Option<LinkSynchronizer>,
+
+    /// The running morph or chain, if any
+    morph_task: Option<JoinHandle<()>>,
+
+    /// Request waiting for the running morph to arrive, under the finish-then-go policy
+    pending: Arc<Mutex<Option<Queued>>>,
+
+    /// The morph under manual control, if any
+    manual: Option<ManualMorph>,
+
+    /// Last manual position received, to tell when the input crosses the morph
+    last_input: Option<f64>,
 }
 
-impl MorphEngine {
-    /// Create a new morph engine
-    pub fn new(state: SharedState, event_bus: EventBus) -> Self {
-        let event_receiver = event_bus.subscribe();
+/// A morph whose position comes from a fader, pad pressure or the UI
+struct ManualMorph {
+    setup: MorphSetup,
+    last_sent_values: Vec<Option<u8>>,
+
+    /// Whether the input has caught up with the morph; until then it is ignored
+    picked_up: bool,
+}
+
+/// A morph as requested on the bus
+#[derive(Clone, Debug)]
+struct MorphRequest {
+    from_snap: usize,
+    to_snap: usize,
+    duration: MorphDuration,
+    curve_type: MorphCurve,
+    quantize: LaunchQuantize,
+
+    /// Step of the chain this morph belongs to
+    step: Option<usize>,
+}
+
+/// A request waiting for the running morph to arrive
+#[derive(Clone, Debug)]
+enum Queued {
+    Morph(MorphRequest),
+    Chain { chain_id: String, quantize: LaunchQuantize },
+    Loop(MorphRequest, MorphLoop),
+    Manual(MorphRequest),
+}
+
+/// How an admitted request starts
+struct Start {
+    /// Values reached by the morph it interrupts, to continue from
+    values: Option<Vec<u8>>,
+
+    /// Whether to reach the first target at once
+    instant: bool,
+}
+
+/// What a morph task needs from the engine
+#[derive(Clone)]
+struct MorphContext {
+    state: SharedState,
+    event_bus: EventBus,
+    link: Option<LinkSynchronizer>,
+    pending: Arc<Mutex<Option<Queued>>>,
+}
 
+impl MorphContext {
+    fn morph_active(&self) -> bool {
+        self.state.read().unwrap().active_morph.is_some()
+    }
+}
+
+impl MorphEngine {
+    /// Create a new morph engine, optionally synced to Link for tempo and quantization
+    pub fn new(state: SharedState, event_bus: EventBus, link: Option<LinkSynchronizer>) -> Self {
         Self {
             state,
             event_bus,
-            event_receiver,
+            link,
+            morph_task: None,
+            pending: Arc::new(Mutex::new(None)),
+            manual: None,
+            last_input: None,
         }
     }
 
-    /// Start the morph engine
-    pub fn start(mut self) -> JoinHandle<()> {
+    /// Start the morph engine as a supervised event handler
+    pub fn start(self) -> JoinHandle<()> {
         info!("Starting morph engine");
 
-        tokio::spawn(async move {
-            // Track if we are currently morphing
-            let mut morph_task: Option<JoinHandle<()>> = None;
-
-            // Event loop
-            while let Ok(event) = self.event_receiver.recv().await {
-                match event {
-                    Event::PadPressed { pad, velocity } => {
-                        // Handle note-off events for modifiers (pads 0-4)
-                        if pad < 5 && velocity == 0 {
-                            // Note-off for a modifier pad
-                            let mut state_guard = self.state.write().unwrap();
-                            if state_guard.active_modifier == Some(pad) {
-                                // Clear the active modifier
-                                state_guard.active_modifier = None;
-
-                                // Let the MIDI manager know to update LEDs
-                                drop(state_guard);
-                                // Use try_publish to avoid error handling
-                                let _ = self.event_bus.try_publish(Event::RequestUpdateLEDs);
-                            }
-                        }
-                    },
-                    Event::MorphInitiated {
-                        from_snap,
-                        to_snap,
-                        duration_bars,
-                        curve_type,
-                        quantize,
-                    } => {
-                        info!("Starting morph: {} -> {}, duration: {}bars, quantize: {}", 
-                              from_snap, to_snap, duration_bars, quantize);
-
-                        // Cancel any existing morph task
-                        if let Some(task) = morph_task.take() {
-                            task.abort();
-                            info!("Cancelled previous morph task");
-                        }
+        let event_bus = self.event_bus.clone();
+        supervise(&event_bus, "morph-engine", move || {
+            MorphEngine::new(self.state.clone(), self.event_bus.clone(), self.link.clone())
+        })
+    }
 
-                        // Check if we have valid snap indices
-                        let state_guard = self.state.read().unwrap();
-                        let current_bank = &state_guard.project.banks[state_guard.current_bank];
-                        if from_snap >= current_bank.snaps.len() || to_snap >= current_bank.snaps.len() {
-                            error!("Invalid snap indices for morph: {} -> {}", from_snap, to_snap);
-                            continue;
-                        }
+    /// Abort the running morph task, if any, along with the launch it waited for
+    fn cancel_task(&mut self) -> bool {
+        match self.morph_task.take() {
+            Some(task) => {
+                task.abort();
+                let mut state_guard = self.state.write().unwrap();
+                if state_guard.pending_launch.is_some_and(|pending| pending.morph) {
+                    state_guard.pending_launch = None;
+                }
+                true
+            }
+            None => false,
+        }
+    }
 
-                        // Clone necessary data for the morph task
-                        let state = self.state.clone();
-                        let event_bus = self.event_bus.clone();
-                        let bank_id = state_guard.current_bank;
-
-                        // Start a new morph task - pass the quantize flag directly
-                        morph_task = Some(tokio::spawn(async move {
-                            Self::run_morph(
-                                state, event_bus, bank_id,
-                                from_snap, to_snap, duration_bars, curve_type, quantize
-                            ).await;
-                        }));
-                    },
-
-                    // Handle MorphCompleted event for direct cancellation 
-                    Event::MorphCompleted => {
-                        info!("Received morph completed/cancelled event");
-
-                        // Cancel any active morph task
-                        if let Some(task) = morph_task.take() {
-                            task.abort();
-                            info!("Cancelled morph task due to explicit completion event");
-                        }
+    /// Apply the project's interrupt policy to a request. Returns how to start it,
+    /// or None when it is dropped or waits for the running morph.
+    fn admit(&mut self, request: Queued) -> Option<Start> {
+        let (mut policy, running_values, manual) = {
+            let state_guard = self.state.read().unwrap();
+            let morph = state_guard.active_morph.as_ref();
+            (
+                state_guard.project.interrupt_policy,
+                morph.map(|morph| morph.current_values.clone()),
+                morph.is_some_and(|morph| morph.manual),
+            )
+        };
 
-                        // Ensure the morph state is cleared
-                        let mut state_guard = self.state.write().unwrap();
-                        if state_guard.active_morph.is_some() {
-                            state_guard.active_morph = None;
-                            info!("Cleared active morph state");
+        // A manual morph only arrives when its input says so, so a request
+        // that waits for it or is ignored might never run; take over instead
+        if manual && matches!(policy, InterruptPolicy::Ignore | InterruptPolicy::FinishThenGo) {
+            policy = InterruptPolicy::Retarget;
+        }
 
-                            // Request LED update to reflect the change
-                            drop(state_guard);
-                            let _ = self.event_bus.try_publish(Event::RequestUpdateLEDs);
-                        }
-                    },
+        let start = match (running_values, policy) {
+            (None, _) => Start { values: None, instant: false },
+            (Some(_), InterruptPolicy::Ignore) => {
+                info!("Ignoring {:?} while another morph is running", request);
+                return None;
+            }
+            (Some(_), InterruptPolicy::FinishThenGo) => {
+                info!("{:?} will start when the running morph arrives", request);
+                *self.pending.lock().unwrap() = Some(request);
+                return None;
+            }
+            (Some(_), InterruptPolicy::Jump) => Start { values: None, instant: true },
+            (Some(values), InterruptPolicy::Retarget) => Start { values: Some(values), instant: false },
+        };
 
-                    // Handle RequestUpdateLEDs event
-                    Event::RequestUpdateLEDs => {
-                        // Forward to the MIDI manager to update LEDs
-                        self.event_bus.try_publish(Event::RequestMIDIUpdate);
-                        debug!("Requested MIDI LED update");
-                    },
+        // Cancel any existing morph task; this request supersedes a waiting one too
+        if self.cancel_task() {
+            info!("Cancelled previous morph task");
+        }
+        self.pending.lock().unwrap().take();
+        self.release_manual();
+        Some(start)
+    }
 
-                    Event::Shutdown => {
-                        info!("Shutting down morph engine");
+    /// Forget the manual morph and any input that was following it
+    fn release_manual(&mut self) {
+        self.manual = None;
+        self.last_input = None;
+    }
 
-                        // Cancel any active morph
-                        if let Some(task) = morph_task.take() {
-                            task.abort();
-                        }
+    /// Move the active morph to a position set by hand. Input is soft-takeover:
+    /// it is ignored until it reaches the morph's current position, so handing
+    /// a running morph to a fader or pad never makes the values jump.
+    async fn manual_position(&mut self, position: f64) {
+        let position = position.clamp(0.0, 1.0);
+        let Some((progress, from_snap, to_snap, from_values, curve_type)) = ({
+            let state_guard = self.state.read().unwrap();
+            state_guard.active_morph.as_ref().map(|morph| {
+                (morph.progress, morph.from_snap, morph.to_snap, morph.from_values.clone(), morph.curve_type.clone())
+            })
+        }) else {
+            self.release_manual();
+            return;
+        };
 
-                        break;
-                    },
-                    _ => {
-                        // Ignore other events
-                    }
-                }
+        let previous = self.last_input.replace(position);
+        let needs_pickup = self.manual.as_ref().is_none_or(|manual| !manual.picked_up);
+        if needs_pickup {
+            let offset = position - progress;
+            let crossed = previous.is_some_and(|previous| (previous - progress).signum() != offset.signum());
+            if offset.abs() > PICKUP_TOLERANCE && !crossed {
+                return;
             }
+        }
 
-            info!("Morph engine shutdown complete");
-        })
-    }
-
-    /// Run a morph from one snap to another
-    async fn run_morph(
-        state: SharedState,
-        event_bus: EventBus,
-        bank_id: usize,
-        from_snap: usize,
-        to_snap: usize,
-        duration_bars: u8,
-        curve_type: MorphCurve,
-        quantize: bool,
-    ) {
-        // Get the values for both snaps and parameters
-        let (from_values, to_values, parameters, param_count) = {
-            let state_guard = state.read().unwrap();
-            let bank = &state_guard.project.banks[bank_id];
-
-            // Safely get the snap values
-            let from = if from_snap < bank.snaps.len() {
-                &bank.snaps[from_snap]
-            } else {
-                error!("Invalid from_snap index: {}", from_snap);
+        // Take a timed morph over, keeping where it came from and where it goes
+        if self.manual.is_none() {
+            let bank_id = self.state.read().unwrap().current_bank;
+            let Some(mut setup) = Self::prepare(&self.state, bank_id, from_snap, to_snap, &curve_type) else {
                 return;
             };
+            setup.from_values = from_values;
 
-            let to = if to_snap < bank.snaps.len() {
-                &bank.snaps[to_snap]
-            } else {
-                error!("Invalid to_snap index: {}", to_snap);
-                return;
-            };
+            if self.cancel_task() {
+                info!("Manual input took over the morph to {}", to_snap);
+            }
+            self.pending.lock().unwrap().take();
+            if let Some(morph) = self.state.write().unwrap().active_morph.as_mut() {
+                morph.manual = true;
+                morph.step = None;
+                morph.looping = None;
+            }
 
-            let param_count = state_guard.project.parameters.len();
-            let parameters = state_guard.project.parameters.clone();
+            let param_count = setup.parameters.len();
+            self.manual = Some(ManualMorph {
+                setup,
+                last_sent_values: vec![None; param_count],
+                picked_up: true,
+            });
+        }
 
-            // Clone the values to avoid holding the lock for too long
-            (from.values.clone(), to.values.clone(), parameters, param_count)
+        let context = self.context();
+        let Some(manual) = self.manual.as_mut() else {
+            return;
         };
+        if !manual.picked_up {
+            info!("Manual input picked up the morph at {:.2}", position);
+            manual.picked_up = true;
+        }
 
-        // Create a new active morph
-        let active_morph = ActiveMorph {
-            from_snap,
-            to_snap,
-            duration_bars,
-            progress: 0.0,
-            from_values: from_values.clone(),
-            to_values: to_values.clone(),
-            current_values: from_values.clone(), // Start with source values
-        };
+        let MorphSetup { from_values, to_values, parameters, lanes } = &manual.setup;
+        let current_values = Self::interpolate_values(from_values, to_values, position, lanes);
+        Self::show_progress(&context, parameters, &mut manual.last_sent_values, position, current_values, None).await;
+    }
 
-        // Update the state
-        {
-            let mut state_guard = state.write().unwrap();
-            state_guard.active_morph = Some(active_morph);
+    fn context(&self) -> MorphContext {
+        MorphContext {
+            state: self.state.clone(),
+            event_bus: self.event_bus.clone(),
+            link: self.link.clone(),
+            pending: self.pending.clone(),
         }
+    }
+}
 
-        // If quantization is requested, get the link state and potentially wait
-        if quantize {
-            // First check if Link is connected to any peers by requesting status
-            let mut status_receiver = event_bus.subscribe();
-            let _ = event_bus.publish(Event::RequestLinkStatus);
+impl EventHandler for MorphEngine {
+    async fn handle(&mut self, event: Event) -> Flow {
+        match event {
+            // Handle note-off events for modifiers (pads 0-4)
+            Event::PadPressed { pad, velocity } if pad < 5 && velocity == 0 => {
+                let mut state_guard = self.state.write().unwrap();
+                if state_guard.active_modifier == Some(pad) {
+                    // Clear the active modifier
+                    state_guard.active_modifier = None;
+
+                    // Let the MIDI manager know to update LEDs
+                    drop(state_guard);
+                    // Use try_publish to avoid error handling
+                    self.event_bus.try_publish(Event::RequestUpdateLEDs);
+                }
+            },
+            Event::MorphInitiated {
+                from_snap,
+                to_snap,
+                duration,
+                curve_type,
+                quantize,
+            } => {
+                info!("Starting morph: {} -> {}, duration: {}, quantize: {}",
+                      from_snap, to_snap, duration, quantize);
+
+                // Check if we have valid snap indices
+                let bank_id = {
+                    let state_guard = self.state.read().unwrap();
+                    let snap_count = state_guard
+                        .project
+                        .banks
+                        .get(state_guard.current_bank)
+                        .map_or(0, |bank| bank.snaps.len());
+                    if from_snap >= snap_count || to_snap >= snap_count {
+                        error!("Invalid snap indices for morph: {} -> {}", from_snap, to_snap);
+                        return Flow::Continue;
+                    }
+                    state_guard.current_bank
+                };
 
-            let mut should_quantize = false;
-            let mut wait_ms = 0u64;
+                let mut request = MorphRequest { from_snap, to_snap, duration, curve_type, quantize, step: None };
 
-            // Wait up to 200ms for a response
-            let timeout = time::Duration::from_millis(200);
-            if let Ok(Ok(event)) = time::timeout(timeout, status_receiver.recv()).await {
-                if let Event::LinkStatusChanged { connected, .. } = event {
-                    should_quantize = connected;
+                // A running morph is interrupted according to the project's policy
+                let Some(start) = self.admit(Queued::Morph(request.clone())) else {
+                    return Flow::Continue;
+                };
+                if start.instant {
+                    request.duration = MorphDuration::Millis(0);
+                }
 
-                    // If connected, request the next bar time
-                    if connected {
-                        let _ = event_bus.publish(Event::RequestNextBarTime);
+                // Start a new morph task, which waits for its launch point
+                let context = self.context();
+                self.morph_task = Some(tokio::spawn(async move {
+                    if Self::run_morph(&context, bank_id, request, start.values).await {
+                        Self::finish(&context);
+                    }
+                }));
+            },
+
+            Event::LoopInitiated {
+                from_snap,
+                to_snap,
+                looping,
+                curve_type,
+                quantize,
+            } => {
+                let bank_id = {
+                    let state_guard = self.state.read().unwrap();
+                    let snap_count = state_guard
+                        .project
+                        .banks
+                        .get(state_guard.current_bank)
+                        .map_or(0, |bank| bank.snaps.len());
+                    if from_snap >= snap_count || to_snap >= snap_count {
+                        error!("Invalid snap indices for loop: {} -> {}", from_snap, to_snap);
+                        return Flow::Continue;
+                    }
+                    state_guard.current_bank
+                };
 
-                        // Wait for response with timeout
-                        if let Ok(Ok(time_event)) = time::timeout(timeout, status_receiver.recv()).await {
-                            if let Event::NextBarTime { wait_time_ms } = time_event {
-                                wait_ms = wait_time_ms;
-                            }
-                        }
+                let request = MorphRequest {
+                    from_snap,
+                    to_snap,
+                    duration: looping.period,
+                    curve_type,
+                    quantize,
+                    step: None,
+                };
+                let Some(start) = self.admit(Queued::Loop(request.clone(), looping)) else {
+                    return Flow::Continue;
+                };
+
+                let context = self.context();
+                self.morph_task = Some(tokio::spawn(async move {
+                    Self::run_loop(&context, bank_id, request, looping, start.values).await;
+                }));
+            },
+
+            Event::ChainInitiated { chain_id, quantize } => {
+                let (chain, bank_id) = {
+                    let state_guard = self.state.read().unwrap();
+                    let Some(chain) = state_guard.project.chains.iter().find(|chain| chain.id == chain_id) else {
+                        error!("Unknown morph chain: {}", chain_id);
+                        return Flow::Continue;
+                    };
+                    let snap_count = state_guard
+                        .project
+                        .banks
+                        .get(state_guard.current_bank)
+                        .map_or(0, |bank| bank.snaps.len());
+                    if let Some(step) = chain.steps.iter().find(|step| step.to_snap >= snap_count) {
+                        error!("Morph chain {} goes to snap {}, which is not in this bank", chain_id, step.to_snap);
+                        return Flow::Continue;
                     }
-                }
-            }
+                    (chain.clone(), state_guard.current_bank)
+                };
+
+                let Some(start) = self.admit(Queued::Chain { chain_id, quantize }) else {
+                    return Flow::Continue;
+                };
 
-            // If we should quantize and have a wait time, sleep until the next bar
-            if should_quantize && wait_ms > 0 {
-                info!("Quantizing morph to next bar boundary in {} ms", wait_ms);
+                let context = self.context();
+                self.morph_task = Some(tokio::spawn(async move {
+                    Self::run_chain(&context, bank_id, chain, quantize, start).await;
+                }));
+            },
+
+            Event::ManualMorphInitiated {
+                from_snap,
+                to_snap,
+                curve_type,
+            } => {
+                let bank_id = {
+                    let state_guard = self.state.read().unwrap();
+                    let snap_count = state_guard
+                        .project
+                        .banks
+                        .get(state_guard.current_bank)
+                        .map_or(0, |bank| bank.snaps.len());
+                    if from_snap >= snap_count || to_snap >= snap_count {
+                        error!("Invalid snap indices for manual morph: {} -> {}", from_snap, to_snap);
+                        return Flow::Continue;
+                    }
+                    state_guard.current_bank
+                };
 
-                // Before waiting, check if the morph was cancelled during this setup
-                let morph_still_active = {
-                    let state_guard = state.read().unwrap();
-                    state_guard.active_morph.is_some()
+                let request = MorphRequest {
+                    from_snap,
+                    to_snap,
+                    duration: MorphDuration::Millis(0),
+                    curve_type: curve_type.clone(),
+                    quantize: LaunchQuantize::Immediate,
+                    step: None,
+                };
+                let Some(start) = self.admit(Queued::Manual(request)) else {
+                    return Flow::Continue;
                 };
 
-                if !morph_still_active {
-                    info!("Morph was cancelled before quantization wait completed");
-                    return;
+                let Some(mut setup) = Self::prepare(&self.state, bank_id, from_snap, to_snap, &curve_type) else {
+                    return Flow::Continue;
+                };
+                if let Some(values) = start.values {
+                    setup.from_values = values;
                 }
 
-                tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+                info!("Starting manual morph: {} -> {}", from_snap, to_snap);
+                self.state.write().unwrap().active_morph = Some(ActiveMorph {
+                    from_snap,
+                    to_snap,
+                    duration: MorphDuration::Millis(0),
+                    step: None,
+                    looping: None,
+                    manual: true,
+                    curve_type,
+                    progress: 0.0,
+                    from_values: setup.from_values.clone(),
+                    to_values: setup.to_values.clone(),
+                    current_values: setup.from_values.clone(),
+                });
+
+                let param_count = setup.parameters.len();
+                self.manual = Some(ManualMorph {
+                    setup,
+                    last_sent_values: vec![None; param_count],
+                    picked_up: false,
+                });
+            },
+
+            Event::MorphPositionInput { position } => {
+                self.manual_position(position).await;
+            },
 
-                // Check again after waiting if morph is still active
-                let morph_still_active = {
-                    let state_guard = state.read().unwrap();
-                    state_guard.active_morph.is_some()
+            Event::ControlInput { channel, cc, value } => {
+                let bound = {
+                    let state_guard = self.state.read().unwrap();
+                    let binding = &state_guard.project.manual_control;
+                    binding.cc == Some(cc) && binding.channel.is_none_or(|c| c == channel)
                 };
+                if bound {
+                    self.manual_position(value as f64 / 127.0).await;
+                }
+            },
+
+            Event::PadPressure { pad, pressure } => {
+                // Only pressure on the pad of the morph's target counts
+                let on_target = {
+                    let state_guard = self.state.read().unwrap();
+                    state_guard.project.manual_control.pressure
+                        && state_guard
+                            .active_morph
+                            .as_ref()
+                            .is_some_and(|morph| morph.to_snap + 8 == pad as usize)
+                };
+                if on_target {
+                    self.manual_position(pressure as f64 / 127.0).await;
+                }
+            },
 
-                if !morph_still_active {
-                    info!("Morph was cancelled during quantization wait");
-                    return;
+            // Handle MorphCompleted event for direct cancellation
+            Event::MorphCompleted => {
+                info!("Received morph completed/cancelled event");
+
+                // Cancel any active morph task, and anything waiting for it
+                if self.cancel_task() {
+                    info!("Cancelled morph task due to explicit completion event");
+                }
+                self.pending.lock().unwrap().take();
+                self.release_manual();
+
+                // Ensure the morph state is cleared
+                let mut state_guard = self.state.write().unwrap();
+                if state_guard.active_morph.is_some() {
+                    state_guard.active_morph = None;
+                    info!("Cleared active morph state");
+
+                    // Request LED update to reflect the change
+                    drop(state_guard);
+                    self.event_bus.try_publish(Event::RequestUpdateLEDs);
                 }
+            },
+
+            // Handle RequestUpdateLEDs event
+            Event::RequestUpdateLEDs => {
+                // Forward to the MIDI manager to update LEDs
+                self.event_bus.try_publish(Event::RequestMIDIUpdate);
+                debug!("Requested MIDI LED update");
+            },
+
+            Event::Shutdown => {
+                info!("Shutting down morph engine");
+
+                // Cancel any active morph
+                self.cancel_task();
+
+                info!("Morph engine shutdown complete");
+                return Flow::Stop;
+            },
+            _ => {
+                // Ignore other events
             }
         }
 
-        // Get the current tempo (default or from Link)
-        let mut tempo_receiver = event_bus.subscribe();
-        let _ = event_bus.publish(Event::RequestLinkTempo);
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, missed: u64) {
+        // A restarted engine has lost the morph the state still shows; its task went with the old engine
+        if missed == 0 {
+            let mut state_guard = self.state.write().unwrap();
+            if state_guard.active_morph.take().is_some() {
+                info!("Dropped the morph of the previous engine after a restart");
+            }
+            if state_guard.pending_launch.is_some_and(|pending| pending.morph) {
+                state_guard.pending_launch = None;
+            }
+        }
 
-        // Default tempo
-        let mut bpm = 120.0;
+        // A missed cancellation leaves a task running for a morph the state no longer has
+        let morph_active = self.state.read().unwrap().active_morph.is_some();
+        if !morph_active && self.cancel_task() {
+            info!("Cancelled orphaned morph task after resync");
+        }
+        if !morph_active {
+            self.release_manual();
+        }
+
+        self.event_bus.try_publish(Event::RequestMIDIUpdate);
+    }
+}
 
-        // Wait up to 200ms for a tempo response
-        let timeout = time::Duration::from_millis(200);
-        if let Ok(Ok(event)) = time::timeout(timeout, tempo_receiver.recv()).await {
-            if let Event::LinkTempoChanged { tempo } = event {
-                bpm = tempo;
+impl Drop for MorphEngine {
+    fn drop(&mut self) {
+        // A panicking engine must not leave its morph running; the state is left to `resync`
+        // since the lock may be poisoned
+        if let Some(task) = self.morph_task.take() {
+            task.abort();
+        }
+    }
+}
+
+impl MorphEngine {
+    /// Run the steps of a chain one after another, holding on each snap as asked
+    async fn run_chain(context: &MorphContext, bank_id: usize, chain: MorphChain, quantize: LaunchQuantize, start: Start) {
+        info!("Starting morph chain {} with {} steps", chain.id, chain.steps.len());
+
+        let mut start_values = start.values;
+        for (step, leg) in chain.steps.iter().enumerate() {
+            let from_snap = context.state.read().unwrap().current_snap;
+            let request = MorphRequest {
+                from_snap,
+                to_snap: leg.to_snap,
+                duration: if step == 0 && start.instant { MorphDuration::Millis(0) } else { leg.duration },
+                curve_type: curve_by_id(leg.curve.as_deref().unwrap_or("linear")),
+                // Later steps follow on from the first without waiting for the grid again
+                quantize: if step == 0 { quantize } else { LaunchQuantize::Immediate },
+                step: Some(step),
+            };
+
+            if !Self::run_morph(context, bank_id, request, start_values.take()).await {
+                return;
+            }
+
+            if let Some(hold) = leg.hold {
+                let link = if hold.is_tempo_based() { context.link.as_ref() } else { None };
+                let mut clock = MorphClock::start(hold, link, LaunchQuantize::Immediate).await;
+                let mut interval = time::interval(UPDATE_INTERVAL);
+                interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+                loop {
+                    interval.tick().await;
+                    if !context.morph_active() {
+                        info!("Morph chain {} was cancelled during a hold", chain.id);
+                        return;
+                    }
+                    if clock.position().await.is_some_and(|position| position >= 1.0) {
+                        break;
+                    }
+                }
             }
         }
 
-        let beats_per_second = bpm / 60.0;
-        let bars = duration_bars as f64;
-        let beats_per_bar = 4.0; // Assuming 4/4 time
-        let total_duration_secs = (bars * beats_per_bar) / beats_per_second;
+        info!("Morph chain {} completed", chain.id);
+        let _ = context.event_bus.publish(Event::ChainCompleted { chain_id: chain.id });
+        Self::finish(context);
+    }
 
-        // Use more updates for longer morphs
-        let updates_per_second = 30.0; // 30 fps is smooth enough
-        let total_updates = (total_duration_secs * updates_per_second) as u32;
-        let update_interval = Duration::from_millis((1000.0 / updates_per_second) as u64);
+    /// Look up the snaps and parameters a morph works with
+    fn prepare(
+        state: &SharedState,
+        bank_id: usize,
+        from_snap: usize,
+        to_snap: usize,
+        curve_type: &MorphCurve,
+    ) -> Option<MorphSetup> {
+        let state_guard = state.read().unwrap();
+        let Some(bank) = state_guard.project.banks.get(bank_id) else {
+            error!("Invalid bank index: {}", bank_id);
+            return None;
+        };
 
-        info!("Starting morph with duration: {}s, total updates: {}", total_duration_secs, total_updates);
+        // Safely get the snap values
+        let from = if from_snap < bank.snaps.len() {
+            &bank.snaps[from_snap]
+        } else {
+            error!("Invalid from_snap index: {}", from_snap);
+            return None;
+        };
 
-        // Track morph start time
-        let start_time = Instant::now();
-        let total_duration = Duration::from_secs_f64(total_duration_secs);
+        let to = if to_snap < bank.snaps.len() {
+            &bank.snaps[to_snap]
+        } else {
+            error!("Invalid to_snap index: {}", to_snap);
+            return None;
+        };
 
-        // Create an interval for regular updates
-        let mut interval = time::interval(update_interval);
-        interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+        let param_count = state_guard.project.parameters.len();
+        let parameters = state_guard.project.parameters.clone();
+
+        // Look the curve up once; editing it mid-morph does not affect this morph
+        let curve = ResolvedCurve::resolve(curve_type, &state_guard.project.curves).unwrap_or_else(|e| {
+            warn!("{}, morphing linearly", e);
+            ResolvedCurve::Builtin(MorphCurve::Linear)
+        });
+
+        // Each parameter moves within its own window, on its own curve if it has one
+        let curves = &state_guard.project.curves;
+        let lanes: Vec<ParameterLane> = (0..param_count)
+            .map(|param_id| {
+                let timing = to.timing_for(param_id, parameters.get(param_id));
+                let curve = match &timing.curve {
+                    Some(id) => ResolvedCurve::resolve(&curve_by_id(id), curves).unwrap_or_else(|e| {
+                        warn!("{} for parameter {}, using the morph curve", e, param_id);
+                        curve.clone()
+                    }),
+                    None => curve.clone(),
+                };
+                let motion = parameters.get(param_id).map(|param| param.motion).unwrap_or_default();
+                ParameterLane { timing, curve, motion }
+            })
+            .collect();
+
+        // Clone the values to avoid holding the lock for too long
+        Some(MorphSetup {
+            from_values: from.values.clone(),
+            to_values: to.values.clone(),
+            parameters,
+            lanes,
+        })
+    }
+
+    /// Wait for the launch point on the beat grid when Link is enabled. Link keeps
+    /// a beat timeline even without peers. The target pad shows the launch as
+    /// pending meanwhile. Returns false if the morph was cancelled while waiting.
+    async fn wait_for_launch(context: &MorphContext, quantize: LaunchQuantize, bank_id: usize, snap_id: usize) -> bool {
+        let Some(link) = context.link.as_ref().filter(|_| quantize != LaunchQuantize::Immediate) else {
+            return true;
+        };
+
+        match link.status().await {
+            Ok(status) if status.enabled => match link.time_to_next_launch(quantize).await {
+                Ok(wait) if !wait.is_zero() => {
+                    info!("Quantizing morph to the next {} in {} ms", quantize, wait.as_millis());
+
+                    context.state.write().unwrap().pending_launch = Some(PendingLaunch { bank_id, snap_id, morph: true });
+                    context.event_bus.try_publish(Event::RequestUpdateLEDs);
+
+                    tokio::time::sleep(wait).await;
+
+                    {
+                        let mut state_guard = context.state.write().unwrap();
+                        if state_guard.pending_launch == Some(PendingLaunch { bank_id, snap_id, morph: true }) {
+                            state_guard.pending_launch = None;
+                        }
+                    }
+                    context.event_bus.try_publish(Event::RequestUpdateLEDs);
+
+                    // Check after waiting if morph is still active
+                    if !context.morph_active() {
+                        info!("Morph was cancelled during quantization wait");
+                        return false;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Skipping morph quantization: {}", e),
+            },
+            Ok(_) => debug!("Link is disabled, starting morph without quantization"),
+            Err(e) => warn!("Skipping morph quantization: {}", e),
+        }
+        true
+    }
+
+    /// Record, send and announce the values reached at a point of the morph
+    async fn show_progress(
+        context: &MorphContext,
+        parameters: &[Parameter],
+        last_sent_values: &mut [Option<u8>],
+        progress: f64,
+        current_values: Vec<u8>,
+        step: Option<usize>,
+    ) {
+        // Update the morph state
+        {
+            let mut state_guard = context.state.write().unwrap();
+            if let Some(morph) = &mut state_guard.active_morph {
+                morph.progress = progress;
+                morph.current_values = current_values.clone();
+            }
+        }
+
+        // Send current CC values to MIDI output
+        send_morph_cc_values(&context.event_bus, parameters, &current_values, last_sent_values).await;
+
+        // Publish progress event
+        let _ = context.event_bus.publish(Event::MorphProgressed {
+            progress,
+            current_values,
+            step,
+        });
+    }
+
+    /// Run a morph from one snap to another, starting from `start_values`
+    /// instead of the source snap when retargeting a running morph.
+    /// Returns whether the morph arrived; it stays active until `finish`.
+    async fn run_morph(
+        context: &MorphContext,
+        bank_id: usize,
+        request: MorphRequest,
+        start_values: Option<Vec<u8>>,
+    ) -> bool {
+        let MorphRequest { from_snap, to_snap, duration, curve_type, quantize, step } = request;
+        let (state, event_bus) = (&context.state, &context.event_bus);
+
+        // Get the values for both snaps and parameters
+        let Some(MorphSetup { from_values, to_values, parameters, lanes }) =
+            Self::prepare(state, bank_id, from_snap, to_snap, &curve_type)
+        else {
+            return false;
+        };
+        let from_values = start_values.unwrap_or(from_values);
+        let param_count = parameters.len();
+
+        // Create a new active morph
+        let active_morph = ActiveMorph {
+            from_snap,
+            to_snap,
+            duration,
+            step,
+            looping: None,
+            manual: false,
+            curve_type,
+            progress: 0.0,
+            from_values: from_values.clone(),
+            to_values: to_values.clone(),
+            current_values: from_values.clone(), // Start with source values
+        };
+
+        // Update the state
+        {
+            let mut state_guard = state.write().unwrap();
+            state_guard.active_morph = Some(active_morph);
+        }
 
         // Last sent values for each parameter - avoid sending duplicates
         let mut last_sent_values: Vec<Option<u8>> = vec![None; param_count];
 
+        if !Self::wait_for_launch(context, quantize, bank_id, to_snap).await {
+            return false;
+        }
+
+        // A zero-length morph is an instant recall
+        if duration.is_instant() {
+            send_morph_cc_values(event_bus, &parameters, &to_values, &mut last_sent_values).await;
+            return Self::arrive(state, event_bus, bank_id, to_snap, &to_values);
+        }
+
+        // Time-based morphs run on their own clock once launched
+        let link = if duration.is_tempo_based() { context.link.as_ref() } else { None };
+
+        // Progress follows the beat clock, so tempo changes mid-morph still land on the grid
+        let mut clock = MorphClock::start(duration, link, quantize).await;
+        info!("Starting morph over {}", duration);
+
+        // Create an interval for regular updates
+        let mut interval = time::interval(UPDATE_INTERVAL);
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
         loop {
             interval.tick().await;
 
             // Check if morph was cancelled - this is the key addition
-            let morph_still_active = {
-                let state_guard = state.read().unwrap();
-                state_guard.active_morph.is_some()
-            };
-
-            if !morph_still_active {
+            if !context.morph_active() {
                 info!("Morph was cancelled during execution");
-                return;
+                return false;
             }
 
             // Calculate progress
-            let elapsed = start_time.elapsed();
-            if elapsed >= total_duration {
+            let Some(progress) = clock.position().await else {
+                continue;
+            };
+            if progress >= 1.0 {
                 // Morph complete - send final values
-                send_morph_cc_values(&event_bus, &parameters, &to_values, &mut last_sent_values).await;
+                send_morph_cc_values(event_bus, &parameters, &to_values, &mut last_sent_values).await;
 
                 // Morph complete
-                Self::complete_morph(&state, &event_bus, &to_values).await;
-                break;
+                return Self::arrive(state, event_bus, bank_id, to_snap, &to_values);
             }
 
-            let progress = elapsed.as_secs_f64() / total_duration_secs;
-
-            // Apply curve to the progress
-            let curved_progress = Self::apply_curve(progress, &curve_type);
-
             // Calculate and update current values
-            let current_values = Self::interpolate_values(
-                &from_values,
-                &to_values,
-                curved_progress,
-                param_count
-            );
-
-            // Update the morph state
-            {
-                let mut state_guard = state.write().unwrap();
-                if let Some(morph) = &mut state_guard.active_morph {
-                    morph.progress = progress;
-                    morph.current_values = current_values.clone();
-                }
-            }
+            let current_values = Self::interpolate_values(&from_values, &to_values, progress, &lanes);
+            Self::show_progress(context, &parameters, &mut last_sent_values, progress, current_values, step).await;
+        }
+    }
 
-            // Send current CC values to MIDI output
-            send_morph_cc_values(&event_bus, &parameters, &current_values, &mut last_sent_values).await;
+    /// Move back and forth between two snaps until stopped or out of repeats.
+    /// Tempo-based periods follow the Link beat timeline, so the loop stays on
+    /// the grid through tempo changes; otherwise it runs on the wall clock.
+    async fn run_loop(
+        context: &MorphContext,
+        bank_id: usize,
+        request: MorphRequest,
+        looping: MorphLoop,
+        start_values: Option<Vec<u8>>,
+    ) {
+        let MorphRequest { from_snap, to_snap, curve_type, quantize, .. } = request;
+        let (state, event_bus) = (&context.state, &context.event_bus);
+
+        let Some(MorphSetup { from_values, to_values, parameters, lanes }) =
+            Self::prepare(state, bank_id, from_snap, to_snap, &curve_type)
+        else {
+            return;
+        };
+        // Only the first pass starts from an interrupted morph's values
+        let first_values = start_values.unwrap_or_else(|| from_values.clone());
 
-            // Publish progress event
-            let _ = event_bus.publish(Event::MorphProgressed {
-                progress,
-                current_values,
+        {
+            let mut state_guard = state.write().unwrap();
+            state_guard.active_morph = Some(ActiveMorph {
+                from_snap,
+                to_snap,
+                duration: looping.period,
+                step: None,
+                looping: Some(looping),
+                manual: false,
+                curve_type,
+                progress: 0.0,
+                from_values: first_values.clone(),
+                to_values: to_values.clone(),
+                current_values: first_values.clone(),
             });
         }
-    }
 
-    /// Apply a curve function to the progress value
-    fn apply_curve(progress: f64, curve_type: &MorphCurve) -> f64 {
-        match curve_type {
-            MorphCurve::Linear => progress,
-            #[cfg(feature = "pro")]
-            MorphCurve::Exponential => progress * progress,
-            #[cfg(feature = "pro")]
-            MorphCurve::Logarithmic => progress.sqrt(),
-            #[cfg(feature = "pro")]
-            MorphCurve::SCurve => 0.5 * (1.0 - (PI * progress).cos()),
+        if !Self::wait_for_launch(context, quantize, bank_id, to_snap).await {
+            return;
         }
-    }
+        let link = if looping.period.is_tempo_based() { context.link.as_ref() } else { None };
+
+        let mut clock = MorphClock::start(looping.period, link, quantize).await;
+        info!("Starting {:?} loop between {} and {} every {}", looping.mode, from_snap, to_snap, looping.period);
+
+        let mut interval = time::interval(UPDATE_INTERVAL);
+        interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+        let mut last_sent_values: Vec<Option<u8>> = vec![None; parameters.len()];
 
-    /// Complete a morph and finalize to the target values
-    async fn complete_morph(state: &SharedState, event_bus: &EventBus, final_values: &[u8]) {
-        // First, extract what we need from the active morph
-        let (to_snap, current_bank) = {
-            let state_guard = state.read().unwrap();
-            match &state_guard.active_morph {
-                Some(morph) => (morph.to_snap, state_guard.current_bank),
-                None => {
-                    warn!("No active morph to complete");
+        loop {
+            interval.tick().await;
+
+            if !context.morph_active() {
+                info!("Loop was stopped");
+                return;
+            }
+
+            let Some(position) = clock.position().await else {
+                continue;
+            };
+            let pass = position.floor() as u64;
+
+            // Out of repeats: settle on the snap the last pass went to
+            if let Some(repeats) = looping.repeats {
+                if pass >= repeats as u64 {
+                    let ends_on_source = looping.mode == LoopMode::PingPong && repeats % 2 == 0;
+                    let (snap, values) = if ends_on_source { (from_snap, &from_values) } else { (to_snap, &to_values) };
+                    send_morph_cc_values(event_bus, &parameters, values, &mut last_sent_values).await;
+                    if Self::arrive(state, event_bus, bank_id, snap, values) {
+                        Self::finish(context);
+                    }
                     return;
                 }
             }
-        };
 
-        // Now update everything with the extracted values
-        {
+            let within = position - pass as f64;
+            let progress = match looping.mode {
+                LoopMode::PingPong if pass % 2 == 1 => 1.0 - within,
+                _ => within,
+            };
+            let source = if pass == 0 { &first_values } else { &from_values };
+            let current_values = Self::interpolate_values(source, &to_values, progress, &lanes);
+            Self::show_progress(context, &parameters, &mut last_sent_values, progress, current_values, None).await;
+        }
+    }
+
+    /// Settle on a snap's values: it becomes the current snap, while the
+    /// morph stays active for the rest of its chain or until `finish`
+    fn arrive(state: &SharedState, event_bus: &EventBus, bank_id: usize, snap_id: usize, final_values: &[u8]) -> bool {
+        let (step, selected) = {
             let mut state_guard = state.write().unwrap();
-            // Update the current snap
-            state_guard.current_snap = to_snap;
+            let Some(morph) = state_guard.active_morph.as_mut() else {
+                warn!("No active morph to complete");
+                return false;
+            };
+            morph.progress = 1.0;
+            morph.current_values = final_values.to_vec();
+            let step = morph.step;
+
+            // Update the current snap; a crossfaded recall selected it already
+            let selected = state_guard.current_snap != snap_id;
+            state_guard.current_snap = snap_id;
 
             // Update the snap's values to ensure they match exactly
-            if let Some(bank) = state_guard.project.banks.get_mut(current_bank) {
-                if let Some(snap) = bank.snaps.get_mut(to_snap) {
+            if let Some(bank) = state_guard.project.banks.get_mut(bank_id) {
+                if let Some(snap) = bank.snaps.get_mut(snap_id) {
                     snap.values = final_values.to_vec();
                 }
             }
-
-            // Clear the active morph
-            state_guard.active_morph = None;
-        }
+            (step, selected)
+        };
 
         // Send the final values
         let _ = event_bus.publish(Event::MorphProgressed {
             progress: 1.0,
             current_values: final_values.to_vec(),
+            step,
         });
+        if selected {
+            let _ = event_bus.publish(Event::SnapSelected { bank: bank_id, snap_id });
+        }
+        true
+    }
+
+    /// End the morph or chain, then start any request waiting for it
+    fn finish(context: &MorphContext) {
+        let current_snap = {
+            let mut state_guard = context.state.write().unwrap();
+            state_guard.active_morph = None;
+            state_guard.current_snap
+        };
+
+        // Taken first, since the completion event clears it
+        let next = context.pending.lock().unwrap().take();
 
         // Send completion event
-        let _ = event_bus.publish(Event::MorphCompleted);
+        let _ = context.event_bus.publish(Event::MorphCompleted);
 
         info!("Morph completed");
+
+        match next {
+            Some(Queued::Morph(next)) => {
+                info!("Starting the waiting morph to {}", next.to_snap);
+                let _ = context.event_bus.publish(Event::MorphInitiated {
+                    from_snap: current_snap,
+                    to_snap: next.to_snap,
+                    duration: next.duration,
+                    curve_type: next.curve_type,
+                    quantize: next.quantize,
+                });
+            }
+            Some(Queued::Chain { chain_id, quantize }) => {
+                info!("Starting the waiting morph chain {}", chain_id);
+                let _ = context.event_bus.publish(Event::ChainInitiated { chain_id, quantize });
+            }
+            Some(Queued::Manual(next)) => {
+                info!("Starting the waiting manual morph to {}", next.to_snap);
+                let _ = context.event_bus.publish(Event::ManualMorphInitiated {
+                    from_snap: current_snap,
+                    to_snap: next.to_snap,
+                    curve_type: next.curve_type,
+                });
+            }
+            Some(Queued::Loop(next, looping)) => {
+                info!("Starting the waiting loop to {}", next.to_snap);
+                let _ = context.event_bus.publish(Event::LoopInitiated {
+                    from_snap: current_snap,
+                    to_snap: next.to_snap,
+                    looping,
+                    curve_type: next.curve_type,
+                    quantize: next.quantize,
+                });
+            }
+            None => {}
+        }
     }
 
     /// Interpolate between two sets of values based on a progress value
@@ -413,20 +1016,16 @@ impl MorphEngine {
         from: &[u8],
         to: &[u8],
         progress: f64,
-        param_count: usize,
+        lanes: &[ParameterLane],
     ) -> Vec<u8> {
-        let mut result = Vec::with_capacity(param_count);
-
-        for i in 0..param_count {
-            let from_val = *from.get(i).unwrap_or(&0) as f64;
-            let to_val = *to.get(i).unwrap_or(&0) as f64;
+        let mut result = Vec::with_capacity(lanes.len());
 
-            // Interpolate
-            let value = from_val + (to_val - from_val) * progress;
+        for (i, lane) in lanes.iter().enumerate() {
+            let from_val = *from.get(i).unwrap_or(&0);
+            let to_val = *to.get(i).unwrap_or(&0);
 
-            // Clamp and convert back to u8
-            let clamped = value.round().max(0.0).min(127.0) as u8;
-            result.push(clamped);
+            // Interpolate within the parameter's window, as the parameter moves
+            result.push(lane.value(from_val, to_val, progress));
         }
 
         result
@@ -434,6 +1033,185 @@ impl MorphEngine {
 }
 
 
+/// Tempo and bar length for a duration: from Link for bars and beats,
+/// otherwise 4/4 at the default tempo
+async fn tempo(duration: MorphDuration, link: Option<&LinkSynchronizer>) -> (f64, f64) {
+    match link.filter(|_| duration.is_tempo_based()) {
+        Some(link) => match link.status().await {
+            Ok(status) => (status.tempo.max(1.0), beats_per_bar(status.quantum)),
+            Err(e) => {
+                warn!("Using default tempo for morph: {}", e);
+                (DEFAULT_TEMPO, 4.0)
+            }
+        },
+        None => (DEFAULT_TEMPO, 4.0),
+    }
+}
+
+/// Bar length in beats for a Link quantum. `ControlService::set_quantum` keeps it in
+/// range; anything else would leave passes off the bar grid, so 4/4 is used instead.
+fn beats_per_bar(quantum: f64) -> f64 {
+    if QUANTUM_RANGE.contains(&quantum) {
+        quantum
+    } else {
+        warn!("Link quantum of {} beats is out of range, counting 4 beats per bar", quantum);
+        4.0
+    }
+}
+
+/// Snap values and parameter lanes a morph works with
+struct MorphSetup {
+    from_values: Vec<u8>,
+    to_values: Vec<u8>,
+    parameters: Vec<Parameter>,
+    lanes: Vec<ParameterLane>,
+}
+
+/// Where a morph or loop is, counted in passes from snap to snap
+enum MorphClock {
+    /// Beats on the Link timeline shared with peers, so tempo changes and the
+    /// session's meter are followed exactly
+    Link {
+        link: LinkSynchronizer,
+        start_beat: f64,
+        beats_per_pass: f64,
+    },
+    /// Beats counted here at the current tempo, when there are no peers to follow
+    Internal {
+        link: Option<LinkSynchronizer>,
+        tempo: f64,
+        last_tick: Instant,
+        beats: f64,
+        beats_per_pass: f64,
+    },
+    /// Wall-clock time, for millisecond durations
+    Wall { start: Instant, seconds_per_pass: f64 },
+}
+
+impl MorphClock {
+    async fn start(period: MorphDuration, link: Option<&LinkSynchronizer>, quantize: LaunchQuantize) -> Self {
+        if !period.is_tempo_based() {
+            return MorphClock::Wall {
+                start: Instant::now(),
+                seconds_per_pass: period.seconds(DEFAULT_TEMPO, 4.0),
+            };
+        }
+
+        if let Some(link) = link {
+            match (link.status().await, link.beat_position().await) {
+                (Ok(status), Ok(beat)) if status.enabled && status.peers > 0 => {
+                    let beats_per_bar = beats_per_bar(status.quantum);
+                    let beats_per_pass = period.beats(beats_per_bar).unwrap_or(beats_per_bar);
+                    // Passes start on the launch grid when quantized, as placed by Link's phase
+                    let start_beat = match quantize.beats(beats_per_bar) {
+                        Some(grid) => match link.phase(grid).await {
+                            Ok(phase) if phase > grid / 2.0 => beat - phase + grid,
+                            Ok(phase) => beat - phase,
+                            Err(_) => beat,
+                        },
+                        None => beat,
+                    };
+                    return MorphClock::Link {
+                        link: link.clone(),
+                        start_beat,
+                        beats_per_pass,
+                    };
+                }
+                (Ok(_), Ok(_)) => debug!("No Link peers, counting beats on the internal clock"),
+                (Err(e), _) | (_, Err(e)) => warn!("{}, counting beats on the internal clock", e),
+            }
+        }
+
+        let (tempo, beats_per_bar) = tempo(period, link).await;
+        MorphClock::Internal {
+            link: link.cloned(),
+            tempo,
+            last_tick: Instant::now(),
+            beats: 0.0,
+            beats_per_pass: period.beats(beats_per_bar).unwrap_or(beats_per_bar),
+        }
+    }
+
+    /// Passes since the clock started; None when Link cannot be read this time
+    async fn position(&mut self) -> Option<f64> {
+        match self {
+            MorphClock::Link {
+                link,
+                start_beat,
+                beats_per_pass,
+            } => match link.beat_position().await {
+                Ok(beat) => Some(((beat - *start_beat) / *beats_per_pass).max(0.0)),
+                Err(e) => {
+                    debug!("Skipping morph update: {}", e);
+                    None
+                }
+            },
+            MorphClock::Internal {
+                link,
+                tempo,
+                last_tick,
+                beats,
+                beats_per_pass,
+            } => {
+                // Follow tempo changes from here on; the beats so far are kept
+                if let Some(link) = link {
+                    if let Ok(status) = link.status().await {
+                        *tempo = status.tempo.max(1.0);
+                    }
+                }
+                let now = Instant::now();
+                *beats += now.duration_since(*last_tick).as_secs_f64() * *tempo / 60.0;
+                *last_tick = now;
+                Some(*beats / *beats_per_pass)
+            }
+            MorphClock::Wall { start, seconds_per_pass } => {
+                Some(start.elapsed().as_secs_f64() / *seconds_per_pass)
+            }
+        }
+    }
+}
+
+/// How one parameter moves during a morph
+struct ParameterLane {
+    timing: MorphTiming,
+    curve: ResolvedCurve,
+    motion: ParameterMotion,
+}
+
+impl ParameterLane {
+    /// How far the parameter has gone from its source to its target value (0-1)
+    fn blend(&self, progress: f64) -> f64 {
+        let local = self.timing.local_progress(progress);
+        match self.motion {
+            // Switches and selectors never pass through the values in between
+            ParameterMotion::Discrete { at } => {
+                if at.reached(local) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            ParameterMotion::Continuous | ParameterMotion::Stepped { .. } => self.curve.evaluate(local),
+        }
+    }
+
+    /// The parameter's value at a point of the morph, on its way from `from` to `to`
+    fn value(&self, from: u8, to: u8, progress: f64) -> u8 {
+        let blend = self.blend(progress);
+        let value = (from as f64 + (to as f64 - from as f64) * blend).clamp(0.0, 127.0);
+
+        match self.motion {
+            // In between, a stepped parameter only takes its fixed positions;
+            // it still sets off from and lands on the snaps' own values
+            ParameterMotion::Stepped { steps } if blend > 0.0 && blend < 1.0 => {
+                let spacing = 127.0 / (steps.max(2) - 1) as f64;
+                ((value / spacing).round() * spacing).round() as u8
+            }
+            _ => value.round() as u8,
+        }
+    }
+}
+
 /// Helper function to send CC values during morphing
 async fn send_morph_cc_values(
     event_bus: &EventBus,
@@ -442,21 +1220,138 @@ async fn send_morph_cc_values(
     last_sent: &mut [Option<u8>]
 ) {
     // For each parameter that has a value
-    for (idx, param) in parameters.iter().enumerate() {
-        if idx < values.len() {
-            let value = values[idx];
-
-            // Only send if value has changed since last update
-            if last_sent[idx] != Some(value) {
-                // Save this value to avoid redundant sends
-                last_sent[idx] = Some(value);
-
-                // Send CC value changed event
-                let _ = event_bus.publish(Event::CCValueChanged {
-                    param_id: idx,
-                    value,
-                });
-            }
+    for (idx, &value) in values.iter().enumerate().take(parameters.len()) {
+        // Only send if value has changed since last update
+        if last_sent[idx] != Some(value) {
+            // Save this value to avoid redundant sends
+            last_sent[idx] = Some(value);
+
+            // Send CC value changed event
+            let _ = event_bus.publish(Event::CCValueChanged {
+                param_id: idx,
+                value,
+            });
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::JumpPoint;
+
+    fn lane(start: f64, end: f64, motion: ParameterMotion) -> ParameterLane {
+        ParameterLane {
+            timing: MorphTiming {
+                start,
+                end,
+                curve: None,
+            },
+            curve: ResolvedCurve::Builtin(MorphCurve::Linear),
+            motion,
+        }
+    }
+
+    #[test]
+    fn discrete_lanes_jump_at_their_point_of_the_window() {
+        let jump = |at, progress| lane(0.2, 0.6, ParameterMotion::Discrete { at }).blend(progress);
+
+        assert_eq!(jump(JumpPoint::Start, 0.2), 0.0);
+        assert_eq!(jump(JumpPoint::Start, 0.21), 1.0);
+        assert_eq!(jump(JumpPoint::Middle, 0.39), 0.0);
+        assert_eq!(jump(JumpPoint::Middle, 0.4), 1.0);
+        assert_eq!(jump(JumpPoint::End, 0.59), 0.0);
+        assert_eq!(jump(JumpPoint::End, 0.6), 1.0);
+
+        // Outside the window the lane sits at its source or target
+        for at in [JumpPoint::Start, JumpPoint::Middle, JumpPoint::End] {
+            assert_eq!(jump(at, 0.0), 0.0);
+            assert_eq!(jump(at, 1.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn a_zero_length_window_jumps_at_its_start() {
+        let discrete = lane(0.5, 0.5, ParameterMotion::Discrete { at: JumpPoint::End });
+        assert_eq!(discrete.blend(0.49), 0.0);
+        assert_eq!(discrete.blend(0.5), 1.0);
+
+        let continuous = lane(0.5, 0.5, ParameterMotion::Continuous);
+        assert_eq!(continuous.blend(0.49), 0.0);
+        assert_eq!(continuous.blend(0.5), 1.0);
+    }
+
+    #[test]
+    fn stepped_lanes_only_take_fixed_positions_in_between() {
+        // Five positions across the range: 0, 32, 64, 95 and 127
+        let positions = [0, 32, 64, 95, 127];
+        let stepped = lane(0.0, 1.0, ParameterMotion::Stepped { steps: 5 });
+
+        assert_eq!(stepped.value(0, 127, 0.3), 32);
+        assert_eq!(stepped.value(0, 127, 0.5), 64);
+        assert_eq!(stepped.value(0, 127, 0.9), 127);
+
+        // From and to values off the positions: it sets off from and lands on
+        // the snaps' own values, and only takes the positions in between
+        assert_eq!(stepped.value(40, 100, 0.0), 40);
+        assert_eq!(stepped.value(40, 100, 0.25), 64);
+        assert_eq!(stepped.value(40, 100, 0.75), 95);
+        assert_eq!(stepped.value(40, 100, 1.0), 100);
+
+        let mut previous = 0;
+        for i in 1..100 {
+            let value = stepped.value(40, 100, i as f64 / 100.0);
+            assert!(positions.contains(&value), "{} is not a position", value);
+            assert!(value >= previous);
+            previous = value;
+        }
+
+        // With two positions it flips between the ends of the range within its window
+        let windowed = lane(0.5, 1.0, ParameterMotion::Stepped { steps: 2 });
+        assert_eq!(windowed.value(20, 100, 0.4), 20);
+        assert_eq!(windowed.value(20, 100, 0.75), 0);
+        assert_eq!(windowed.value(20, 100, 0.8), 127);
+        assert_eq!(windowed.value(20, 100, 1.0), 100);
+    }
+
+    #[test]
+    fn continuous_lanes_follow_the_curve_across_the_window() {
+        let continuous = lane(0.25, 0.75, ParameterMotion::Continuous);
+        assert_eq!(continuous.blend(0.0), 0.0);
+        assert_eq!(continuous.blend(0.5), 0.5);
+        assert_eq!(continuous.blend(0.625), 0.75);
+        assert_eq!(continuous.blend(1.0), 1.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn internal_clock_bars_follow_the_quantum_through_tempo_changes() {
+        let link = LinkSynchronizer::new(EventBus::new(16, "test"));
+        // Without Link, beats are counted on the internal clock
+        link.enable(false).await;
+        link.set_quantum(7.0).await;
+        link.set_tempo(120.0).await;
+
+        let mut clock = MorphClock::start(MorphDuration::Bars(1.0), Some(&link), LaunchQuantize::Immediate).await;
+        assert!(matches!(clock, MorphClock::Internal { beats_per_pass, .. } if beats_per_pass == 7.0));
+
+        // Half of the 7/4 bar at 120 BPM
+        time::advance(Duration::from_millis(1750)).await;
+        let position = clock.position().await.unwrap();
+        assert!((position - 0.5).abs() < 1e-6, "{}", position);
+
+        // The other 3.5 beats at half the tempo take twice as long
+        link.set_tempo(60.0).await;
+        time::advance(Duration::from_millis(3500)).await;
+        let position = clock.position().await.unwrap();
+        assert!((position - 1.0).abs() < 1e-6, "{}", position);
+    }
+
+    #[test]
+    fn out_of_range_quanta_count_as_four_four() {
+        assert_eq!(beats_per_bar(3.0), 3.0);
+        assert_eq!(beats_per_bar(7.0), 7.0);
+        for quantum in [0.0, -3.0, 64.0, f64::NAN] {
+            assert_eq!(beats_per_bar(quantum), 4.0);
+        }
+    }
+}
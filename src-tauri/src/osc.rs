@@ -0,0 +1,471 @@
+// src-tauri/src/osc.rs
+use crate::control::{parse_curve, ControlService};
+use crate::events::{Event, EventBus, LaunchQuantize, MorphDuration};
+use crate::supervisor::{supervise, EventHandler, Flow};
+use rosc::{OscMessage, OscPacket, OscType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::UdpSocket;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Prefix of every address Snap-Blaster handles or sends
+const PREFIX: &str = "/snapblaster";
+
+/// Largest datagram accepted
+const MAX_PACKET_SIZE: usize = 8192;
+
+/// OSC server settings, stored with the app settings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OscSettings {
+    /// Listen for OSC messages
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Address to listen on; `0.0.0.0` accepts messages from other machines
+    #[serde(default = "default_bind")]
+    pub bind: String,
+
+    /// UDP port to listen on
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+fn default_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_port() -> u16 {
+    9000
+}
+
+impl Default for OscSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: default_bind(),
+            port: default_port(),
+        }
+    }
+}
+
+/// A request decoded from an OSC message
+#[derive(Clone, Debug, PartialEq)]
+enum OscCommand {
+    SelectBank(usize),
+    SelectSnap { bank: Option<usize>, snap: usize },
+    Morph { to_snap: usize, duration: Option<MorphDuration>, curve: String, quantize: Option<LaunchQuantize> },
+    SetParameter { param_id: usize, value: u8 },
+    SetTempo(f64),
+    SetQuantum(f64),
+    Transport(bool),
+    Register(Option<u16>),
+    Unregister(Option<u16>),
+}
+
+/// OscServer maps an OSC address space onto control operations and
+/// echoes state changes back to registered clients.
+///
+/// Incoming addresses:
+/// - `/snapblaster/bank <bank>`
+/// - `/snapblaster/snap <snap>` or `/snapblaster/snap <bank> <snap>`
+/// - `/snapblaster/morph <to_snap> [duration] [curve] [quantize]`, where the duration is
+///   a number of bars or a string such as `3/4bar`, `2beats` or `500ms`
+///   and `quantize` is 0, 1 or a grid such as `beat` or `4bars`
+/// - `/snapblaster/param <param_id> <value>`
+/// - `/snapblaster/tempo <bpm>`
+/// - `/snapblaster/quantum <beats>`
+/// - `/snapblaster/transport/start`, `/snapblaster/transport/stop`, `/snapblaster/transport <0|1>`
+/// - `/snapblaster/register [port]`, `/snapblaster/unregister [port]`
+///
+/// Registered clients receive `/snapblaster/snap <bank> <snap>`,
+/// `/snapblaster/morph/progress <0..1>` and `/snapblaster/tempo <bpm>`.
+pub struct OscServer {
+    control: ControlService,
+    socket: Arc<UdpSocket>,
+    clients: Arc<Mutex<HashSet<SocketAddr>>>,
+}
+
+impl OscServer {
+    /// Bind the server socket; must be called from within the async runtime
+    pub fn bind(settings: &OscSettings, control: ControlService) -> Result<Self, Box<dyn Error>> {
+        let socket = std::net::UdpSocket::bind((settings.bind.as_str(), settings.port))?;
+        socket.set_nonblocking(true)?;
+        let socket = UdpSocket::from_std(socket)?;
+        let local_addr = socket.local_addr()?;
+        info!("OSC server listening on {}", local_addr);
+
+        // OSC has no authentication, so anyone who can reach the port can drive the show
+        if !local_addr.ip().is_loopback() {
+            warn!("OSC server is reachable from the network without authentication");
+        }
+
+        Ok(Self {
+            control,
+            socket: Arc::new(socket),
+            clients: Arc::new(Mutex::new(HashSet::new())),
+        })
+    }
+
+    /// Start receiving commands and broadcasting state changes
+    pub fn start(self, event_bus: &EventBus) -> Vec<JoinHandle<()>> {
+        let socket = self.socket.clone();
+        let clients = self.clients.clone();
+        let broadcaster = supervise(event_bus, "osc-broadcaster", move || OscBroadcaster {
+            socket: socket.clone(),
+            clients: clients.clone(),
+        });
+
+        let receiver = tokio::spawn(async move { self.run().await });
+
+        vec![receiver, broadcaster]
+    }
+
+    async fn run(self) {
+        let mut buf = vec![0u8; MAX_PACKET_SIZE];
+
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(e) => {
+                    // ICMP errors from clients that went away surface here; keep listening
+                    debug!("OSC receive error: {}", e);
+                    continue;
+                }
+            };
+
+            match rosc::decoder::decode_udp(&buf[..len]) {
+                Ok((_, packet)) => self.handle_packet(packet, from).await,
+                Err(e) => warn!("Ignoring malformed OSC packet from {}: {:?}", from, e),
+            }
+        }
+    }
+
+    async fn handle_packet(&self, packet: OscPacket, from: SocketAddr) {
+        // Bundles are flattened; their time tags are not honoured
+        let mut pending = vec![packet];
+        while let Some(packet) = pending.pop() {
+            match packet {
+                OscPacket::Message(msg) => self.handle_message(msg, from).await,
+                OscPacket::Bundle(bundle) => pending.extend(bundle.content.into_iter().rev()),
+            }
+        }
+    }
+
+    async fn handle_message(&self, msg: OscMessage, from: SocketAddr) {
+        debug!("OSC {} {:?} from {}", msg.addr, msg.args, from);
+
+        let command = match parse_command(&msg) {
+            Ok(command) => command,
+            Err(e) => {
+                warn!("Rejected OSC message {} from {}: {}", msg.addr, from, e);
+                return;
+            }
+        };
+
+        let result = match command {
+            OscCommand::SelectBank(bank) => self.control.select_bank(bank),
+            OscCommand::SelectSnap { bank, snap } => {
+                let bank = bank.unwrap_or_else(|| self.control.current_bank());
+                self.control.select_snap(bank, snap)
+            }
+            OscCommand::Morph { to_snap, duration, curve, quantize } => {
+                self.control.morph_to(to_snap, duration, parse_curve(&curve), quantize)
+            }
+            OscCommand::SetParameter { param_id, value } => self.control.set_parameter(param_id, value),
+            OscCommand::SetTempo(tempo) => self.control.set_tempo(tempo).await,
+            OscCommand::SetQuantum(quantum) => self.control.set_quantum(quantum).await,
+            OscCommand::Transport(playing) => self.control.set_transport(playing).await,
+            OscCommand::Register(port) => {
+                let addr = SocketAddr::new(from.ip(), port.unwrap_or(from.port()));
+                info!("OSC client registered: {}", addr);
+                self.clients.lock().unwrap().insert(addr);
+                Ok(())
+            }
+            OscCommand::Unregister(port) => {
+                let addr = SocketAddr::new(from.ip(), port.unwrap_or(from.port()));
+                if self.clients.lock().unwrap().remove(&addr) {
+                    info!("OSC client unregistered: {}", addr);
+                }
+                Ok(())
+            }
+        };
+
+        if let Err(e) = result {
+            warn!("OSC command {} failed: {}", msg.addr, e);
+        }
+    }
+}
+
+/// Decode a message into a command
+fn parse_command(msg: &OscMessage) -> Result<OscCommand, String> {
+    let path = msg
+        .addr
+        .strip_prefix(PREFIX)
+        .ok_or_else(|| format!("Address outside {}", PREFIX))?;
+    let args = &msg.args;
+
+    match path {
+        "/bank" => Ok(OscCommand::SelectBank(arg_index(args, 0)?)),
+        "/snap" if args.len() >= 2 => Ok(OscCommand::SelectSnap {
+            bank: Some(arg_index(args, 0)?),
+            snap: arg_index(args, 1)?,
+        }),
+        "/snap" => Ok(OscCommand::SelectSnap {
+            bank: None,
+            snap: arg_index(args, 0)?,
+        }),
+        "/morph" => Ok(OscCommand::Morph {
+            to_snap: arg_index(args, 0)?,
+            duration: match args.get(1) {
+                Some(OscType::String(duration)) => Some(duration.parse()?),
+                Some(_) => Some(MorphDuration::Bars(arg_number(args, 1)?)),
+                None => None,
+            },
+            curve: match args.get(2) {
+                Some(OscType::String(curve)) => curve.clone(),
+                Some(other) => return Err(format!("Expected a curve name, got {:?}", other)),
+                None => "linear".to_string(),
+            },
+            quantize: match args.get(3) {
+                Some(OscType::String(quantize)) => Some(quantize.parse()?),
+                Some(_) => Some(LaunchQuantize::from_flag(arg_number(args, 3)? != 0.0)),
+                None => None,
+            },
+        }),
+        "/param" => Ok(OscCommand::SetParameter {
+            param_id: arg_index(args, 0)?,
+            value: arg_number(args, 1)?.round().clamp(0.0, 127.0) as u8,
+        }),
+        "/tempo" => Ok(OscCommand::SetTempo(arg_number(args, 0)?)),
+        "/quantum" => Ok(OscCommand::SetQuantum(arg_number(args, 0)?)),
+        "/transport/start" => Ok(OscCommand::Transport(true)),
+        "/transport/stop" => Ok(OscCommand::Transport(false)),
+        "/transport" => Ok(OscCommand::Transport(arg_number(args, 0)? != 0.0)),
+        "/register" => Ok(OscCommand::Register(arg_port(args)?)),
+        "/unregister" => Ok(OscCommand::Unregister(arg_port(args)?)),
+        _ => Err("Unknown address".to_string()),
+    }
+}
+
+/// Read a numeric argument; surfaces like TouchOSC send floats for everything
+fn arg_number(args: &[OscType], idx: usize) -> Result<f64, String> {
+    match args.get(idx) {
+        Some(OscType::Int(v)) => Ok(*v as f64),
+        Some(OscType::Long(v)) => Ok(*v as f64),
+        Some(OscType::Float(v)) => Ok(*v as f64),
+        Some(OscType::Double(v)) => Ok(*v),
+        Some(OscType::Bool(v)) => Ok(if *v { 1.0 } else { 0.0 }),
+        Some(OscType::String(v)) => v
+            .trim()
+            .parse()
+            .map_err(|_| format!("Argument {} is not a number: {}", idx, v)),
+        Some(other) => Err(format!("Argument {} is not a number: {:?}", idx, other)),
+        None => Err(format!("Missing argument {}", idx)),
+    }
+}
+
+/// Read the optional reply port of a register or unregister message
+fn arg_port(args: &[OscType]) -> Result<Option<u16>, String> {
+    match args.first() {
+        Some(_) => Ok(Some(arg_number(args, 0)?.clamp(1.0, u16::MAX as f64) as u16)),
+        None => Ok(None),
+    }
+}
+
+/// Read a non-negative index argument
+fn arg_index(args: &[OscType], idx: usize) -> Result<usize, String> {
+    let value = arg_number(args, idx)?;
+    if value < 0.0 || !value.is_finite() {
+        return Err(format!("Argument {} is not a valid index: {}", idx, value));
+    }
+    Ok(value.round() as usize)
+}
+
+/// Sends state changes to registered OSC clients
+struct OscBroadcaster {
+    socket: Arc<UdpSocket>,
+    clients: Arc<Mutex<HashSet<SocketAddr>>>,
+}
+
+impl OscBroadcaster {
+    async fn send(&self, addr: &str, args: Vec<OscType>) {
+        let clients: Vec<SocketAddr> = self.clients.lock().unwrap().iter().copied().collect();
+        if clients.is_empty() {
+            return;
+        }
+
+        let packet = OscPacket::Message(OscMessage {
+            addr: format!("{}{}", PREFIX, addr),
+            args,
+        });
+        let bytes = match rosc::encoder::encode(&packet) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Failed to encode OSC message {}: {:?}", addr, e);
+                return;
+            }
+        };
+
+        for client in clients {
+            if let Err(e) = self.socket.send_to(&bytes, client).await {
+                debug!("Failed to send OSC to {}: {}", client, e);
+            }
+        }
+    }
+}
+
+impl EventHandler for OscBroadcaster {
+    async fn handle(&mut self, event: Event) -> Flow {
+        match event {
+            Event::SnapSelected { bank, snap_id } => {
+                self.send("/snap", vec![OscType::Int(bank as i32), OscType::Int(snap_id as i32)])
+                    .await;
+            }
+            Event::MorphProgressed { progress, .. } => {
+                self.send("/morph/progress", vec![OscType::Float(progress as f32)]).await;
+            }
+            Event::MorphCompleted => {
+                self.send("/morph/progress", vec![OscType::Float(1.0)]).await;
+            }
+            Event::LinkTempoChanged { tempo } => {
+                self.send("/tempo", vec![OscType::Float(tempo as f32)]).await;
+            }
+            Event::Shutdown => return Flow::Stop,
+            _ => {}
+        }
+        Flow::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(addr: &str, args: Vec<OscType>) -> OscMessage {
+        OscMessage {
+            addr: addr.to_string(),
+            args,
+        }
+    }
+
+    fn parse(addr: &str, args: Vec<OscType>) -> Result<OscCommand, String> {
+        parse_command(&message(addr, args))
+    }
+
+    #[test]
+    fn addresses_route_to_commands() {
+        assert_eq!(parse("/snapblaster/bank", vec![OscType::Int(2)]), Ok(OscCommand::SelectBank(2)));
+        assert_eq!(
+            parse("/snapblaster/snap", vec![OscType::Int(3)]),
+            Ok(OscCommand::SelectSnap { bank: None, snap: 3 })
+        );
+        assert_eq!(
+            parse("/snapblaster/snap", vec![OscType::Int(1), OscType::Int(4)]),
+            Ok(OscCommand::SelectSnap { bank: Some(1), snap: 4 })
+        );
+        assert_eq!(parse("/snapblaster/tempo", vec![OscType::Double(128.0)]), Ok(OscCommand::SetTempo(128.0)));
+        assert_eq!(parse("/snapblaster/quantum", vec![OscType::Int(7)]), Ok(OscCommand::SetQuantum(7.0)));
+        assert_eq!(parse("/snapblaster/transport/start", vec![]), Ok(OscCommand::Transport(true)));
+        assert_eq!(parse("/snapblaster/transport/stop", vec![]), Ok(OscCommand::Transport(false)));
+        assert_eq!(parse("/snapblaster/transport", vec![OscType::Bool(true)]), Ok(OscCommand::Transport(true)));
+        assert_eq!(parse("/snapblaster/transport", vec![OscType::Int(0)]), Ok(OscCommand::Transport(false)));
+
+        assert!(parse("/snapblaster/unknown", vec![]).is_err());
+        assert!(parse("/other/bank", vec![OscType::Int(0)]).is_err());
+    }
+
+    #[test]
+    fn float_indexes_and_values_are_rounded_and_checked() {
+        // Surfaces such as TouchOSC send floats for everything
+        assert_eq!(parse("/snapblaster/bank", vec![OscType::Float(1.6)]), Ok(OscCommand::SelectBank(2)));
+        assert_eq!(
+            parse("/snapblaster/param", vec![OscType::Float(0.0), OscType::Float(200.0)]),
+            Ok(OscCommand::SetParameter { param_id: 0, value: 127 })
+        );
+        assert_eq!(
+            parse("/snapblaster/param", vec![OscType::Int(1), OscType::String(" 64 ".to_string())]),
+            Ok(OscCommand::SetParameter { param_id: 1, value: 64 })
+        );
+
+        assert!(parse("/snapblaster/bank", vec![OscType::Float(-1.0)]).is_err());
+        assert!(parse("/snapblaster/bank", vec![OscType::Float(f32::NAN)]).is_err());
+        assert!(parse("/snapblaster/bank", vec![OscType::String("two".to_string())]).is_err());
+        assert!(parse("/snapblaster/bank", vec![]).is_err());
+        assert!(parse("/snapblaster/param", vec![OscType::Int(0)]).is_err());
+    }
+
+    #[test]
+    fn morphs_read_durations_curves_and_quantization() {
+        assert_eq!(
+            parse("/snapblaster/morph", vec![OscType::Int(5)]),
+            Ok(OscCommand::Morph {
+                to_snap: 5,
+                duration: None,
+                curve: "linear".to_string(),
+                quantize: None,
+            })
+        );
+        assert_eq!(
+            parse(
+                "/snapblaster/morph",
+                vec![
+                    OscType::Int(5),
+                    OscType::String("3/4bar".to_string()),
+                    OscType::String("ease".to_string()),
+                    OscType::String("4bars".to_string()),
+                ]
+            ),
+            Ok(OscCommand::Morph {
+                to_snap: 5,
+                duration: Some(MorphDuration::Bars(0.75)),
+                curve: "ease".to_string(),
+                quantize: Some(LaunchQuantize::Phrase(4)),
+            })
+        );
+        assert_eq!(
+            parse(
+                "/snapblaster/morph",
+                vec![OscType::Int(1), OscType::Float(2.0), OscType::String("linear".to_string()), OscType::Int(1)]
+            ),
+            Ok(OscCommand::Morph {
+                to_snap: 1,
+                duration: Some(MorphDuration::Bars(2.0)),
+                curve: "linear".to_string(),
+                quantize: Some(LaunchQuantize::Bar),
+            })
+        );
+        assert_eq!(
+            parse("/snapblaster/morph", vec![OscType::Int(1), OscType::String("500ms".to_string())]),
+            Ok(OscCommand::Morph {
+                to_snap: 1,
+                duration: Some(MorphDuration::Millis(500)),
+                curve: "linear".to_string(),
+                quantize: None,
+            })
+        );
+
+        assert!(parse("/snapblaster/morph", vec![OscType::Int(1), OscType::String("soon".to_string())]).is_err());
+        assert!(parse("/snapblaster/morph", vec![OscType::Int(1), OscType::Int(1), OscType::Int(2)]).is_err());
+        assert!(parse(
+            "/snapblaster/morph",
+            vec![OscType::Int(1), OscType::Int(1), OscType::String("linear".to_string()), OscType::String("3bars".to_string())]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn register_ports_are_optional_and_clamped() {
+        assert_eq!(parse("/snapblaster/register", vec![]), Ok(OscCommand::Register(None)));
+        assert_eq!(parse("/snapblaster/register", vec![OscType::Int(9001)]), Ok(OscCommand::Register(Some(9001))));
+        assert_eq!(
+            parse("/snapblaster/unregister", vec![OscType::Float(70000.0)]),
+            Ok(OscCommand::Unregister(Some(u16::MAX)))
+        );
+        assert_eq!(parse("/snapblaster/unregister", vec![OscType::Int(0)]), Ok(OscCommand::Unregister(Some(1))));
+        assert!(parse("/snapblaster/register", vec![OscType::String("port".to_string())]).is_err());
+    }
+}
@@ -0,0 +1,512 @@
+// src-tauri/src/runtime.rs
+use crate::events::{Event, EventBus, MorphDuration, MorphLoop};
+use crate::link::{LinkStatus, LinkSynchronizer};
+use crate::midi::controller::Rgb;
+use crate::midi::manager::{MidiManager, PortStatus};
+use crate::model::SharedState;
+use crate::supervisor::{supervise, EventHandler, Flow};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio::time;
+
+/// Deltas kept for clients catching up after a reconnect; runs of morph progress
+/// are kept as one, so these cover minutes of playing rather than seconds
+const HISTORY_LEN: usize = 512;
+
+/// Capacity of the live delta channel
+const DELTA_CAPACITY: usize = 256;
+
+/// How often state that changes without an event (LEDs, ports) is checked
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often the Link session is asked for its status, between Link events
+const LINK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The morph in progress
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MorphStatus {
+    pub from_snap: usize,
+    pub to_snap: usize,
+    pub duration: MorphDuration,
+    pub progress: f64,
+
+    /// Step of the running morph chain, if any
+    pub step: Option<usize>,
+
+    /// How the morph loops, if it does
+    pub looping: Option<MorphLoop>,
+
+    /// Whether the position is set by hand
+    pub manual: bool,
+
+    /// Interpolated values, one per parameter
+    pub values: Vec<u8>,
+}
+
+/// Everything a client needs to draw the app, in one typed value
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RuntimeState {
+    /// Incremented with every delta; a client holding this version can resume from here
+    pub version: u64,
+
+    pub bank: usize,
+    pub snap: usize,
+    pub morph: Option<MorphStatus>,
+
+    /// Morph duration modifier pad held on the controller
+    pub active_modifier: Option<u8>,
+
+    /// Morph duration picked on the controller, in bars
+    pub morph_duration: u8,
+
+    /// Last known Link session status
+    pub link: Option<LinkStatus>,
+
+    pub ports: PortStatus,
+
+    /// Controller LED colors indexed by pad; empty without a controller
+    pub leds: Vec<Rgb>,
+}
+
+impl Default for RuntimeState {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            bank: 0,
+            snap: 0,
+            morph: None,
+            active_modifier: None,
+            morph_duration: 4,
+            link: None,
+            ports: PortStatus::default(),
+            leds: Vec::new(),
+        }
+    }
+}
+
+/// One part of the runtime state that changed
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateChange {
+    Selection { bank: usize, snap: usize },
+    Morph { morph: Option<MorphStatus> },
+    Modifier { active_modifier: Option<u8>, morph_duration: u8 },
+    Link { link: Option<LinkStatus> },
+    Ports { ports: PortStatus },
+    /// Pads whose color changed; a full grid when the controller appears or goes away
+    Leds { pads: Vec<(u8, Rgb)>, count: usize },
+}
+
+/// Changes that take the state from the previous delta's version to `version`;
+/// live deltas are one version apart, kept ones may span several
+#[derive(Clone, Debug, Serialize)]
+pub struct RuntimeDelta {
+    pub version: u64,
+    pub changes: Vec<StateChange>,
+}
+
+/// Message on a runtime state stream
+#[derive(Clone, Debug, Serialize)]
+pub enum RuntimeMessage {
+    /// Full state; clients replace what they have
+    Snapshot(Box<RuntimeState>),
+    /// Apply on top of the previous version
+    Delta(RuntimeDelta),
+}
+
+impl RuntimeMessage {
+    /// Version of the state after applying this message
+    pub fn version(&self) -> u64 {
+        match self {
+            RuntimeMessage::Snapshot(snapshot) => snapshot.version,
+            RuntimeMessage::Delta(delta) => delta.version,
+        }
+    }
+}
+
+struct TrackerInner {
+    current: RuntimeState,
+    history: VecDeque<RuntimeDelta>,
+
+    /// Version the oldest kept delta applies to
+    base: u64,
+}
+
+/// RuntimeTracker keeps the current runtime state and the recent deltas leading to it
+#[derive(Clone)]
+pub struct RuntimeTracker {
+    inner: Arc<Mutex<TrackerInner>>,
+    sender: broadcast::Sender<RuntimeDelta>,
+
+    /// Held while the state is read from its sources, so refreshes commit in order
+    /// without keeping readers of the state waiting on slow sources
+    refreshing: Arc<Mutex<()>>,
+}
+
+impl Default for RuntimeTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RuntimeTracker {
+    /// Create a tracker holding the default state at version 0
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(DELTA_CAPACITY);
+        Self {
+            inner: Arc::new(Mutex::new(TrackerInner {
+                current: RuntimeState::default(),
+                history: VecDeque::with_capacity(HISTORY_LEN),
+                base: 0,
+            })),
+            sender,
+            refreshing: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Keep the state up to date from the bus and the running components
+    pub fn start(
+        &self,
+        event_bus: &EventBus,
+        state: SharedState,
+        midi_manager: Option<Arc<MidiManager>>,
+        link_sync: Option<LinkSynchronizer>,
+    ) -> Vec<JoinHandle<()>> {
+        let tracker = self.clone();
+        let sources = StateSources {
+            state,
+            midi_manager,
+            link: Arc::new(Mutex::new(None)),
+        };
+
+        let poll_tracker = tracker.clone();
+        let poll_sources = sources.clone();
+        let poller = tokio::spawn(async move {
+            let mut ticker = time::interval(POLL_INTERVAL);
+            loop {
+                ticker.tick().await;
+                poll_tracker.refresh(&poll_sources);
+            }
+        });
+
+        // Link is asked on its own timer, so a slow answer holds up neither the poller nor events
+        let link_sources = sources.clone();
+        let link_poller = tokio::spawn(async move {
+            let Some(link_sync) = link_sync else {
+                return;
+            };
+            let mut ticker = time::interval(LINK_POLL_INTERVAL);
+            loop {
+                ticker.tick().await;
+                if let Ok(status) = link_sync.status().await {
+                    *link_sources.link.lock().unwrap() = Some(status);
+                }
+            }
+        });
+
+        let updater = supervise(event_bus, "runtime-state", move || RuntimeUpdater {
+            tracker: tracker.clone(),
+            sources: sources.clone(),
+        });
+
+        vec![poller, link_poller, updater]
+    }
+
+    /// The current state
+    pub fn snapshot(&self) -> RuntimeState {
+        self.inner.lock().unwrap().current.clone()
+    }
+
+    /// Deltas after `version`, oldest first, or None when they are no longer
+    /// kept and the client has to start over from a snapshot
+    pub fn deltas_since(&self, version: u64) -> Option<Vec<RuntimeDelta>> {
+        let inner = self.inner.lock().unwrap();
+        if version > inner.current.version {
+            return None;
+        }
+        if version == inner.current.version {
+            return Some(Vec::new());
+        }
+
+        if version < inner.base {
+            return None;
+        }
+        Some(
+            inner
+                .history
+                .iter()
+                .filter(|delta| delta.version > version)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// What a client resuming from `version` needs: the missed deltas if they
+    /// are still kept, otherwise a snapshot. Without a version, always a snapshot.
+    pub fn resume(&self, version: Option<u64>) -> Vec<RuntimeMessage> {
+        match version.and_then(|version| self.deltas_since(version)) {
+            Some(deltas) => deltas.into_iter().map(RuntimeMessage::Delta).collect(),
+            None => vec![RuntimeMessage::Snapshot(Box::new(self.snapshot()))],
+        }
+    }
+
+    /// Live deltas from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<RuntimeDelta> {
+        self.sender.subscribe()
+    }
+
+    /// Re-read the state from its sources, recording and announcing what changed
+    fn refresh(&self, sources: &StateSources) {
+        let _refreshing = self.refreshing.lock().unwrap();
+        let next = sources.collect();
+        self.commit(next);
+    }
+
+    /// Make `next` the current state, recording and announcing what changed
+    fn commit(&self, mut next: RuntimeState) {
+        let mut inner = self.inner.lock().unwrap();
+        let changes = diff(&inner.current, &next);
+        if changes.is_empty() {
+            return;
+        }
+
+        next.version = inner.current.version + 1;
+        let delta = RuntimeDelta {
+            version: next.version,
+            changes,
+        };
+        inner.current = next;
+
+        // A morph change carries the whole morph, so one in a row of them stands for all
+        // before it; a client behind any of them gets the latest instead
+        let morph_only = |delta: &RuntimeDelta| matches!(delta.changes[..], [StateChange::Morph { .. }]);
+        if morph_only(&delta) && inner.history.back().is_some_and(morph_only) {
+            inner.history.pop_back();
+        } else if inner.history.len() == HISTORY_LEN {
+            if let Some(oldest) = inner.history.pop_front() {
+                inner.base = oldest.version;
+            }
+        }
+        inner.history.push_back(delta.clone());
+
+        // Sent under the lock so subscribers see deltas in version order
+        let _ = self.sender.send(delta);
+    }
+}
+
+/// Where the parts of the runtime state are read from
+#[derive(Clone)]
+struct StateSources {
+    state: SharedState,
+    midi_manager: Option<Arc<MidiManager>>,
+
+    /// Link status as of the last Link event or query
+    link: Arc<Mutex<Option<LinkStatus>>>,
+}
+
+impl StateSources {
+    fn collect(&self) -> RuntimeState {
+        let link = self.link.lock().unwrap().clone();
+        let (ports, leds) = match &self.midi_manager {
+            Some(midi_manager) => (midi_manager.port_status(), midi_manager.led_grid().unwrap_or_default()),
+            None => (PortStatus::default(), Vec::new()),
+        };
+
+        let state_guard = self.state.read().unwrap();
+        RuntimeState {
+            version: 0,
+            bank: state_guard.current_bank,
+            snap: state_guard.current_snap,
+            morph: state_guard.active_morph.as_ref().map(|morph| MorphStatus {
+                from_snap: morph.from_snap,
+                to_snap: morph.to_snap,
+                duration: morph.duration,
+                progress: morph.progress,
+                step: morph.step,
+                looping: morph.looping,
+                manual: morph.manual,
+                values: morph.current_values.clone(),
+            }),
+            active_modifier: state_guard.active_modifier,
+            morph_duration: state_guard.morph_duration,
+            link,
+            ports,
+            leds,
+        }
+    }
+}
+
+/// What changed between two states
+fn diff(old: &RuntimeState, new: &RuntimeState) -> Vec<StateChange> {
+    let mut changes = Vec::new();
+
+    if (old.bank, old.snap) != (new.bank, new.snap) {
+        changes.push(StateChange::Selection {
+            bank: new.bank,
+            snap: new.snap,
+        });
+    }
+    if old.morph != new.morph {
+        changes.push(StateChange::Morph {
+            morph: new.morph.clone(),
+        });
+    }
+    if (old.active_modifier, old.morph_duration) != (new.active_modifier, new.morph_duration) {
+        changes.push(StateChange::Modifier {
+            active_modifier: new.active_modifier,
+            morph_duration: new.morph_duration,
+        });
+    }
+    if old.link != new.link {
+        changes.push(StateChange::Link {
+            link: new.link.clone(),
+        });
+    }
+    if old.ports != new.ports {
+        changes.push(StateChange::Ports {
+            ports: new.ports.clone(),
+        });
+    }
+    if old.leds != new.leds {
+        let pads = if old.leds.len() == new.leds.len() {
+            new.leds
+                .iter()
+                .zip(&old.leds)
+                .enumerate()
+                .filter(|(_, (new, old))| new != old)
+                .map(|(pad, (color, _))| (pad as u8, *color))
+                .collect()
+        } else {
+            new.leds.iter().enumerate().map(|(pad, color)| (pad as u8, *color)).collect()
+        };
+        changes.push(StateChange::Leds {
+            pads,
+            count: new.leds.len(),
+        });
+    }
+
+    changes
+}
+
+/// Refreshes the runtime state after every event
+struct RuntimeUpdater {
+    tracker: RuntimeTracker,
+    sources: StateSources,
+}
+
+impl EventHandler for RuntimeUpdater {
+    async fn handle(&mut self, event: Event) -> Flow {
+        // Until the first Link query answers there is nothing to update
+        if let Some(link) = self.sources.link.lock().unwrap().as_mut() {
+            match event {
+                Event::LinkStatusChanged { connected, peers } => {
+                    link.connected = connected;
+                    link.peers = peers;
+                }
+                Event::LinkTempoChanged { tempo } => link.tempo = tempo,
+                Event::LinkTransportChanged { playing } => link.playing = playing,
+                _ => {}
+            }
+        }
+
+        if let Event::Shutdown = event {
+            return Flow::Stop;
+        }
+
+        self.tracker.refresh(&self.sources);
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, _missed: u64) {
+        self.tracker.refresh(&self.sources);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_snap(snap: usize) -> RuntimeState {
+        RuntimeState {
+            snap,
+            ..RuntimeState::default()
+        }
+    }
+
+    fn with_progress(progress: f64) -> RuntimeState {
+        RuntimeState {
+            morph: Some(MorphStatus {
+                from_snap: 0,
+                to_snap: 1,
+                duration: MorphDuration::Bars(4.0),
+                progress,
+                step: None,
+                looping: None,
+                manual: false,
+                values: Vec::new(),
+            }),
+            ..RuntimeState::default()
+        }
+    }
+
+    #[test]
+    fn deltas_since_returns_what_a_client_missed() {
+        let tracker = RuntimeTracker::new();
+        tracker.commit(with_snap(1));
+        tracker.commit(with_snap(2));
+        tracker.commit(with_snap(2));
+
+        assert_eq!(tracker.snapshot().version, 2);
+        let versions = |since| {
+            tracker
+                .deltas_since(since)
+                .map(|deltas| deltas.iter().map(|delta| delta.version).collect::<Vec<_>>())
+        };
+        assert_eq!(versions(0), Some(vec![1, 2]));
+        assert_eq!(versions(1), Some(vec![2]));
+        assert_eq!(versions(2), Some(vec![]));
+        assert_eq!(versions(3), None);
+    }
+
+    #[test]
+    fn deltas_since_gives_up_on_versions_no_longer_kept() {
+        let tracker = RuntimeTracker::new();
+        for snap in 1..=HISTORY_LEN + 2 {
+            tracker.commit(with_snap(snap));
+        }
+
+        let version = tracker.snapshot().version;
+        assert!(tracker.deltas_since(0).is_none());
+        assert!(tracker.deltas_since(1).is_none());
+        assert_eq!(tracker.deltas_since(2).map(|deltas| deltas.len()), Some(HISTORY_LEN));
+        assert!(matches!(tracker.resume(Some(0))[..], [RuntimeMessage::Snapshot(_)]));
+        assert_eq!(tracker.deltas_since(version - 1).map(|deltas| deltas.len()), Some(1));
+    }
+
+    #[test]
+    fn morph_progress_is_kept_as_one_delta() {
+        let tracker = RuntimeTracker::new();
+        tracker.commit(with_snap(1));
+        for step in 1..=100 {
+            tracker.commit(RuntimeState {
+                snap: 1,
+                ..with_progress(step as f64 / 100.0)
+            });
+        }
+
+        // Every version still resumes, with the latest morph state
+        for since in [0, 1, 50, 100] {
+            let deltas = tracker.deltas_since(since).unwrap();
+            let Some(StateChange::Morph { morph: Some(morph) }) = deltas.last().and_then(|d| d.changes.first()) else {
+                panic!("expected a morph change after version {}", since);
+            };
+            assert_eq!(morph.progress, 1.0);
+        }
+        assert_eq!(tracker.deltas_since(0).unwrap().len(), 2);
+        assert_eq!(tracker.deltas_since(101).unwrap().len(), 0);
+    }
+}
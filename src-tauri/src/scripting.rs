@@ -0,0 +1,600 @@
+// src-tauri/src/scripting.rs
+use crate::control::{parse_curve, ControlService};
+use crate::events::{Event, EventBus, MorphDuration};
+use crate::model::SharedState;
+use crate::storage::ProjectFormat;
+use crate::supervisor::{supervise, EventHandler, Flow};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{CallFnOptions, Dynamic, Engine, Scope, AST};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::{debug, info, warn};
+
+/// How often the scripts directory is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Operations a single hook call may run before it is stopped
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// Actions a single event may trigger across all scripts, to stop runaway feedback
+const MAX_ACTIONS_PER_EVENT: usize = 16;
+
+/// Actions scripts may trigger within `ACTION_WINDOW`, across events, to stop
+/// scripts that keep answering the events their own actions cause
+const MAX_ACTIONS_PER_WINDOW: usize = 64;
+
+/// Window over which `MAX_ACTIONS_PER_WINDOW` applies
+const ACTION_WINDOW: Duration = Duration::from_secs(1);
+
+/// Tempo reported to scripts until Link tells us otherwise
+const DEFAULT_TEMPO: f64 = 120.0;
+
+/// Hooks a script may define, with their parameter counts
+const HOOKS: [(&str, usize); 7] = [
+    ("init", 0),
+    ("on_event", 1),
+    ("on_beat", 1),
+    ("on_bar", 1),
+    ("on_tempo", 1),
+    ("on_snap", 2),
+    ("on_bank", 1),
+];
+
+/// Something a script asked the app to do
+#[derive(Clone, Debug)]
+enum ScriptAction {
+    SelectBank(i64),
+    SelectSnap(i64, i64),
+    /// Duration as text, such as `4`, `3/4bar` or `500ms`
+    MorphTo { snap: i64, duration: Option<String>, curve: String },
+    SetParameter(i64, i64),
+    SetTempo(f64),
+    Transport(bool),
+}
+
+type ActionQueue = Arc<Mutex<Vec<ScriptAction>>>;
+
+/// Directory holding the scripts of a project: `scripts/` inside a directory
+/// project, or `<name>.scripts/` next to a single-file project
+pub fn scripts_dir(project_path: &Path) -> PathBuf {
+    match ProjectFormat::detect(project_path) {
+        ProjectFormat::Directory if project_path.is_dir() => project_path.join("scripts"),
+        ProjectFormat::Directory => project_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("scripts"),
+        ProjectFormat::Json | ProjectFormat::Toml => {
+            let stem = project_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "project".to_string());
+            project_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(format!("{}.scripts", stem))
+        }
+    }
+}
+
+/// Script files and their modification times, to notice edits
+fn fingerprint(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut files: Vec<(PathBuf, Option<SystemTime>)> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .map(|path| {
+                let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+                (path, modified)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
+
+/// A compiled script with the state it keeps between events
+struct LoadedScript {
+    name: String,
+    ast: AST,
+    scope: Scope<'static>,
+
+    /// Bound as `this` in every hook so scripts can keep their own state
+    this: Dynamic,
+
+    /// Hooks the script defines
+    hooks: HashSet<&'static str>,
+}
+
+/// ScriptHost runs the current project's scripts against the event stream
+pub struct ScriptHost {
+    engine: Engine,
+    state: SharedState,
+    event_bus: EventBus,
+    control: ControlService,
+    actions: ActionQueue,
+    tempo: Arc<Mutex<f64>>,
+    scripts: Vec<LoadedScript>,
+
+    /// Start of the current action window and the actions run in it
+    window: (Instant, usize),
+}
+
+impl ScriptHost {
+    /// Create a host with the sandboxed script API; `tempo` is what scripts see as `tempo()`
+    pub fn new(state: SharedState, event_bus: EventBus, control: ControlService, tempo: Arc<Mutex<f64>>) -> Self {
+        let actions: ActionQueue = Arc::new(Mutex::new(Vec::new()));
+        let engine = build_engine(state.clone(), actions.clone(), tempo.clone());
+
+        Self {
+            engine,
+            state,
+            event_bus,
+            control,
+            actions,
+            tempo,
+            scripts: Vec::new(),
+            window: (Instant::now(), 0),
+        }
+    }
+
+    /// Run scripts as a supervised handler, reloading them when their files change
+    pub fn start(state: SharedState, event_bus: EventBus, control: ControlService) -> Vec<JoinHandle<()>> {
+        let watcher = spawn_watcher(state.clone(), event_bus.clone());
+
+        // Start from the Link session's tempo; tempo changes keep it current from here on
+        let tempo = Arc::new(Mutex::new(DEFAULT_TEMPO));
+        let seed = {
+            let tempo = tempo.clone();
+            let control = control.clone();
+            tokio::spawn(async move {
+                match control.link_status().await {
+                    Ok(status) => *tempo.lock().unwrap() = status.tempo,
+                    Err(e) => debug!("Scripts start at {} BPM: {}", DEFAULT_TEMPO, e),
+                }
+            })
+        };
+
+        let bus = event_bus.clone();
+        let host = supervise(&event_bus, "script-host", move || {
+            let mut host = ScriptHost::new(state.clone(), bus.clone(), control.clone(), tempo.clone());
+            host.reload();
+            host
+        });
+
+        vec![watcher, seed, host]
+    }
+
+    /// Load every script of the current project, replacing the running ones
+    fn reload(&mut self) {
+        self.scripts.clear();
+        self.actions.lock().unwrap().clear();
+
+        let project_path = self.state.read().unwrap().project_path.clone();
+        let Some(project_path) = project_path else {
+            return;
+        };
+
+        let dir = scripts_dir(&project_path);
+        let mut loaded = Vec::new();
+        let mut errors = Vec::new();
+
+        for (path, _) in fingerprint(&dir) {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            match self.load_script(&path, &name) {
+                Ok(script) => {
+                    loaded.push(name);
+                    self.scripts.push(script);
+                }
+                Err(e) => {
+                    warn!("Failed to load script {}: {}", name, e);
+                    errors.push(format!("{}: {}", name, e));
+                }
+            }
+        }
+
+        if !loaded.is_empty() || !errors.is_empty() {
+            info!("Loaded {} scripts from {:?}", loaded.len(), dir);
+        }
+
+        // Run init hooks, which may queue actions like any other hook
+        for idx in 0..self.scripts.len() {
+            self.call_hook(idx, "init", ());
+        }
+
+        self.event_bus.try_publish(Event::ScriptsLoaded {
+            scripts: loaded,
+            errors,
+        });
+    }
+
+    fn load_script(&self, path: &Path, name: &str) -> Result<LoadedScript, String> {
+        let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let ast = self.engine.compile(&source).map_err(|e| e.to_string())?;
+
+        let hooks = HOOKS
+            .iter()
+            .filter(|(hook, params)| {
+                ast.iter_functions()
+                    .any(|f| f.name == *hook && f.params.len() == *params)
+            })
+            .map(|(hook, _)| *hook)
+            .collect();
+
+        // Top-level statements run once, when the script is loaded
+        let mut scope = Scope::new();
+        self.engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| e.to_string())?;
+
+        Ok(LoadedScript {
+            name: name.to_string(),
+            ast,
+            scope,
+            this: Dynamic::from_map(rhai::Map::new()),
+            hooks,
+        })
+    }
+
+    fn call_hook(&mut self, idx: usize, hook: &str, args: impl rhai::FuncArgs + Clone) {
+        let script = &mut self.scripts[idx];
+        if !script.hooks.contains(hook) {
+            return;
+        }
+
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .rewind_scope(true)
+            .bind_this_ptr(&mut script.this);
+
+        if let Err(e) = self
+            .engine
+            .call_fn_with_options::<Dynamic>(options, &mut script.scope, &script.ast, hook, args)
+        {
+            warn!("Script {} failed in {}: {}", script.name, hook, e);
+        }
+    }
+
+    /// Call a hook on every script that defines it
+    fn call_all(&mut self, hook: &str, args: impl rhai::FuncArgs + Clone) {
+        for idx in 0..self.scripts.len() {
+            self.call_hook(idx, hook, args.clone());
+        }
+    }
+
+    /// Run the actions scripts queued while handling an event
+    async fn run_actions(&mut self) {
+        let actions: Vec<ScriptAction> = std::mem::take(&mut *self.actions.lock().unwrap());
+        if actions.is_empty() {
+            return;
+        }
+        if actions.len() > MAX_ACTIONS_PER_EVENT {
+            warn!(
+                "Scripts queued {} actions for one event, running only the first {}",
+                actions.len(),
+                MAX_ACTIONS_PER_EVENT
+            );
+        }
+
+        if self.window.0.elapsed() >= ACTION_WINDOW {
+            self.window = (Instant::now(), 0);
+        }
+        let allowed = MAX_ACTIONS_PER_WINDOW.saturating_sub(self.window.1);
+        let count = actions.len().min(MAX_ACTIONS_PER_EVENT);
+        if count > allowed {
+            warn!(
+                "Scripts ran {} actions within {:?}, dropping {} more; do they answer their own events?",
+                self.window.1,
+                ACTION_WINDOW,
+                count - allowed
+            );
+        }
+        self.window.1 += count.min(allowed);
+
+        for action in actions.into_iter().take(count.min(allowed)) {
+            debug!("Script action: {:?}", action);
+            let result = match &action {
+                ScriptAction::SelectBank(bank) => index(*bank).and_then(|bank| self.control.select_bank(bank)),
+                ScriptAction::SelectSnap(bank, snap) => index(*bank)
+                    .and_then(|bank| Ok((bank, index(*snap)?)))
+                    .and_then(|(bank, snap)| self.control.select_snap(bank, snap)),
+                ScriptAction::MorphTo { snap, duration, curve } => index(*snap).and_then(|snap| {
+                    let duration = duration.as_deref().map(str::parse::<MorphDuration>).transpose()?;
+                    self.control.morph_to(snap, duration, parse_curve(curve), None)
+                }),
+                ScriptAction::SetParameter(param, value) => index(*param)
+                    .and_then(|param| self.control.set_parameter(param, (*value).clamp(0, 127) as u8)),
+                ScriptAction::SetTempo(tempo) => self.control.set_tempo(*tempo).await,
+                ScriptAction::Transport(playing) => self.control.set_transport(*playing).await,
+            };
+
+            if let Err(e) = result {
+                warn!("Script action {:?} failed: {}", action, e);
+            }
+        }
+    }
+}
+
+impl EventHandler for ScriptHost {
+    async fn handle(&mut self, event: Event) -> Flow {
+        match &event {
+            Event::ProjectLoaded | Event::ScriptsChanged => {
+                self.reload();
+                self.run_actions().await;
+                return Flow::Continue;
+            }
+            Event::Shutdown => return Flow::Stop,
+            Event::LinkTempoChanged { tempo } => *self.tempo.lock().unwrap() = *tempo,
+            // Scripts' own bookkeeping and high-rate output never reach them
+            Event::ScriptsLoaded { .. }
+            | Event::CCValueChanged { .. }
+            | Event::MorphProgressed { .. }
+            | Event::MorphPositionInput { .. }
+            | Event::ControlInput { .. }
+            | Event::PadPressure { .. }
+            | Event::CommandIssued { .. }
+            | Event::MetricsReported { .. } => return Flow::Continue,
+            _ => {}
+        }
+
+        if self.scripts.is_empty() {
+            return Flow::Continue;
+        }
+
+        let event_map = event_to_dynamic(&event);
+        self.call_all("on_event", (event_map,));
+
+        match event {
+            Event::BeatOccurred { beat, .. } => self.call_all("on_beat", (beat as i64,)),
+            Event::BarOccurred { bar } => self.call_all("on_bar", (bar as i64,)),
+            Event::LinkTempoChanged { tempo } => self.call_all("on_tempo", (tempo,)),
+            Event::SnapSelected { bank, snap_id } => self.call_all("on_snap", (bank as i64, snap_id as i64)),
+            Event::BankSelected { bank_id } => self.call_all("on_bank", (bank_id as i64,)),
+            _ => {}
+        }
+
+        self.run_actions().await;
+        Flow::Continue
+    }
+}
+
+/// Present an event to scripts as a map with a `type` field and the event's fields
+fn event_to_dynamic(event: &Event) -> Dynamic {
+    let mut map = rhai::Map::new();
+    map.insert("type".into(), event.event_type().into());
+
+    if let Ok(serde_json::Value::Object(outer)) = serde_json::to_value(event) {
+        if let Some(serde_json::Value::Object(fields)) = outer.into_iter().next().map(|(_, v)| v) {
+            for (key, value) in fields {
+                if let Ok(value) = rhai::serde::to_dynamic(&value) {
+                    map.insert(key.into(), value);
+                }
+            }
+        }
+    }
+
+    Dynamic::from_map(map)
+}
+
+fn index(value: i64) -> Result<usize, String> {
+    usize::try_from(value).map_err(|_| format!("{} is not a valid index", value))
+}
+
+/// Build an engine whose only view of the world is the functions registered here
+fn build_engine(state: SharedState, actions: ActionQueue, tempo: Arc<Mutex<f64>>) -> Engine {
+    let mut engine = Engine::new();
+
+    // Scripts run inline with the event stream; keep them short and self-contained
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(32);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(16 * 1024);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    engine.disable_symbol("eval");
+    // The default resolver would let `import` load any file on disk
+    engine.set_module_resolver(DummyModuleResolver::new());
+
+    engine.on_print(|text| info!(target: "script", "{}", text));
+    engine.on_debug(|text, source, pos| debug!(target: "script", "{:?} {:?}: {}", source, pos, text));
+
+    // Actions are queued and run once the hook returns
+    let queue = actions.clone();
+    engine.register_fn("select_bank", move |bank: i64| {
+        queue.lock().unwrap().push(ScriptAction::SelectBank(bank));
+    });
+    let queue = actions.clone();
+    engine.register_fn("select_snap", move |bank: i64, snap: i64| {
+        queue.lock().unwrap().push(ScriptAction::SelectSnap(bank, snap));
+    });
+    let queue = actions.clone();
+    let query_state = state.clone();
+    engine.register_fn("select_snap", move |snap: i64| {
+        let bank = query_state.read().unwrap().current_bank as i64;
+        queue.lock().unwrap().push(ScriptAction::SelectSnap(bank, snap));
+    });
+    let queue = actions.clone();
+    engine.register_fn("morph_to", move |snap: i64| {
+        queue.lock().unwrap().push(ScriptAction::MorphTo {
+            snap,
+            duration: None,
+            curve: "linear".to_string(),
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn("morph_to", move |snap: i64, bars: i64| {
+        queue.lock().unwrap().push(ScriptAction::MorphTo {
+            snap,
+            duration: Some(bars.to_string()),
+            curve: "linear".to_string(),
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn("morph_to", move |snap: i64, bars: i64, curve: &str| {
+        queue.lock().unwrap().push(ScriptAction::MorphTo {
+            snap,
+            duration: Some(bars.to_string()),
+            curve: curve.to_string(),
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn("morph_to", move |snap: i64, duration: &str| {
+        queue.lock().unwrap().push(ScriptAction::MorphTo {
+            snap,
+            duration: Some(duration.to_string()),
+            curve: "linear".to_string(),
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn("morph_to", move |snap: i64, duration: &str, curve: &str| {
+        queue.lock().unwrap().push(ScriptAction::MorphTo {
+            snap,
+            duration: Some(duration.to_string()),
+            curve: curve.to_string(),
+        });
+    });
+    let queue = actions.clone();
+    engine.register_fn("set_param", move |param: i64, value: i64| {
+        queue.lock().unwrap().push(ScriptAction::SetParameter(param, value));
+    });
+    let queue = actions.clone();
+    engine.register_fn("set_tempo", move |bpm: f64| {
+        queue.lock().unwrap().push(ScriptAction::SetTempo(bpm));
+    });
+    let queue = actions.clone();
+    engine.register_fn("set_tempo", move |bpm: i64| {
+        queue.lock().unwrap().push(ScriptAction::SetTempo(bpm as f64));
+    });
+    let queue = actions.clone();
+    engine.register_fn("start_transport", move || {
+        queue.lock().unwrap().push(ScriptAction::Transport(true));
+    });
+    let queue = actions;
+    engine.register_fn("stop_transport", move || {
+        queue.lock().unwrap().push(ScriptAction::Transport(false));
+    });
+
+    // Read-only queries against the project state
+    let s = state.clone();
+    engine.register_fn("current_bank", move || s.read().unwrap().current_bank as i64);
+    let s = state.clone();
+    engine.register_fn("current_snap", move || s.read().unwrap().current_snap as i64);
+    let s = state.clone();
+    engine.register_fn("bank_count", move || s.read().unwrap().project.banks.len() as i64);
+    let s = state.clone();
+    engine.register_fn("bank_name", move |bank: i64| {
+        let guard = s.read().unwrap();
+        usize::try_from(bank)
+            .ok()
+            .and_then(|bank| guard.project.banks.get(bank))
+            .map(|bank| bank.name.clone())
+            .unwrap_or_default()
+    });
+    let s = state.clone();
+    engine.register_fn("snap_count", move |bank: i64| {
+        let guard = s.read().unwrap();
+        usize::try_from(bank)
+            .ok()
+            .and_then(|bank| guard.project.banks.get(bank))
+            .map_or(0, |bank| bank.snaps.len() as i64)
+    });
+    let s = state.clone();
+    engine.register_fn("snap_name", move |bank: i64, snap: i64| {
+        let guard = s.read().unwrap();
+        usize::try_from(bank)
+            .ok()
+            .and_then(|bank| guard.project.banks.get(bank))
+            .zip(usize::try_from(snap).ok())
+            .and_then(|(bank, snap)| bank.snaps.get(snap))
+            .map(|snap| snap.name.clone())
+            .unwrap_or_default()
+    });
+    let s = state.clone();
+    engine.register_fn("param_count", move || s.read().unwrap().project.parameters.len() as i64);
+    let s = state.clone();
+    engine.register_fn("param_value", move |param: i64| {
+        let guard = s.read().unwrap();
+        let Ok(param) = usize::try_from(param) else {
+            return -1;
+        };
+        guard
+            .project
+            .banks
+            .get(guard.current_bank)
+            .and_then(|bank| bank.snaps.get(guard.current_snap))
+            .and_then(|snap| snap.values.get(param))
+            .map_or(-1, |value| *value as i64)
+    });
+    let s = state;
+    engine.register_fn("is_morphing", move || s.read().unwrap().active_morph.is_some());
+    engine.register_fn("tempo", move || *tempo.lock().unwrap());
+
+    engine
+}
+
+/// Watch the scripts directory of whatever project is open and announce changes
+fn spawn_watcher(state: SharedState, event_bus: EventBus) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last = None;
+        let mut ticker = time::interval(WATCH_INTERVAL);
+
+        loop {
+            ticker.tick().await;
+
+            let project_path = state.read().unwrap().project_path.clone();
+            let current = project_path.map(|path| {
+                let dir = scripts_dir(&path);
+                let files = fingerprint(&dir);
+                (dir, files)
+            });
+
+            // The first look only sets the baseline, as does a switch to another project:
+            // the host loads scripts when it starts and on ProjectLoaded
+            let same_dir = matches!((&last, &current), (Some((last_dir, _)), Some((dir, _))) if last_dir == dir);
+            if same_dir && current != last {
+                debug!("Scripts changed, reloading");
+                event_bus.try_publish(Event::ScriptsChanged);
+            }
+            last = current;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::new_shared_state;
+
+    fn engine() -> Engine {
+        build_engine(new_shared_state(), Arc::new(Mutex::new(Vec::new())), Arc::new(Mutex::new(120.0)))
+    }
+
+    #[test]
+    fn scripts_cannot_import_files() {
+        let dir = std::env::temp_dir().join(format!("snapblaster-import-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let module = dir.join("module.rhai");
+        fs::write(&module, "fn secret() { 42 }").unwrap();
+
+        let script = format!("import {:?} as m; m::secret()", module.with_extension("").to_string_lossy());
+        let result = engine().eval::<i64>(&script);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(result.is_err(), "import loaded {:?}", result);
+    }
+
+    #[test]
+    fn scripts_cannot_eval() {
+        assert!(engine().compile(r#"eval("1 + 1")"#).is_err());
+    }
+}
@@ -0,0 +1,331 @@
+// src-tauri/src/session.rs
+use crate::api::ApiSettings;
+use crate::mcp::McpSettings;
+use crate::events::{Event, EventBus};
+use crate::link::LinkSynchronizer;
+use crate::midi::manager::{MidiManager, MidiPortSelection};
+use crate::model::SharedState;
+use crate::osc::OscSettings;
+use crate::supervisor::{supervise, EventHandler, Flow};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time;
+use tracing::{debug, info, warn};
+
+/// Maximum number of recent projects remembered
+const MAX_RECENT_PROJECTS: usize = 10;
+
+/// How long to wait after a state change before writing the session to disk
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(1000);
+
+/// Runtime state that is restored on startup
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Session {
+    /// Project that was open
+    pub project_path: Option<PathBuf>,
+
+    /// Selected bank
+    pub current_bank: usize,
+
+    /// Selected snap
+    pub current_snap: usize,
+
+    /// Morph duration in bars
+    pub morph_duration: u8,
+
+    /// Link tempo in BPM
+    pub link_tempo: Option<f64>,
+
+    /// Link quantum (beats per bar)
+    pub link_quantum: Option<f64>,
+
+    /// Hardware MIDI ports the controller was connected to
+    #[serde(default)]
+    pub midi_ports: MidiPortSelection,
+}
+
+/// Application settings that live outside any project
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Most recently used project files, newest first
+    #[serde(default)]
+    pub recent_projects: Vec<PathBuf>,
+
+    /// Reopen the last project and restore its session on startup
+    #[serde(default = "default_true")]
+    pub restore_session: bool,
+
+    /// The session as it was when the app last ran
+    #[serde(default)]
+    pub session: Option<Session>,
+
+    /// OSC control server
+    #[serde(default)]
+    pub osc: OscSettings,
+
+    /// HTTP and WebSocket control API
+    #[serde(default)]
+    pub api: ApiSettings,
+
+    /// Model Context Protocol server for AI agents
+    #[serde(default)]
+    pub mcp: McpSettings,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            recent_projects: Vec::new(),
+            restore_session: true,
+            session: None,
+            osc: OscSettings::default(),
+            api: ApiSettings::default(),
+            mcp: McpSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Move a project to the top of the recent list
+    pub fn add_recent_project(&mut self, path: &Path) {
+        self.recent_projects.retain(|recent| recent != path);
+        self.recent_projects.insert(0, path.to_path_buf());
+        self.recent_projects.truncate(MAX_RECENT_PROJECTS);
+    }
+}
+
+/// SessionStore reads and writes the settings file
+#[derive(Clone, Debug)]
+pub struct SessionStore {
+    path: PathBuf,
+}
+
+impl SessionStore {
+    /// Create a store backed by a specific file
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Create a store in the platform's configuration directory
+    pub fn default_location() -> Option<Self> {
+        dirs::config_dir().map(|dir| Self::new(dir.join("snapblaster").join("settings.json")))
+    }
+
+    /// Directory containing the settings file
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or_else(|| Path::new("."))
+    }
+
+    /// Load settings, falling back to defaults if the file is missing or unreadable
+    pub fn load(&self) -> AppSettings {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable settings file {:?}: {}", self.path, e);
+                AppSettings::default()
+            }),
+            Err(_) => AppSettings::default(),
+        }
+    }
+
+    /// Write settings to disk
+    pub fn save(&self, settings: &AppSettings) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(self.dir())?;
+        fs::write(&self.path, serde_json::to_string_pretty(settings)?)?;
+        debug!("Settings saved to {:?}", self.path);
+        Ok(())
+    }
+
+    /// Capture the current session from the running components
+    pub async fn capture(
+        state: &SharedState,
+        link_sync: Option<&LinkSynchronizer>,
+        midi_manager: Option<&Arc<MidiManager>>,
+    ) -> Session {
+        let (project_path, current_bank, current_snap, morph_duration) = {
+            let state_guard = state.read().unwrap();
+            (
+                state_guard.project_path.clone(),
+                state_guard.current_bank,
+                state_guard.current_snap,
+                state_guard.morph_duration,
+            )
+        };
+
+        let (link_tempo, link_quantum) = match link_sync {
+            Some(link_sync) => (Some(link_sync.tempo().await), Some(link_sync.quantum().await)),
+            None => (None, None),
+        };
+
+        Session {
+            project_path,
+            current_bank,
+            current_snap,
+            morph_duration,
+            link_tempo,
+            link_quantum,
+            midi_ports: midi_manager.map(|m| m.port_selection()).unwrap_or_default(),
+        }
+    }
+
+    /// Keep the session and recent projects up to date as the app runs
+    pub fn start_recorder(
+        &self,
+        state: SharedState,
+        event_bus: EventBus,
+        link_sync: Option<LinkSynchronizer>,
+        midi_manager: Option<Arc<MidiManager>>,
+    ) -> JoinHandle<()> {
+        let store = self.clone();
+        info!("Session recorder started");
+
+        supervise(&event_bus, "session-recorder", move || SessionRecorder {
+            store: store.clone(),
+            state: state.clone(),
+            link_sync: link_sync.clone(),
+            midi_manager: midi_manager.clone(),
+            pending: None,
+        })
+    }
+
+    /// Capture the session and write it along with the recent projects list
+    async fn record(
+        &self,
+        state: &SharedState,
+        link_sync: Option<&LinkSynchronizer>,
+        midi_manager: Option<&Arc<MidiManager>>,
+    ) {
+        let mut session = Self::capture(state, link_sync, midi_manager).await;
+        let mut settings = self.load();
+        match (&session.project_path, &settings.session) {
+            (Some(path), _) => settings.add_recent_project(path),
+            // Until a project file is open, e.g. after the last one failed to reopen,
+            // keep pointing at it rather than forget it
+            (None, Some(previous)) if previous.project_path.is_some() => {
+                session.project_path = previous.project_path.clone();
+                session.current_bank = previous.current_bank;
+                session.current_snap = previous.current_snap;
+            }
+            (None, _) => {}
+        }
+        settings.session = Some(session);
+
+        if let Err(e) = self.save(&settings) {
+            warn!("Failed to save session: {}", e);
+        }
+    }
+}
+
+/// Writes the session to disk shortly after anything it covers changes
+struct SessionRecorder {
+    store: SessionStore,
+    state: SharedState,
+    link_sync: Option<LinkSynchronizer>,
+    midi_manager: Option<Arc<MidiManager>>,
+
+    /// Debounced write waiting to run
+    pending: Option<JoinHandle<()>>,
+}
+
+impl SessionRecorder {
+    /// Schedule a write unless one is already waiting; it will see the latest state anyway
+    fn schedule_save(&mut self) {
+        if self.pending.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+
+        let store = self.store.clone();
+        let state = self.state.clone();
+        let link_sync = self.link_sync.clone();
+        let midi_manager = self.midi_manager.clone();
+
+        // Let bursts of changes settle before writing
+        self.pending = Some(tokio::spawn(async move {
+            time::sleep(SAVE_DEBOUNCE).await;
+            store.record(&state, link_sync.as_ref(), midi_manager.as_ref()).await;
+        }));
+    }
+}
+
+impl EventHandler for SessionRecorder {
+    async fn handle(&mut self, event: Event) -> Flow {
+        if matches!(event, Event::Shutdown) {
+            // Never delay a shutdown: write now instead of waiting for the debounce
+            if let Some(task) = self.pending.take() {
+                task.abort();
+            }
+            self.store
+                .record(&self.state, self.link_sync.as_ref(), self.midi_manager.as_ref())
+                .await;
+
+            info!("Session recorder stopped");
+            return Flow::Stop;
+        }
+
+        if affects_session(&event) {
+            self.schedule_save();
+        }
+
+        Flow::Continue
+    }
+
+    async fn resync(&mut self, _missed: u64) {
+        // Whatever was missed may have changed the session
+        self.schedule_save();
+    }
+}
+
+/// Events after which the stored session is out of date
+fn affects_session(event: &Event) -> bool {
+    matches!(
+        event,
+        Event::ProjectLoaded
+            | Event::ProjectSaved
+            | Event::SnapSelected { .. }
+            | Event::BankSelected { .. }
+            | Event::MorphDurationChanged { .. }
+            | Event::MorphCompleted
+            | Event::LinkTempoChanged { .. }
+            | Event::LinkQuantumChanged { .. }
+            | Event::MidiPortsChanged
+            | Event::ControllerChanged { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::new_shared_state;
+
+    #[tokio::test]
+    async fn a_session_without_a_project_keeps_the_last_one() {
+        let dir = std::env::temp_dir().join(format!("snapblaster-session-{}", std::process::id()));
+        let store = SessionStore::new(dir.join("settings.json"));
+        let state = new_shared_state();
+
+        // The last run had a project open
+        state.write().unwrap().project_path = Some(PathBuf::from("/Volumes/Gig/set.json"));
+        state.write().unwrap().current_snap = 3;
+        store.record(&state, None, None).await;
+
+        // This run could not reopen it, but the tempo and ports are still recorded
+        state.write().unwrap().project_path = None;
+        state.write().unwrap().current_snap = 0;
+        store.record(&state, None, None).await;
+        let settings = store.load();
+        let _ = fs::remove_dir_all(&dir);
+
+        let session = settings.session.unwrap();
+        assert_eq!(session.project_path, Some(PathBuf::from("/Volumes/Gig/set.json")));
+        assert_eq!(session.current_snap, 3);
+        assert_eq!(settings.recent_projects, vec![PathBuf::from("/Volumes/Gig/set.json")]);
+    }
+}
@@ -1,64 +1,124 @@
 use crate::events::{Event, EventBus};
+use crate::exchange::{self, BankExport, ImportReport, MergeReport};
 use crate::model::{Project, SharedState};
+use crate::templates::{self, ProjectTemplate, TemplateInfo, TemplateStore};
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufReader, BufWriter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
+/// Manifest file at the root of a directory project
+const MANIFEST_FILE: &str = "project.toml";
+
+/// Parameter definitions file inside a directory project
+const PARAMETERS_FILE: &str = "parameters.toml";
+
+/// Sub-directory holding one file per bank
+const BANKS_DIR: &str = "banks";
+
+/// On-disk layout of a project
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProjectFormat {
+    /// A single pretty-printed JSON file
+    Json,
+    /// A single TOML file
+    Toml,
+    /// A directory with a TOML manifest, a parameters file and one file per bank
+    Directory,
+}
+
+impl ProjectFormat {
+    /// Detect the format of an existing project on disk
+    pub fn detect(path: &Path) -> Self {
+        if path.is_dir() || path.file_name().is_some_and(|name| name == MANIFEST_FILE) {
+            ProjectFormat::Directory
+        } else if is_toml(path) {
+            ProjectFormat::Toml
+        } else {
+            ProjectFormat::Json
+        }
+    }
+
+    /// Pick the format to save to: existing directories and paths without an
+    /// extension use the directory layout, `.toml` files are written as TOML
+    /// and everything else as JSON
+    pub fn for_path(path: &Path) -> Self {
+        if path.is_dir() || path.extension().is_none() {
+            ProjectFormat::Directory
+        } else {
+            ProjectFormat::detect(path)
+        }
+    }
+}
+
 /// ProjectStorage handles saving and loading project files
 pub struct ProjectStorage {
     state: SharedState,
     event_bus: EventBus,
+    templates: Option<TemplateStore>,
 }
 
 impl ProjectStorage {
     /// Create a new project storage service
     pub fn new(state: SharedState, event_bus: EventBus) -> Self {
-        Self { state, event_bus }
+        Self {
+            state,
+            event_bus,
+            templates: None,
+        }
+    }
+
+    /// Use a directory for user templates
+    pub fn set_template_store(&mut self, templates: TemplateStore) {
+        self.templates = Some(templates);
     }
 
-    /// Save the current project to a file
+    /// Save the current project, choosing the format from the path
     pub fn save_project(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        self.save_project_as(path, ProjectFormat::for_path(path))
+    }
+
+    /// Save the current project in an explicit format
+    pub fn save_project_as(&self, path: &Path, format: ProjectFormat) -> Result<(), Box<dyn Error>> {
         let state_guard = self.state.read().unwrap();
 
         // Make a fresh clone of the project to ensure we have all the data
         let project = state_guard.project.clone();
+        drop(state_guard);
 
         // Ensure we're getting the parameters and all data from the shared state
         debug!("Saving project with {} parameters", project.parameters.len());
         debug!("Current project state: {:?}", project);
 
-        // Create the file
-        let file = File::create(path)?;
-        let writer = BufWriter::new(file);
+        match format {
+            ProjectFormat::Json => write_json(&project, path)?,
+            ProjectFormat::Toml => write_toml(&project, path)?,
+            ProjectFormat::Directory => write_directory(&project, &project_dir(path))?,
+        }
 
-        // Serialize and write the project
-        serde_json::to_writer_pretty(writer, &project)?;
+        self.state.write().unwrap().project_path = Some(path.to_path_buf());
 
         // Publish event that project was saved
         let _ = self.event_bus.publish(Event::ProjectSaved);
 
-        debug!("Project saved successfully to {:?}", path);
+        debug!("Project saved successfully to {:?} as {:?}", path, format);
         Ok(())
     }
 
-    /// Load a project from a file
+    /// Load a project from a JSON file or a project directory
     pub fn load_project(&self, path: &Path) -> Result<(), Box<dyn Error>> {
-        // Open the file
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-
-        // Deserialize the project
-        let project: Project = serde_json::from_reader(reader)?;
+        let project = read_project(path)?;
 
         // Update the state
         {
             let mut state_guard = self.state.write().unwrap();
             state_guard.project = project;
+            state_guard.project_path = Some(path.to_path_buf());
             state_guard.current_bank = 0;
             state_guard.current_snap = 0;
             state_guard.active_morph = None;
+            state_guard.pending_launch = None;
         }
 
         // Publish event that project was loaded
@@ -67,13 +127,21 @@ impl ProjectStorage {
         Ok(())
     }
 
-    /// Create a new empty project
-    pub fn new_project(&self) -> Result<(), Box<dyn Error>> {
+    /// Create a new project from a template, or the empty template if none is given
+    pub fn new_project(&self, template: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let name = template.unwrap_or(templates::DEFAULT_TEMPLATE);
+        let template = self
+            .find_template(name)
+            .ok_or_else(|| format!("Unknown template: {}", name))?;
+        debug!("Creating new project from template '{}'", template.name);
+
         let mut state_guard = self.state.write().unwrap();
-        state_guard.project = Project::default();
+        state_guard.project = template.instantiate();
+        state_guard.project_path = None;
         state_guard.current_bank = 0;
         state_guard.current_snap = 0;
         state_guard.active_morph = None;
+        state_guard.pending_launch = None;
 
         // Publish event that project was loaded (new projects are treated as "loaded")
         drop(state_guard); // Release the lock before publishing event
@@ -81,4 +149,477 @@ impl ProjectStorage {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// List the templates available for new projects
+    pub fn list_templates(&self) -> Vec<TemplateInfo> {
+        match &self.templates {
+            Some(store) => store.list(),
+            None => templates::builtin_templates()
+                .into_iter()
+                .map(|t| TemplateInfo {
+                    name: t.name,
+                    description: t.description,
+                    builtin: true,
+                })
+                .collect(),
+        }
+    }
+
+    /// Save the current project as a user template
+    pub fn save_template(
+        &self,
+        name: &str,
+        description: &str,
+        reset_values: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        let store = self
+            .templates
+            .as_ref()
+            .ok_or("No template directory available")?;
+
+        let template = {
+            let state_guard = self.state.read().unwrap();
+            ProjectTemplate::from_project(name, description, &state_guard.project, reset_values)
+        };
+
+        store.save(&template)
+    }
+
+    fn find_template(&self, name: &str) -> Option<ProjectTemplate> {
+        match &self.templates {
+            Some(store) => store.find(name),
+            None => templates::builtin_templates()
+                .into_iter()
+                .find(|t| t.name == name),
+        }
+    }
+
+    /// Export a bank, with the parameters it refers to, to a JSON or TOML file
+    pub fn export_bank(&self, bank_id: usize, path: &Path) -> Result<(), Box<dyn Error>> {
+        let export = {
+            let state_guard = self.state.read().unwrap();
+            exchange::export_bank(&state_guard.project, bank_id)?
+        };
+
+        if is_toml(path) {
+            fs::write(path, toml::to_string(&export)?)?;
+        } else {
+            let writer = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(writer, &export)?;
+        }
+
+        debug!("Exported bank {} to {:?}", bank_id, path);
+        Ok(())
+    }
+
+    /// Import a bank file into the current project, appending it as a new bank
+    pub fn import_bank(&self, path: &Path) -> Result<ImportReport, Box<dyn Error>> {
+        let export: BankExport = if is_toml(path) {
+            toml::from_str(&fs::read_to_string(path)?)?
+        } else {
+            serde_json::from_reader(BufReader::new(File::open(path)?))?
+        };
+
+        let report = {
+            let mut state_guard = self.state.write().unwrap();
+            exchange::import_bank(&mut state_guard.project, export)?
+        };
+
+        for conflict in &report.conflicts {
+            debug!("Bank import conflict: {}", conflict.reason);
+        }
+        for note in &report.skipped {
+            debug!("Bank import: {}", note);
+        }
+
+        let _ = self.event_bus.publish(Event::BankImported {
+            bank_id: report.bank_id,
+        });
+
+        Ok(report)
+    }
+
+    /// Merge another project file into the current project.
+    /// With `dry_run` nothing changes and the report previews the merge.
+    pub fn merge_project(&self, path: &Path, dry_run: bool) -> Result<MergeReport, Box<dyn Error>> {
+        let other = read_project(path)?;
+
+        let report = {
+            let mut state_guard = self.state.write().unwrap();
+            exchange::merge_projects(&mut state_guard.project, &other, dry_run)?
+        };
+
+        if !dry_run {
+            let _ = self.event_bus.publish(Event::ProjectMerged {
+                banks_added: report.added_banks.len(),
+            });
+        }
+
+        debug!(
+            "Merged {:?} (dry run: {}): {} parameters added, {} banks added, {} conflicts",
+            path,
+            dry_run,
+            report.added_parameters.len(),
+            report.added_banks.len(),
+            report.conflicts.len()
+        );
+        Ok(report)
+    }
+}
+
+fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "toml")
+}
+
+/// Read a project from disk, detecting its format
+pub fn read_project(path: &Path) -> Result<Project, Box<dyn Error>> {
+    match ProjectFormat::detect(path) {
+        ProjectFormat::Json => read_json(path),
+        ProjectFormat::Toml => read_toml(path),
+        ProjectFormat::Directory => read_directory(&project_dir(path)),
+    }
+}
+
+/// Resolve the project directory, accepting a path to its manifest as well
+fn project_dir(path: &Path) -> PathBuf {
+    if path.file_name().is_some_and(|name| name == MANIFEST_FILE) {
+        path.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn write_json(project: &Project, path: &Path) -> Result<(), Box<dyn Error>> {
+    let file = File::create(path)?;
+    let writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(writer, project)?;
+    Ok(())
+}
+
+fn read_json(path: &Path) -> Result<Project, Box<dyn Error>> {
+    let file = File::open(path)?;
+    let reader = BufReader::new(file);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+fn write_toml(project: &Project, path: &Path) -> Result<(), Box<dyn Error>> {
+    fs::write(path, toml::to_string_pretty(project)?)?;
+    Ok(())
+}
+
+fn read_toml(path: &Path) -> Result<Project, Box<dyn Error>> {
+    Ok(toml::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Write a project as a directory:
+///
+/// ```text
+/// my-set/
+///   project.toml        everything except parameters and banks, plus the bank file order
+///   parameters.toml     [[parameters]] tables
+///   banks/01-drop.toml  one file per bank
+/// ```
+fn write_directory(project: &Project, dir: &Path) -> Result<(), Box<dyn Error>> {
+    let banks_dir = dir.join(BANKS_DIR);
+    fs::create_dir_all(&banks_dir)?;
+
+    // One file per bank, prefixed with its position so a directory listing matches the project
+    let mut bank_files = Vec::with_capacity(project.banks.len());
+    for (idx, bank) in project.banks.iter().enumerate() {
+        let file_name = format!("{:02}-{}.toml", idx + 1, slugify(&bank.name, "bank"));
+
+        // Non-pretty output keeps each snap's values on a single line
+        fs::write(banks_dir.join(&file_name), toml::to_string(bank)?)?;
+        bank_files.push(format!("{}/{}", BANKS_DIR, file_name));
+    }
+
+    // Remove bank files left behind by renamed or deleted banks
+    for entry in fs::read_dir(&banks_dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let relative = format!("{}/{}", BANKS_DIR, file_name);
+        if file_name.ends_with(".toml") && !bank_files.contains(&relative) {
+            debug!("Removing stale bank file {:?}", entry.path());
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    // Parameters live in their own file
+    let mut parameters = toml::Table::new();
+    parameters.insert(
+        "parameters".to_string(),
+        toml::Value::try_from(&project.parameters)?,
+    );
+    fs::write(dir.join(PARAMETERS_FILE), toml::to_string_pretty(&parameters)?)?;
+
+    // The manifest holds the remaining project fields, so new fields are saved automatically
+    let mut manifest = match toml::Value::try_from(project)? {
+        toml::Value::Table(table) => table,
+        _ => return Err("Project did not serialize to a TOML table".into()),
+    };
+    manifest.remove("parameters");
+    manifest.insert(
+        "banks".to_string(),
+        toml::Value::Array(bank_files.into_iter().map(toml::Value::String).collect()),
+    );
+    fs::write(dir.join(MANIFEST_FILE), toml::to_string_pretty(&manifest)?)?;
+
+    Ok(())
+}
+
+/// Read a project directory written by `write_directory`
+fn read_directory(dir: &Path) -> Result<Project, Box<dyn Error>> {
+    let mut manifest: toml::Table = fs::read_to_string(dir.join(MANIFEST_FILE))?.parse()?;
+
+    // Replace the bank file list with the bank contents, in manifest order
+    let bank_files = match manifest.remove("banks") {
+        Some(toml::Value::Array(files)) => files,
+        Some(_) => return Err(format!("'banks' in {} must be a list of files", MANIFEST_FILE).into()),
+        None => Vec::new(),
+    };
+
+    let mut banks = Vec::with_capacity(bank_files.len());
+    for file in bank_files {
+        let relative = file
+            .as_str()
+            .ok_or_else(|| format!("'banks' in {} must be a list of files", MANIFEST_FILE))?;
+        let bank: toml::Table = fs::read_to_string(dir.join(relative))
+            .map_err(|e| format!("Failed to read bank file {}: {}", relative, e))?
+            .parse::<toml::Table>()
+            .map_err(|e| format!("Failed to parse bank file {}: {}", relative, e))?;
+        banks.push(toml::Value::Table(bank));
+    }
+    manifest.insert("banks".to_string(), toml::Value::Array(banks));
+
+    // A project without a parameters file simply has no parameters yet
+    let parameters_path = dir.join(PARAMETERS_FILE);
+    let parameters = if parameters_path.exists() {
+        let mut table: toml::Table = fs::read_to_string(&parameters_path)?.parse()?;
+        table
+            .remove("parameters")
+            .unwrap_or_else(|| toml::Value::Array(Vec::new()))
+    } else {
+        toml::Value::Array(Vec::new())
+    };
+    manifest.insert("parameters".to_string(), parameters);
+
+    Ok(toml::Value::Table(manifest).try_into()?)
+}
+
+/// Turn a name into a file-name friendly slug, or `fallback` if nothing is left of it
+pub(crate) fn slugify(name: &str, fallback: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for ch in name.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    let slug = slug.trim_matches('-').to_string();
+    if slug.is_empty() {
+        fallback.to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curves::{CurvePoint, CurveShape, UserCurve};
+    use crate::events::{LaunchQuantize, MorphDuration};
+    use crate::model::{
+        Bank, ChainStep, InterruptPolicy, JumpPoint, LaunchQuantization, ManualControl, MorphChain, MorphTiming,
+        Parameter, ParameterMotion, ParameterTiming, Snap,
+    };
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("snapblaster-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn sample_project() -> Project {
+        let mut project = Project {
+            project_name: "Round Trip".to_string(),
+            parameters: vec![Parameter {
+                name: "Cutoff".to_string(),
+                description: "Filter cutoff".to_string(),
+                cc: 74,
+                morph_timing: None,
+                motion: Default::default(),
+            }],
+            ..Project::default()
+        };
+        project.banks[0].snaps[0].values = vec![12];
+        project.banks.push(Bank {
+            name: "Drop / Build!".to_string(),
+            snaps: vec![Snap {
+                name: "Peak".to_string(),
+                description: String::new(),
+                values: vec![127],
+                morph_timings: Vec::new(),
+            }],
+            launch_quantization: None,
+        });
+        project
+    }
+
+    /// A project using every setting the formats have to keep
+    fn full_project() -> Project {
+        let mut project = sample_project();
+        project.openai_api_key = Some("sk-test".to_string());
+        project.parameters[0].morph_timing = Some(MorphTiming {
+            start: 0.25,
+            end: 0.75,
+            curve: Some("ease".to_string()),
+        });
+        project.parameters.push(Parameter {
+            name: "Waveform".to_string(),
+            description: String::new(),
+            cc: 20,
+            morph_timing: None,
+            motion: ParameterMotion::Discrete { at: JumpPoint::End },
+        });
+        project.parameters.push(Parameter {
+            name: "Octave".to_string(),
+            description: String::new(),
+            cc: 21,
+            morph_timing: None,
+            motion: ParameterMotion::Stepped { steps: 5 },
+        });
+        for bank in &mut project.banks {
+            for snap in &mut bank.snaps {
+                snap.values.extend([0, 127]);
+            }
+        }
+        project.banks[1].snaps[0].morph_timings = vec![ParameterTiming {
+            param_id: 1,
+            timing: MorphTiming {
+                start: 0.0,
+                end: 0.5,
+                curve: None,
+            },
+        }];
+        project.banks[1].launch_quantization = Some(LaunchQuantization {
+            morph: LaunchQuantize::Phrase(4),
+            recall: LaunchQuantize::Beat,
+        });
+
+        project.curves = vec![
+            UserCurve {
+                id: "ease".to_string(),
+                name: "Ease".to_string(),
+                shape: CurveShape::Bezier {
+                    x1: 0.42,
+                    y1: 0.0,
+                    x2: 0.58,
+                    y2: 1.0,
+                },
+            },
+            UserCurve {
+                id: "swell".to_string(),
+                name: "Swell".to_string(),
+                shape: CurveShape::Breakpoints {
+                    points: vec![
+                        CurvePoint { x: 0.0, y: 0.0 },
+                        CurvePoint { x: 0.8, y: 0.2 },
+                        CurvePoint { x: 1.0, y: 1.0 },
+                    ],
+                },
+            },
+            UserCurve {
+                id: "stairs".to_string(),
+                name: "Stairs".to_string(),
+                shape: CurveShape::Stepped { steps: 4 },
+            },
+            UserCurve {
+                id: "late".to_string(),
+                name: "Late".to_string(),
+                shape: CurveShape::HoldThenJump { at: 0.9 },
+            },
+        ];
+        project.chains = vec![MorphChain {
+            id: "build".to_string(),
+            name: "Build".to_string(),
+            steps: vec![
+                ChainStep {
+                    to_snap: 0,
+                    duration: MorphDuration::Bars(0.5),
+                    curve: Some("swell".to_string()),
+                    hold: Some(MorphDuration::Beats(3.0)),
+                },
+                ChainStep {
+                    to_snap: 0,
+                    duration: MorphDuration::Millis(750),
+                    curve: None,
+                    hold: None,
+                },
+            ],
+            pad: Some(55),
+        }];
+        project.recall_crossfade_ms = 250;
+        project.interrupt_policy = InterruptPolicy::FinishThenGo;
+        project.launch_quantization = LaunchQuantization {
+            morph: LaunchQuantize::Phrase(16),
+            recall: LaunchQuantize::Bar,
+        };
+        project.manual_control = ManualControl {
+            cc: Some(1),
+            channel: Some(15),
+            pressure: true,
+        };
+        project
+    }
+
+    #[test]
+    fn directory_round_trip_keeps_the_project() {
+        let dir = scratch_dir("round-trip");
+        let project = full_project();
+
+        write_directory(&project, &dir).unwrap();
+        let read = read_project(&dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&project).unwrap()
+        );
+    }
+
+    #[test]
+    fn toml_file_round_trip_keeps_the_project() {
+        let dir = scratch_dir("toml-file");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("set.toml");
+        let project = full_project();
+
+        assert_eq!(ProjectFormat::for_path(&path), ProjectFormat::Toml);
+        write_toml(&project, &path).unwrap();
+        let read = read_project(&path).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&project).unwrap()
+        );
+    }
+
+    #[test]
+    fn directory_save_removes_stale_bank_files() {
+        let dir = scratch_dir("stale-banks");
+        let mut project = sample_project();
+
+        write_directory(&project, &dir).unwrap();
+        project.banks.pop();
+        write_directory(&project, &dir).unwrap();
+        let bank_files = fs::read_dir(dir.join(BANKS_DIR)).unwrap().count();
+        let read = read_project(&dir.join(MANIFEST_FILE)).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(bank_files, 1);
+        assert_eq!(read.banks.len(), 1);
+    }
+}
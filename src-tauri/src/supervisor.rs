@@ -0,0 +1,279 @@
+// src-tauri/src/supervisor.rs
+use crate::events::{Event, EventBus, EventSubscriber};
+use std::future::Future;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+/// Delay before restarting a handler that failed, multiplied by the restart count
+const RESTART_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Upper bound for the restart delay
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Restarts in a row after which a handler is given up on, e.g. when it keeps
+/// panicking on a poisoned lock
+const MAX_RESTARTS: u32 = 10;
+
+/// A handler that ran this long before panicking counts as recovered, restarting the count
+const HEALTHY_RUN: Duration = Duration::from_secs(60);
+
+/// What the supervisor should do after a handler processed an event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    /// Keep receiving events
+    Continue,
+    /// Stop the subscription for good
+    Stop,
+}
+
+/// A long-running consumer of bus events
+pub trait EventHandler: Send + 'static {
+    /// Handle one event
+    fn handle(&mut self, event: Event) -> impl Future<Output = Flow> + Send;
+
+    /// Rebuild any state derived from events after `missed` events were dropped
+    /// because the subscription lagged, or after the handler was restarted (`missed` is 0)
+    fn resync(&mut self, missed: u64) -> impl Future<Output = ()> + Send {
+        let _ = missed;
+        async {}
+    }
+}
+
+/// Run a handler on its own subscription, recovering from lag and restarting it
+/// with a fresh instance from `factory` if it panics. The returned task ends when the
+/// handler stops, the bus closes, the supervisor is aborted or the handler keeps
+/// panicking after `MAX_RESTARTS` restarts.
+pub fn supervise<H, F>(event_bus: &EventBus, name: &str, factory: F) -> JoinHandle<()>
+where
+    H: EventHandler,
+    F: FnMut() -> H + Send + 'static,
+{
+    spawn_supervisor(event_bus, name, false, factory)
+}
+
+/// Like [`supervise`], but the handler only receives events from the real-time lane
+/// and so never waits behind normal traffic
+pub fn supervise_realtime<H, F>(event_bus: &EventBus, name: &str, factory: F) -> JoinHandle<()>
+where
+    H: EventHandler,
+    F: FnMut() -> H + Send + 'static,
+{
+    spawn_supervisor(event_bus, name, true, factory)
+}
+
+fn spawn_supervisor<H, F>(
+    event_bus: &EventBus,
+    name: &str,
+    realtime_only: bool,
+    mut factory: F,
+) -> JoinHandle<()>
+where
+    H: EventHandler,
+    F: FnMut() -> H + Send + 'static,
+{
+    let event_bus = event_bus.clone();
+    let name = name.to_string();
+
+    tokio::spawn(async move {
+        let mut restarts: u32 = 0;
+
+        loop {
+            // Subscribe before handing over so nothing published after this point is missed
+            let subscriber = if realtime_only {
+                EventSubscriber::realtime(&event_bus, &name)
+            } else {
+                EventSubscriber::new(&event_bus, &name)
+            };
+            let handler = factory();
+            let started = Instant::now();
+            let task = tokio::spawn(run_handler(subscriber, handler, restarts > 0));
+
+            // Aborting the supervisor also has to stop the handler it is waiting on
+            let abort_handle = task.abort_handle();
+            let _guard = AbortOnDrop(abort_handle);
+
+            match task.await {
+                Ok(()) => break,
+                Err(e) if e.is_panic() => {
+                    if started.elapsed() >= HEALTHY_RUN {
+                        restarts = 0;
+                    }
+                    if restarts >= MAX_RESTARTS {
+                        error!(handler = %name, "Handler panicked {} times in a row, giving up", restarts + 1);
+                        break;
+                    }
+
+                    restarts += 1;
+                    event_bus.stats().handler_restarts.fetch_add(1, Ordering::Relaxed);
+
+                    let backoff = (RESTART_BACKOFF * restarts).min(MAX_RESTART_BACKOFF);
+                    error!(handler = %name, "Handler panicked, restarting in {:?} (restart #{})", backoff, restarts);
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(_) => break,
+            }
+        }
+
+        info!(handler = %name, "Supervised handler finished");
+    })
+}
+
+/// Receive events until the handler stops or the bus closes
+async fn run_handler<H: EventHandler>(mut subscriber: EventSubscriber, mut handler: H, restarted: bool) {
+    // A restarted handler starts from scratch and may have missed anything
+    if restarted {
+        handler.resync(0).await;
+    }
+
+    loop {
+        match subscriber.recv().await {
+            Ok(event) => {
+                let flow = handler.handle(event).await;
+                subscriber.record_handled();
+                if flow == Flow::Stop {
+                    break;
+                }
+            }
+            Err(RecvError::Lagged(missed)) => {
+                warn!(handler = %subscriber.name(), "Resyncing after dropping {} events", missed);
+                handler.resync(missed).await;
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Aborts a task when dropped
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// What the handlers of one supervisor saw, across restarts
+    #[derive(Default)]
+    struct Log {
+        started: Vec<tokio::time::Instant>,
+        handled: usize,
+        resyncs: Vec<u64>,
+    }
+
+    /// Panics on its first event when `fragile`, and takes `busy` to handle each event
+    struct TestHandler {
+        log: Arc<Mutex<Log>>,
+        fragile: bool,
+        busy: Duration,
+    }
+
+    impl EventHandler for TestHandler {
+        async fn handle(&mut self, _event: Event) -> Flow {
+            if self.fragile {
+                panic!("test handler failed");
+            }
+            tokio::time::sleep(self.busy).await;
+            self.log.lock().unwrap().handled += 1;
+            Flow::Continue
+        }
+
+        async fn resync(&mut self, missed: u64) {
+            self.log.lock().unwrap().resyncs.push(missed);
+        }
+    }
+
+    /// Supervise handlers built by `fragile`, which is told how many came before
+    fn start(
+        event_bus: &EventBus,
+        busy: Duration,
+        mut fragile: impl FnMut(usize) -> bool + Send + 'static,
+    ) -> (Arc<Mutex<Log>>, JoinHandle<()>) {
+        let log = Arc::new(Mutex::new(Log::default()));
+        let factory_log = log.clone();
+        let supervisor = supervise(event_bus, "test", move || {
+            let mut log = factory_log.lock().unwrap();
+            log.started.push(tokio::time::Instant::now());
+            TestHandler {
+                log: factory_log.clone(),
+                fragile: fragile(log.started.len() - 1),
+                busy,
+            }
+        });
+        (log, supervisor)
+    }
+
+    /// Publish an event every 10ms until the supervisor finishes
+    async fn feed(event_bus: &EventBus, supervisor: &JoinHandle<()>) {
+        while !supervisor.is_finished() {
+            let _ = event_bus.publish(Event::MorphCompleted);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn panicking_handlers_are_restarted_with_growing_backoff_then_given_up() {
+        let event_bus = EventBus::new(16, "test");
+        let (log, supervisor) = start(&event_bus, Duration::ZERO, |_| true);
+        feed(&event_bus, &supervisor).await;
+
+        assert_eq!(event_bus.stats().handler_restarts.load(Ordering::Relaxed), MAX_RESTARTS as usize);
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.started.len(), MAX_RESTARTS as usize + 1);
+        for (restart, gap) in log.started.windows(2).map(|w| w[1] - w[0]).enumerate() {
+            let backoff = (RESTART_BACKOFF * (restart as u32 + 1)).min(MAX_RESTART_BACKOFF);
+            // Plus the wait for the next event to panic on
+            assert!(gap >= backoff && gap <= backoff + Duration::from_millis(20), "restart {}: {:?}", restart, gap);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn restarted_handlers_resync_and_keep_receiving() {
+        let event_bus = EventBus::new(16, "test");
+        let (log, supervisor) = start(&event_bus, Duration::ZERO, |instance| instance == 0);
+
+        tokio::task::yield_now().await;
+        event_bus.publish(Event::MorphCompleted).unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        for _ in 0..3 {
+            event_bus.publish(Event::MorphCompleted).unwrap();
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!supervisor.is_finished());
+        supervisor.abort();
+
+        assert_eq!(event_bus.stats().handler_restarts.load(Ordering::Relaxed), 1);
+        let log = log.lock().unwrap();
+        assert_eq!(log.started.len(), 2);
+        assert_eq!(log.resyncs, vec![0]);
+        assert_eq!(log.handled, 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lagging_handlers_resync_with_the_missed_count() {
+        let event_bus = EventBus::new(2, "test");
+        let (log, supervisor) = start(&event_bus, Duration::from_secs(1), |_| false);
+        tokio::task::yield_now().await;
+
+        // The handler takes the first event and is busy while seven more overflow the lane
+        for _ in 0..8 {
+            event_bus.publish(Event::MorphCompleted).unwrap();
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        supervisor.abort();
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.started.len(), 1);
+        assert_eq!(log.resyncs, vec![5]);
+        assert_eq!(log.handled, 3);
+    }
+}
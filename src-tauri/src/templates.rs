@@ -0,0 +1,290 @@
+// src-tauri/src/templates.rs
+use crate::model::{Bank, Parameter, ParameterMotion, Project, Snap};
+use crate::storage::slugify;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Name of the template used when none is given
+pub const DEFAULT_TEMPLATE: &str = "Empty";
+
+/// Value snaps are reset to when a template drops its values
+const RESET_VALUE: u8 = 64;
+
+/// A reusable starting point for new projects
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    /// Name shown when picking a template
+    pub name: String,
+
+    /// What the template is for
+    pub description: String,
+
+    /// Project settings, parameters and bank structure to start from
+    pub project: Project,
+}
+
+/// Summary of a template for listing
+#[derive(Clone, Debug, Serialize)]
+pub struct TemplateInfo {
+    pub name: String,
+    pub description: String,
+    pub builtin: bool,
+}
+
+impl ProjectTemplate {
+    /// Capture a template from an existing project.
+    /// With `reset_values` every snap keeps its name but its values go back to the middle.
+    pub fn from_project(name: &str, description: &str, project: &Project, reset_values: bool) -> Self {
+        let mut project = project.clone();
+
+        // Credentials never travel with a template
+        project.openai_api_key = None;
+
+        if reset_values {
+            let param_count = project.parameters.len();
+            for bank in &mut project.banks {
+                for snap in &mut bank.snaps {
+                    snap.values = vec![RESET_VALUE; param_count];
+                }
+            }
+        }
+
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            project,
+        }
+    }
+
+    /// Create a new project from this template
+    pub fn instantiate(&self) -> Project {
+        let mut project = self.project.clone();
+        project.project_name = "New Project".to_string();
+        project
+    }
+}
+
+/// Templates that ship with the app
+pub fn builtin_templates() -> Vec<ProjectTemplate> {
+    vec![
+        ProjectTemplate {
+            name: DEFAULT_TEMPLATE.to_string(),
+            description: "One bank with a single snap and no parameters".to_string(),
+            project: Project::default(),
+        },
+        template_with(
+            "Performance FX",
+            "Filter, space and drive sends for live effects on a group or master bus",
+            &[
+                ("Filter Cutoff", "Low-pass filter cutoff", 74),
+                ("Filter Resonance", "Low-pass filter resonance", 71),
+                ("High-pass", "High-pass filter cutoff", 75),
+                ("Reverb Send", "Reverb send level", 91),
+                ("Delay Send", "Delay send level", 94),
+                ("Delay Feedback", "Delay feedback amount", 95),
+                ("Drive", "Saturation amount", 76),
+                ("Dry/Wet", "Overall effect mix", 77),
+            ],
+            &["Dry", "Build", "Wash", "Drop"],
+        ),
+        template_with(
+            "Synth Macros",
+            "Eight macro knobs on CC 16-23, as used by most instrument racks",
+            &[
+                ("Macro 1", "Instrument macro 1", 16),
+                ("Macro 2", "Instrument macro 2", 17),
+                ("Macro 3", "Instrument macro 3", 18),
+                ("Macro 4", "Instrument macro 4", 19),
+                ("Macro 5", "Instrument macro 5", 20),
+                ("Macro 6", "Instrument macro 6", 21),
+                ("Macro 7", "Instrument macro 7", 22),
+                ("Macro 8", "Instrument macro 8", 23),
+            ],
+            &["Intro", "Verse", "Chorus", "Outro"],
+        ),
+    ]
+}
+
+/// Build a one-bank template from parameter definitions and snap names
+fn template_with(
+    name: &str,
+    description: &str,
+    parameters: &[(&str, &str, u8)],
+    snap_names: &[&str],
+) -> ProjectTemplate {
+    let parameters: Vec<Parameter> = parameters
+        .iter()
+        .map(|(name, description, cc)| Parameter {
+            name: name.to_string(),
+            description: description.to_string(),
+            cc: *cc,
+            morph_timing: None,
+            motion: ParameterMotion::Continuous,
+        })
+        .collect();
+
+    let snaps = snap_names
+        .iter()
+        .map(|snap_name| Snap {
+            name: snap_name.to_string(),
+            description: String::new(),
+            values: vec![RESET_VALUE; parameters.len()],
+            morph_timings: Vec::new(),
+        })
+        .collect();
+
+    ProjectTemplate {
+        name: name.to_string(),
+        description: description.to_string(),
+        project: Project {
+            banks: vec![Bank {
+                name: "Default Bank".to_string(),
+                snaps,
+                launch_quantization: None,
+            }],
+            parameters,
+            ..Project::default()
+        },
+    }
+}
+
+/// TemplateStore keeps user templates as JSON files in a directory
+#[derive(Clone, Debug)]
+pub struct TemplateStore {
+    dir: PathBuf,
+}
+
+impl TemplateStore {
+    /// Create a store for the given directory
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// List built-in and user templates; user templates shadow built-ins with the same name
+    pub fn list(&self) -> Vec<TemplateInfo> {
+        let user = self.user_templates();
+
+        let builtin: Vec<TemplateInfo> = builtin_templates()
+            .into_iter()
+            .filter(|t| !user.iter().any(|u| u.name == t.name))
+            .map(|t| TemplateInfo {
+                name: t.name,
+                description: t.description,
+                builtin: true,
+            })
+            .collect();
+
+        builtin
+            .into_iter()
+            .chain(user.into_iter().map(|t| TemplateInfo {
+                name: t.name,
+                description: t.description,
+                builtin: false,
+            }))
+            .collect()
+    }
+
+    /// Find a template by name, preferring user templates
+    pub fn find(&self, name: &str) -> Option<ProjectTemplate> {
+        self.user_templates()
+            .into_iter()
+            .find(|t| t.name == name)
+            .or_else(|| builtin_templates().into_iter().find(|t| t.name == name))
+    }
+
+    /// Save a user template, replacing any with the same name
+    pub fn save(&self, template: &ProjectTemplate) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(&template.name);
+        fs::write(&path, serde_json::to_string_pretty(template)?)?;
+        debug!("Saved template '{}' to {:?}", template.name, path);
+        Ok(())
+    }
+
+    /// The file holding the template with this name, or else a free one.
+    /// Names that slugify alike get a numbered suffix instead of overwriting each other.
+    fn path_for(&self, name: &str) -> PathBuf {
+        let slug = slugify(name, "template");
+
+        for suffix in 1.. {
+            let file_name = match suffix {
+                1 => format!("{}.json", slug),
+                n => format!("{}-{}.json", slug, n),
+            };
+            let path = self.dir.join(file_name);
+            if !path.exists() || read_template(&path).is_some_and(|existing| existing.name == name) {
+                return path;
+            }
+        }
+        unreachable!("ran out of template file names")
+    }
+
+    fn user_templates(&self) -> Vec<ProjectTemplate> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut templates: Vec<ProjectTemplate> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let contents = fs::read_to_string(&path).ok()?;
+                match serde_json::from_str(&contents) {
+                    Ok(template) => Some(template),
+                    Err(e) => {
+                        warn!("Skipping unreadable template {:?}: {}", path, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        templates
+    }
+}
+
+fn read_template(path: &Path) -> Option<ProjectTemplate> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_that_slugify_alike_get_their_own_files() {
+        let dir = std::env::temp_dir().join(format!("snapblaster-templates-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = TemplateStore::new(dir.clone());
+        let template = |name: &str, description: &str| ProjectTemplate {
+            name: name.to_string(),
+            description: description.to_string(),
+            project: Project::default(),
+        };
+
+        store.save(&template("Drum Kit", "first")).unwrap();
+        store.save(&template("drum-kit", "second")).unwrap();
+        store.save(&template("Drum Kit", "replaced")).unwrap();
+        let first = store.find("Drum Kit").map(|t| t.description);
+        let second = store.find("drum-kit").map(|t| t.description);
+        let files = fs::read_dir(&dir).unwrap().count();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(first.as_deref(), Some("replaced"));
+        assert_eq!(second.as_deref(), Some("second"));
+        assert_eq!(files, 2);
+    }
+
+    #[test]
+    fn templates_have_their_own_slug_fallback() {
+        let store = TemplateStore::new(PathBuf::from("/nonexistent"));
+
+        assert_eq!(store.path_for("!!!"), PathBuf::from("/nonexistent/template.json"));
+    }
+}
@@ -120,6 +120,28 @@ function setupEventListeners() {
         }
     });
 
+    // Pick up snaps added or edited outside this window, e.g. by an MCP agent
+    eventBus.on('snap-edited', async ({ bankId, snapId }) => {
+        const project = await api.getProject();
+        const snap = project && project.banks[bankId] && project.banks[bankId].snaps[snapId];
+        if (!snap || !appState.project || !appState.project.banks[bankId]) {
+            return;
+        }
+
+        appState.project.banks[bankId].snaps[snapId] = snap;
+
+        if (bankId === appState.currentBank) {
+            import('./grid.js').then(module => {
+                module.createGrid();
+            });
+        }
+        if (bankId === appState.currentBank && snapId === appState.currentSnap) {
+            import('./parameters.js').then(module => {
+                module.updateParameters();
+            });
+        }
+    });
+
     // Listen for AI generation completion
     eventBus.on('ai-generation-completed', ({ bankId, snapId, values }) => {
         console.log('Received AI generation completed event:', { bankId, snapId, valuesLength: values.length });
@@ -165,6 +165,13 @@ async function initializeListeners() {
                         }
                         break; // Add missing break statement here
 
+                    case 'SnapEdited':
+                        eventBus.emit('snap-edited', {
+                            bankId: eventData.bank_id,
+                            snapId: eventData.snap_id
+                        });
+                        break;
+
                     case 'AIGenerationFailed':
                         console.log('AI Generation failed event detected!', eventData);
 
@@ -244,7 +251,14 @@ async function initializeListeners() {
                     case 'MorphProgressed':
                         eventBus.emit('morph-progressed', {
                             progress: eventData.progress,
-                            currentValues: eventData.current_values
+                            currentValues: eventData.current_values,
+                            step: eventData.step
+                        });
+                        break;
+
+                    case 'ChainCompleted':
+                        eventBus.emit('chain-completed', {
+                            chainId: eventData.chain_id
                         });
                         break;
 
@@ -259,6 +273,17 @@ async function initializeListeners() {
                     case 'ProjectSaved':
                         eventBus.emit('project-saved');
                         break;
+
+                    case 'MetricsReported':
+                        eventBus.emit('metrics-reported', eventData.metrics);
+                        break;
+
+                    case 'ScriptsLoaded':
+                        eventBus.emit('scripts-loaded', {
+                            scripts: eventData.scripts,
+                            errors: eventData.errors
+                        });
+                        break;
                 }
             } catch (err) {
                 console.error('Error parsing event:', err);
@@ -294,6 +319,21 @@ async function initializeListeners() {
             }
         });
 
+        // The backend dropped events for us, so reload everything from the project
+        await listen('snap-resync', (event) => {
+            console.warn(`Resyncing after ${event.payload} missed events`);
+            eventBus.emit('project-loaded');
+        });
+
+        // Runtime state messages are either {Snapshot: state} or {Delta: {version, changes}}
+        await listen('runtime-state', (event) => {
+            try {
+                eventBus.emit('runtime-state', JSON.parse(event.payload));
+            } catch (err) {
+                console.error('Error parsing runtime state:', err);
+            }
+        });
+
         initialized = true;
         console.log("Tauri event listeners initialized");
     } catch (err) {
@@ -355,6 +395,29 @@ const api = {
         }
     },
 
+    // Choose the MIDI input that drives manual morphs; null closes it
+    async setControlInput(port) {
+        if (!tauriReady) {
+            return new Promise((resolve, reject) => {
+                whenTauriReady(async () => {
+                    try {
+                        await this.setControlInput(port);
+                        resolve();
+                    } catch (err) {
+                        reject(err);
+                    }
+                });
+            });
+        }
+
+        try {
+            await invoke('set_control_input', { port });
+        } catch (err) {
+            console.error('Error setting manual-control input:', err);
+            throw err;
+        }
+    },
+
     // Set current MIDI controller
     async setController(name) {
         if (!tauriReady) {
@@ -443,13 +506,13 @@ const api = {
         }
     },
 
-    // Create new project
-    async newProject() {
+    // Create new project, optionally from a named template
+    async newProject(template = null) {
         if (!tauriReady) {
             return new Promise((resolve, reject) => {
                 whenTauriReady(async () => {
                     try {
-                        await this.newProject();
+                        await this.newProject(template);
                         resolve();
                     } catch (err) {
                         reject(err);
@@ -459,20 +522,20 @@ const api = {
         }
 
         try {
-            await invoke('new_project');
+            await invoke('new_project', { template });
         } catch (err) {
             console.error('Error creating new project:', err);
             throw err;
         }
     },
 
-    // Select a snap
-    async selectSnap(bankId, snapId) {
+    // Select a snap; quantize ('none', 'beat', 'bar', '4bars', ...) overrides the bank's recall setting
+    async selectSnap(bankId, snapId, quantize = null) {
         if (!tauriReady) {
             return new Promise((resolve, reject) => {
                 whenTauriReady(async () => {
                     try {
-                        await this.selectSnap(bankId, snapId);
+                        await this.selectSnap(bankId, snapId, quantize);
                         resolve();
                     } catch (err) {
                         reject(err);
@@ -482,7 +545,7 @@ const api = {
         }
 
         try {
-            await invoke('select_snap', { bankId, snapId });
+            await invoke('select_snap', { bankId, snapId, quantize });
         } catch (err) {
             console.error('Error selecting snap:', err);
             throw err;
@@ -538,7 +601,7 @@ const api = {
         }
     },
 
-    // Start morph between snaps
+    // Start morph between snaps; the duration is a number of bars or a string such as '3/4bar', '2beats' or '500ms'
     async startMorph(fromSnap, toSnap, durationBars, curveType, quantize = false) {
         if (!tauriReady) {
             return new Promise((resolve, reject) => {
@@ -554,13 +617,230 @@ const api = {
         }
 
         try {
-            await invoke('start_morph', { fromSnap, toSnap, durationBars, curveType, quantize });
+            const duration = typeof durationBars === 'string' ? durationBars : null;
+            await invoke('start_morph', {
+                fromSnap,
+                toSnap,
+                durationBars: duration === null ? durationBars : 0,
+                duration,
+                curveType,
+                quantize
+            });
         } catch (err) {
             console.error('Error starting morph:', err);
             throw err;
         }
     },
 
+    // List built-in curve IDs and the project's user curves
+    async getCurves() {
+        try {
+            const curvesJson = await invoke('get_curves');
+            return JSON.parse(curvesJson);
+        } catch (err) {
+            console.error('Error getting curves:', err);
+            throw err;
+        }
+    },
+
+    // Add or replace a user curve: { id, name, shape: { kind, ... } }
+    async saveCurve(curve) {
+        try {
+            await invoke('save_curve', { curve });
+        } catch (err) {
+            console.error('Error saving curve:', err);
+            throw err;
+        }
+    },
+
+    // Remove a user curve
+    async deleteCurve(id) {
+        try {
+            await invoke('delete_curve', { id });
+        } catch (err) {
+            console.error('Error deleting curve:', err);
+            throw err;
+        }
+    },
+
+    // List the project's morph chains
+    async getChains() {
+        try {
+            const chainsJson = await invoke('get_chains');
+            return JSON.parse(chainsJson);
+        } catch (err) {
+            console.error('Error getting chains:', err);
+            throw err;
+        }
+    },
+
+    // Add or replace a morph chain: { id, name, steps: [{ to_snap, duration, curve?, hold? }], pad? }
+    async saveChain(chain) {
+        try {
+            await invoke('save_chain', { chain });
+        } catch (err) {
+            console.error('Error saving chain:', err);
+            throw err;
+        }
+    },
+
+    // Remove a morph chain
+    async deleteChain(id) {
+        try {
+            await invoke('delete_chain', { id });
+        } catch (err) {
+            console.error('Error deleting chain:', err);
+            throw err;
+        }
+    },
+
+    // Run a morph chain from the current snap
+    async startChain(id, quantize = null) {
+        try {
+            await invoke('start_chain', { id, quantize });
+        } catch (err) {
+            console.error('Error starting chain:', err);
+            throw err;
+        }
+    },
+
+    // Sample a curve by ID as [{ x, y }] points for drawing
+    async sampleCurve(id, count = 64) {
+        try {
+            const pointsJson = await invoke('sample_curve', { id, count });
+            return JSON.parse(pointsJson);
+        } catch (err) {
+            console.error('Error sampling curve:', err);
+            throw err;
+        }
+    },
+
+    // Sample a curve shape that has not been saved yet
+    async sampleCurveShape(shape, count = 64) {
+        try {
+            const pointsJson = await invoke('sample_curve_shape', { shape, count });
+            return JSON.parse(pointsJson);
+        } catch (err) {
+            console.error('Error sampling curve shape:', err);
+            throw err;
+        }
+    },
+
+    // Set when a parameter moves during morphs: { start, end, curve } or null to reset
+    async setParameterMorphTiming(paramId, timing) {
+        try {
+            await invoke('set_parameter_morph_timing', { paramId, timing });
+        } catch (err) {
+            console.error('Error setting parameter morph timing:', err);
+            throw err;
+        }
+    },
+
+    // Set how a parameter moves during morphs: { mode: 'continuous' },
+    // { mode: 'discrete', at: 'start' | 'middle' | 'end' } or { mode: 'stepped', steps: 4 },
+    // where steps is the number of fixed positions across 0-127 (2 to 128)
+    async setParameterMotion(paramId, motion) {
+        try {
+            await invoke('set_parameter_motion', { paramId, motion });
+        } catch (err) {
+            console.error('Error setting parameter motion:', err);
+            throw err;
+        }
+    },
+
+    // Override a parameter's morph timing for morphs to one snap, or null to remove it
+    async setSnapMorphTiming(bankId, snapId, paramId, timing) {
+        try {
+            await invoke('set_snap_morph_timing', { bankId, snapId, paramId, timing });
+        } catch (err) {
+            console.error('Error setting snap morph timing:', err);
+            throw err;
+        }
+    },
+
+    // Loop between the current snap and another: mode is 'restart' or 'ping_pong', period like '2bars' or '750ms'
+    async startLoop(toSnap, period, mode, repeats = null, curveType = 'linear', quantize = null) {
+        try {
+            await invoke('start_loop', { toSnap, period, mode, repeats, curveType, quantize });
+        } catch (err) {
+            console.error('Error starting loop:', err);
+            throw err;
+        }
+    },
+
+    // Stop the running morph, loop or chain where it is
+    async stopMorph() {
+        try {
+            await invoke('stop_morph');
+        } catch (err) {
+            console.error('Error stopping morph:', err);
+            throw err;
+        }
+    },
+
+    // Start a manual morph to a snap; its position then comes from setMorphPosition,
+    // the bound fader or pad pressure
+    async startManualMorph(toSnap, curveType = 'linear') {
+        try {
+            await invoke('start_manual_morph', { toSnap, curveType });
+        } catch (err) {
+            console.error('Error starting manual morph:', err);
+            throw err;
+        }
+    },
+
+    // Scrub the running morph to a position from 0 to 1
+    async setMorphPosition(position) {
+        try {
+            await invoke('set_morph_position', { position });
+        } catch (err) {
+            console.error('Error setting morph position:', err);
+            throw err;
+        }
+    },
+
+    // Choose what drives manual morphs: { cc, channel, pressure }
+    async setManualControl(manualControl) {
+        try {
+            await invoke('set_manual_control', { manualControl });
+        } catch (err) {
+            console.error('Error setting manual control:', err);
+            throw err;
+        }
+    },
+
+    // Set the crossfade when recalling a snap in milliseconds; 0 recalls instantly
+    async setRecallCrossfade(crossfadeMs) {
+        try {
+            await invoke('set_recall_crossfade', { crossfadeMs });
+        } catch (err) {
+            console.error('Error setting recall crossfade:', err);
+            throw err;
+        }
+    },
+
+    // Set how a running morph reacts to a new morph or recall: 'retarget', 'finish_then_go', 'jump' or 'ignore'
+    async setInterruptPolicy(policy) {
+        try {
+            await invoke('set_interrupt_policy', { policy });
+        } catch (err) {
+            console.error('Error setting interrupt policy:', err);
+            throw err;
+        }
+    },
+
+    // Set when morphs and recalls launch: { morph, recall }, each 'none', 'beat', 'bar',
+    // '2bars', '4bars', '8bars' or '16bars'. With a bank ID it applies to that bank;
+    // null there makes the bank follow the project again.
+    async setLaunchQuantization(launchQuantization, bankId = null) {
+        try {
+            await invoke('set_launch_quantization', { bankId, launchQuantization });
+        } catch (err) {
+            console.error('Error setting launch quantization:', err);
+            throw err;
+        }
+    },
+
     // Set OpenAI API key
     async setOpenAIApiKey(apiKey) {
         if (!tauriReady) {
@@ -723,6 +1003,49 @@ const api = {
         }
     },
 
+    // Get event bus metrics
+    async getEventMetrics() {
+        try {
+            const metricsJson = await invoke('get_event_metrics');
+            return JSON.parse(metricsJson);
+        } catch (err) {
+            console.error('Error getting event metrics:', err);
+            throw err;
+        }
+    },
+
+    // Get the runtime state snapshot
+    async getRuntimeState() {
+        try {
+            const stateJson = await invoke('get_runtime_state');
+            return JSON.parse(stateJson);
+        } catch (err) {
+            console.error('Error getting runtime state:', err);
+            throw err;
+        }
+    },
+
+    // Get the runtime state deltas after a version, or a snapshot if they are gone
+    async getRuntimeDeltas(since) {
+        try {
+            const messagesJson = await invoke('get_runtime_deltas', { since });
+            return JSON.parse(messagesJson);
+        } catch (err) {
+            console.error('Error getting runtime deltas:', err);
+            throw err;
+        }
+    },
+
+    // Reset event bus metrics
+    async resetEventMetrics() {
+        try {
+            await invoke('reset_event_metrics');
+        } catch (err) {
+            console.error('Error resetting event metrics:', err);
+            throw err;
+        }
+    },
+
     // Set Link tempo
     async setLinkTempo(tempo) {
         if (!tauriReady) {